@@ -0,0 +1,30 @@
+use bencher::{benchmark_group, benchmark_main, Bencher};
+use chrono::NaiveDate;
+
+const EMAIL: &str = "Hi team, following up on our discussion from last week. The quarterly \
+review is planned for next friday at 3pm, and the budget draft is due in 2 weeks. Please send \
+your updates before tomorrow evening. I may be out of office on monday, so reach out to Anna if \
+anything comes up. Thanks, and have a great weekend!\n";
+
+fn parse(bench: &mut Bencher, text: &str) {
+    let now = NaiveDate::from_ymd(2018, 8, 3).and_hms(12, 34, 56);
+    let parser = when::Parser::new(chrono_tz::Europe::Moscow);
+    bench.bytes = text.len() as u64;
+    bench.iter(|| parser.parse_fixed_time(now, text));
+}
+
+fn short_sentence(bench: &mut Bencher) {
+    parse(bench, "let's meet next friday at 3pm");
+}
+
+fn email(bench: &mut Bencher) {
+    parse(bench, EMAIL);
+}
+
+// about 5 kilobytes
+fn long_email(bench: &mut Bencher) {
+    parse(bench, &EMAIL.repeat(16));
+}
+
+benchmark_group!(benches, short_sentence, email, long_email);
+benchmark_main!(benches);
@@ -0,0 +1,127 @@
+//! Aliases teach parser extra words, e.g. slang or company jargon, by mapping them to tokens
+//! built-in rules already understand:
+//!
+//! ```
+//! use when::aliases::{chat_slang, Alias, Token, When};
+//!
+//! let parser = when::Parser::new(chrono_tz::Europe::Moscow)
+//!     .aliases(chat_slang())
+//!     .alias(Alias::new("morgen", Token::When(When::Tomorrow), 1));
+//! assert_eq!(parser.parse("see you tmrw at 5pm").len(), 1);
+//! ```
+//!
+//! Note that some words are represented by different tokens in different rules, e.g. "week" is
+//! `Token::Week` in "friday next week" and `Token::TimeInterval(TimeInterval::Week)` in
+//! "in 2 weeks", so an alias should be added for each of them.
+
+pub use crate::rules::tokens::{
+    AmPm, Month, Prepositions, TimeInterval, TimeOfDay, Token, Weekday, When,
+};
+use std::collections::HashMap;
+use strsim::damerau_levenshtein;
+
+/// Extra surface form of a token, e.g. "tmrw" for `When::Tomorrow`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alias {
+    /// single word, lowercase
+    pub surface: String,
+    pub token: Token,
+    /// maximum number of typos, ignored when fuzzy parsing is off
+    pub max_dist: usize,
+}
+
+impl Alias {
+    pub fn new(surface: &str, token: Token, max_dist: usize) -> Self {
+        Alias {
+            surface: surface.to_lowercase().replace(".", ""),
+            token,
+            max_dist,
+        }
+    }
+}
+
+/// Aliases registered in a parser, looked up by surface form first and only then by typos
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AliasIndex {
+    // surface form -> tokens it stands for
+    exact: HashMap<String, Vec<Token>>,
+    // token -> surface forms with typos allowed and their maximum number
+    fuzzy: HashMap<Token, Vec<(String, usize)>>,
+}
+
+impl AliasIndex {
+    pub fn add(&mut self, alias: Alias) {
+        if alias.max_dist > 0 {
+            self.fuzzy
+                .entry(alias.token.clone())
+                .or_default()
+                .push((alias.surface.clone(), alias.max_dist));
+        }
+        self.exact
+            .entry(alias.surface)
+            .or_default()
+            .push(alias.token);
+    }
+
+    /// Returns the smallest distance between the word and an alias of the token
+    pub fn find(&self, word: &str, token: &Token, exact_match: bool) -> Option<usize> {
+        if self
+            .exact
+            .get(word)
+            .iter()
+            .any(|tokens| tokens.contains(token))
+        {
+            return Some(0);
+        }
+        if exact_match {
+            return None;
+        }
+
+        self.fuzzy
+            .get(token)?
+            .iter()
+            .map(|(surface, max_dist)| (damerau_levenshtein(word, surface), *max_dist))
+            .filter(|(dist, max_dist)| dist <= max_dist)
+            .map(|(dist, _)| dist)
+            .min()
+    }
+}
+
+/// Common chat abbreviations, e.g. "tmrw", "2moro", "nxt wk", "hrs"
+pub fn chat_slang() -> Vec<Alias> {
+    let when = |word, when| Alias::new(word, Token::When(when), 0);
+    let interval = |word, interval| Alias::new(word, Token::TimeInterval(interval), 0);
+    let weekday = |word, weekday| Alias::new(word, Token::Weekday(weekday), 0);
+
+    vec![
+        when("tmrw", When::Tomorrow),
+        when("tmrrw", When::Tomorrow),
+        when("tmw", When::Tomorrow),
+        when("2moro", When::Tomorrow),
+        when("2morrow", When::Tomorrow),
+        when("2day", When::Today),
+        when("tdy", When::Today),
+        when("tonite", When::Tonight),
+        when("2nite", When::Tonight),
+        when("yday", When::Yesterday),
+        when("ystrdy", When::Yesterday),
+        when("nxt", When::Next),
+        when("lst", When::Last),
+        Alias::new("wk", Token::Week, 0),
+        interval("wk", TimeInterval::Week),
+        interval("wks", TimeInterval::Week),
+        interval("hr", TimeInterval::Hour),
+        interval("hrs", TimeInterval::Hour),
+        interval("mins", TimeInterval::Minute),
+        interval("sec", TimeInterval::Second),
+        interval("secs", TimeInterval::Second),
+        interval("yr", TimeInterval::Year),
+        interval("yrs", TimeInterval::Year),
+        weekday("tues", Weekday::Tuesday),
+        weekday("weds", Weekday::Wednesday),
+        weekday("thu", Weekday::Thursday),
+        weekday("thurs", Weekday::Thursday),
+        Alias::new("arvo", Token::TimeOfDay(TimeOfDay::Afternoon), 0),
+        Alias::new("b4", Token::Prepositions(Prepositions::Before), 0),
+    ]
+}
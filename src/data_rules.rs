@@ -0,0 +1,582 @@
+//! Rules defined by data files instead of code, so new phrases and languages can be shipped
+//! without rebuilding the crate. A rule is a sequence of pattern elements and a list of actions
+//! applied to the matched text:
+//!
+//! ```
+//! use when::data_rules::RuleSet;
+//!
+//! let rules = RuleSet::from_toml(r#"
+//!     [[rules]]
+//!     name = "de::in_days"
+//!     pattern = [
+//!         { words = ["in"] },
+//!         { number = true },
+//!         { words = ["tagen", "tag"], max_dist = 1 },
+//!     ]
+//!     actions = [{ type = "offset", unit = "day" }]
+//!
+//!     [[rules]]
+//!     name = "de::abends"
+//!     pattern = [{ words = ["abends", "am abend"] }]
+//!     actions = [{ type = "set_time", hour = 19 }]
+//! "#).unwrap();
+//!
+//! let parser = when::Parser::new(chrono_tz::Europe::Berlin).data_rules(rules);
+//! assert_eq!(parser.parse("in 3 tagen abends").len(), 1);
+//! ```
+//!
+//! Pattern elements:
+//!
+//! * `{ words = [...] }` - one of the words or phrases, e.g. "am abend", words may contain typos
+//!   unless exact match is required, the allowed number of typos is set by `max_dist` or depends
+//!   on the word length
+//! * `{ number = true }` - an integer, e.g. "15", which is used by the "offset" action
+//!
+//! Any element may be marked as `optional = true`. Elements are matched greedily from left to
+//! right, there is no backtracking.
+//!
+//! Actions:
+//!
+//! * `{ type = "set_time", hour = 19, minute = 30 }` - sets the time, minute defaults to 0
+//! * `{ type = "offset", unit = "day", count = 2, backwards = false }` - moves the date/time from
+//!   now, unit is one of "minute", "hour", "day", "week", "month" and "year", count defaults to
+//!   the number matched by the pattern or to 1 if there is no number
+//! * `{ type = "weekday", day = "monday", relation = "next" }` - moves to the day of week,
+//!   relation is one of "this" (default), "next" and "last", like in "this/next/last monday"
+
+use crate::parser::add_months;
+use crate::rule::{words, Rule, RuleMatch};
+use crate::rules::combinators::{self, default_dist, Dist};
+use crate::rules::errors::DateTimeError;
+use crate::rules::rules::Context;
+use crate::rules::tokens::PToken;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Weekday};
+use failure::Fail;
+use nom::types::CompleteStr;
+use serde::{Deserialize, Deserializer};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::path::Path;
+
+#[derive(Debug, Fail, PartialEq, Clone)]
+pub enum DataRuleError {
+    #[fail(display = "can't read rules: {}", msg)]
+    Syntax { msg: String },
+    #[fail(display = "invalid rule \"{}\": {}", rule, msg)]
+    InvalidRule { rule: String, msg: String },
+}
+
+/// Rules loaded from a single TOML or JSON document, which contains a "rules" array
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSet {
+    pub rules: Vec<DataRule>,
+}
+
+impl RuleSet {
+    pub fn from_toml(text: &str) -> Result<Self, DataRuleError> {
+        let set: RuleSet =
+            toml::from_str(text).map_err(|e| DataRuleError::Syntax { msg: e.to_string() })?;
+        set.validated()
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, DataRuleError> {
+        let set: RuleSet =
+            serde_json::from_str(text).map_err(|e| DataRuleError::Syntax { msg: e.to_string() })?;
+        set.validated()
+    }
+
+    /// Reads rules from a ".toml" or ".json" file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, DataRuleError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| DataRuleError::Syntax {
+            msg: format!("{}: {}", path.display(), e),
+        })?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            Some("json") => Self::from_json(&text),
+            _ => Err(DataRuleError::Syntax {
+                msg: format!("{}: unknown file format", path.display()),
+            }),
+        }
+    }
+
+    fn validated(mut self) -> Result<Self, DataRuleError> {
+        for rule in &mut self.rules {
+            rule.validate()?;
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DataRule {
+    pub name: String,
+    pub pattern: Vec<Element>,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Element {
+    /// alternatives, words of a phrase are separated by spaces
+    pub words: Vec<String>,
+    pub number: bool,
+    /// maximum number of typos in each word, depends on the word length if not set
+    pub max_dist: Option<usize>,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Action {
+    SetTime {
+        hour: i32,
+        #[serde(default)]
+        minute: i32,
+    },
+    Offset {
+        unit: Unit,
+        count: Option<i32>,
+        #[serde(default)]
+        backwards: bool,
+    },
+    Weekday {
+        #[serde(deserialize_with = "weekday_from_str")]
+        day: Weekday,
+        #[serde(default)]
+        relation: Relation,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    #[default]
+    This,
+    Next,
+    Last,
+}
+
+fn weekday_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Weekday, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse::<Weekday>()
+        .map_err(|_| serde::de::Error::custom(format!("unknown day of week \"{}\"", name)))
+}
+
+impl DataRule {
+    /// Checks the rule and brings words to the form the input text has, i.e. lowercase
+    fn validate(&mut self) -> Result<(), DataRuleError> {
+        let name = self.name.clone();
+        let invalid = |msg: &str| DataRuleError::InvalidRule {
+            rule: name.clone(),
+            msg: msg.to_owned(),
+        };
+
+        if self.name.is_empty() {
+            return Err(invalid("name is empty"));
+        }
+        if self.pattern.iter().all(|element| element.optional) {
+            return Err(invalid("pattern has no required elements"));
+        }
+        if self.actions.is_empty() {
+            return Err(invalid("no actions"));
+        }
+
+        for element in &mut self.pattern {
+            if element.number != element.words.is_empty() {
+                return Err(invalid("element must have either words or number"));
+            }
+            for word in &mut element.words {
+                *word = word.to_lowercase();
+                if word.split_whitespace().next().is_none() {
+                    return Err(invalid("empty word"));
+                }
+            }
+        }
+
+        let numbers = self.pattern.iter().filter(|element| element.number).count();
+
+        for action in &self.actions {
+            match *action {
+                Action::SetTime { hour, minute }
+                    if !(0..=23).contains(&hour) || !(0..=59).contains(&minute) =>
+                {
+                    return Err(invalid("time is out of range"));
+                }
+                Action::Offset { count: None, .. } if numbers > 1 => {
+                    return Err(invalid("offset count is ambiguous"));
+                }
+                Action::Offset {
+                    unit: Unit::Year,
+                    count: Some(count),
+                    ..
+                } if i32::try_from(i64::from(count) * 12).is_err() => {
+                    return Err(invalid("offset count is out of range"));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Matches the pattern at the beginning of the input, returns number of consumed bytes, the
+    /// number found, if any, and the confidence of the match
+    fn recognize(&self, input: &str, exact_match: bool) -> Option<(usize, Option<i32>, f64)> {
+        let mut tail = CompleteStr(input);
+        let mut number = None;
+        let mut evidence = Vec::new();
+
+        for element in &self.pattern {
+            let found = if element.number {
+                combinators::recognize_int(tail).ok().map(|(rest, n)| {
+                    number = Some(n);
+                    (rest, vec![(0.0, 0)])
+                })
+            } else {
+                element
+                    .words
+                    .iter()
+                    .filter_map(|phrase| {
+                        recognize_words(tail, phrase, element.max_dist, exact_match)
+                    })
+                    .min_by(|(rest, words), (other_rest, other_words)| {
+                        let cost = |words: &[(f64, usize)]| -> f64 {
+                            words.iter().map(|(cost, _)| cost).sum()
+                        };
+                        cost(words)
+                            .partial_cmp(&cost(other_words))
+                            .unwrap_or(Ordering::Equal)
+                            .then(rest.len().cmp(&other_rest.len()))
+                    })
+            };
+
+            match found {
+                Some((rest, words)) => {
+                    tail = rest;
+                    evidence.extend(words);
+                }
+                None if element.optional => (),
+                None => return None,
+            }
+        }
+
+        Some((
+            input.len() - tail.len(),
+            number,
+            combinators::match_confidence(&evidence),
+        ))
+    }
+
+    fn make_context<Tz: TimeZone>(
+        &self,
+        text: &str,
+        number: Option<i32>,
+        now: &DateTime<Tz>,
+    ) -> Result<Context, DateTimeError> {
+        let mut ctx = Context::default();
+
+        for action in &self.actions {
+            match *action {
+                Action::SetTime { hour, minute } => {
+                    ctx.hour = Some(hour);
+                    ctx.minute = Some(minute);
+                }
+                Action::Offset {
+                    unit,
+                    count,
+                    backwards,
+                } => {
+                    let count = match count {
+                        Some(count) => count,
+                        None => {
+                            let count = number.unwrap_or(1);
+                            if count < 0 {
+                                return Err(DateTimeError::InvalidTime {
+                                    text: text.to_owned(),
+                                    what: "number".to_owned(),
+                                    value: count,
+                                });
+                            }
+                            count
+                        }
+                    };
+                    let invalid = || DateTimeError::InvalidTime {
+                        text: text.to_owned(),
+                        what: "number".to_owned(),
+                        value: count,
+                    };
+                    let count = if backwards {
+                        -i64::from(count)
+                    } else {
+                        i64::from(count)
+                    };
+
+                    ctx.duration = ctx.duration
+                        + match unit {
+                            Unit::Minute => Duration::minutes(count),
+                            Unit::Hour => Duration::hours(count),
+                            Unit::Day => Duration::days(count),
+                            Unit::Week => Duration::weeks(count),
+                            Unit::Month | Unit::Year => {
+                                let months = if unit == Unit::Year { 12 } else { 1 } * count;
+                                let today = (now.clone() + ctx.duration).naive_local().date();
+                                let date = i32::try_from(months)
+                                    .ok()
+                                    .and_then(|months| add_months(today, months))
+                                    .ok_or_else(invalid)?;
+                                date - today
+                            }
+                        };
+                }
+                Action::Weekday { day, relation } => {
+                    let today = i64::from(
+                        (now.clone() + ctx.duration)
+                            .weekday()
+                            .num_days_from_monday(),
+                    );
+                    let day = i64::from(day.num_days_from_monday());
+                    let days = match relation {
+                        Relation::Next if day > today => day - today,
+                        Relation::Next => 7 + day - today,
+                        Relation::Last if day < today => day - today,
+                        Relation::Last => day - today - 7,
+                        Relation::This if day >= today => day - today,
+                        Relation::This => {
+                            return Err(DateTimeError::AmbiguousTime {
+                                msg: text.to_owned(),
+                            })
+                        }
+                    };
+                    ctx.duration = ctx.duration + Duration::days(days);
+                }
+            }
+        }
+
+        Ok(ctx)
+    }
+}
+
+/// Matches words of the phrase one by one, returns the rest of the input and typos cost and
+/// length of every word
+fn recognize_words<'a>(
+    input: CompleteStr<'a>,
+    phrase: &str,
+    max_dist: Option<usize>,
+    exact_match: bool,
+) -> Option<(CompleteStr<'a>, Vec<(f64, usize)>)> {
+    let mut tail = input;
+    let mut words = Vec::new();
+
+    for word in phrase.split_whitespace() {
+        let max_dist = match max_dist {
+            _ if exact_match => Dist(0),
+            Some(dist) => Dist(dist),
+            None => default_dist(word),
+        };
+        let (rest, desc) =
+            combinators::recognize_word(tail, CompleteStr(word), max_dist, PToken::Stub).ok()?;
+        tail = rest;
+        words.push((desc.cost, desc.len));
+    }
+
+    Some((tail, words))
+}
+
+impl<Tz: TimeZone> Rule<Tz> for DataRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(&self, input: &str, exact_match: bool, now: &DateTime<Tz>) -> Vec<RuleMatch> {
+        let mut result = Vec::new();
+
+        // a phrase may start at every word, parser selects among overlapping matches
+        for word in words(input) {
+            if let Some((len, number, confidence)) =
+                self.recognize(&input[word.start_idx..], exact_match)
+            {
+                let end_idx = word.start_idx + len;
+                let text = &input[word.start_idx..end_idx];
+                result.push(
+                    RuleMatch::new(
+                        word.start_idx,
+                        end_idx,
+                        self.make_context(text, number, now),
+                    )
+                    .with_confidence(confidence),
+                );
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, DataRuleError, Relation, RuleSet, Unit};
+    use crate::rules::errors::DateTimeError;
+    use crate::rule::Rule;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Utc> {
+        // Tuesday
+        Utc.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_load() {
+        let set = RuleSet::from_json(
+            r#"{"rules": [{
+                "name": "ru::next_monday",
+                "pattern": [{"words": ["В следующий"], "optional": true}, {"words": ["понедельник"]}],
+                "actions": [{"type": "weekday", "day": "mon", "relation": "next"}]
+            }]}"#,
+        )
+        .unwrap();
+        assert_eq!(set.rules[0].pattern[0].words, vec!["в следующий"]);
+        assert_eq!(
+            set.rules[0].actions,
+            vec![Action::Weekday {
+                day: Weekday::Mon,
+                relation: Relation::Next
+            }]
+        );
+
+        let set = RuleSet::from_toml(
+            r#"
+            [[rules]]
+            name = "in_days"
+            pattern = [{ number = true }, { words = ["days"] }]
+            actions = [{ type = "offset", unit = "day" }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            set.rules[0].actions,
+            vec![Action::Offset {
+                unit: Unit::Day,
+                count: None,
+                backwards: false
+            }]
+        );
+
+        let err = RuleSet::from_toml(
+            r#"
+            [[rules]]
+            name = "late"
+            pattern = [{ words = ["late"] }]
+            actions = [{ type = "set_time", hour = 25 }]
+            "#,
+        );
+        assert_eq!(
+            err,
+            Err(DataRuleError::InvalidRule {
+                rule: "late".to_owned(),
+                msg: "time is out of range".to_owned()
+            })
+        );
+
+        let err = RuleSet::from_json(
+            r#"{"rules": [{
+                "name": "eons",
+                "pattern": [{"words": ["eons"]}],
+                "actions": [{"type": "offset", "unit": "year", "count": 2000000000}]
+            }]}"#,
+        );
+        assert_eq!(
+            err,
+            Err(DataRuleError::InvalidRule {
+                rule: "eons".to_owned(),
+                msg: "offset count is out of range".to_owned()
+            })
+        );
+
+        assert!(RuleSet::from_json(r#"{"rules": [{"name": "x"}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let set = RuleSet::from_toml(
+            r#"
+            [[rules]]
+            name = "ru::days_ago"
+            pattern = [{ number = true, optional = true }, { words = ["дня", "дней"] }, { words = ["назад"] }]
+            actions = [{ type = "offset", unit = "day", backwards = true }, { type = "set_time", hour = 9 }]
+            "#,
+        )
+        .unwrap();
+        let rule = &set.rules[0];
+
+        let matches = Rule::<Utc>::apply(
+            rule,
+            "было 3 дня назд, или 15 дней назад",
+            false,
+            &fixed_time(),
+        );
+        // the number is optional, so matches without it are candidates too
+        assert_eq!(matches.len(), 4);
+        assert_eq!((matches[0].start_idx, matches[0].end_idx), (9, 26));
+        let context = matches[0].result.clone().unwrap();
+        assert_eq!(context.duration, time::Duration::days(-3));
+        assert_eq!(context.hour, Some(9));
+        assert_eq!((matches[1].start_idx, matches[1].end_idx), (11, 26));
+        assert_eq!(
+            matches[2].result.clone().unwrap().duration,
+            time::Duration::days(-15)
+        );
+
+        let matches = Rule::<Utc>::apply(rule, "3 дня назд", true, &fixed_time());
+        assert!(matches.is_empty());
+
+        let set = RuleSet::from_toml(
+            r#"
+            [[rules]]
+            name = "de::montag"
+            pattern = [{ words = ["nächsten"], optional = true }, { words = ["montag"] }]
+            actions = [{ type = "weekday", day = "monday", relation = "next" }]
+            "#,
+        )
+        .unwrap();
+        let matches = Rule::<Utc>::apply(&set.rules[0], "nächsten montag", false, &fixed_time());
+        assert_eq!(
+            matches[0].result.clone().unwrap().duration,
+            time::Duration::days(6)
+        );
+
+        let set = RuleSet::from_toml(
+            r#"
+            [[rules]]
+            name = "in_years"
+            pattern = [{ words = ["in"] }, { number = true }, { words = ["years"] }]
+            actions = [{ type = "offset", unit = "year" }]
+            "#,
+        )
+        .unwrap();
+        let matches =
+            Rule::<Utc>::apply(&set.rules[0], "in 1000000000 years", false, &fixed_time());
+        assert_eq!(
+            matches[0].result,
+            Err(DateTimeError::InvalidTime {
+                text: "in 1000000000 years".to_owned(),
+                what: "number".to_owned(),
+                value: 1_000_000_000,
+            })
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Trace of a parse which shows why parser produced its results: which rules matched which
+//! words, which matches were dropped and how the rest were merged, see "Parser::explain".
+//! It prints as a tree and serializes to JSON, e.g. to attach it to a bug report:
+//!
+//! ```
+//! let parser = when::Parser::new(chrono_tz::Europe::Moscow);
+//! let explanation = parser.explain("meet me next fridy at 5pm");
+//! println!("{}", explanation);
+//!
+//! let group = &explanation.groups[0];
+//! assert_eq!(group.text, "next fridy at 5pm");
+//! assert_eq!(group.warnings, vec!["assumed \"friday\" for \"fridy\""]);
+//! ```
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Structured trace of a parse, offsets are byte offsets in the input
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Explanation {
+    pub input: String,
+    // every rule applied along with all its candidate matches
+    pub rules: Vec<RuleTrace>,
+    // candidates dropped before merging and why
+    pub removed: Vec<Removal>,
+    // selected matches merged into results
+    pub groups: Vec<Group>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleTrace {
+    pub name: String,
+    // language code, e.g. "en", None for language independent rules
+    pub language: Option<String>,
+    pub priority: i32,
+    pub matches: Vec<MatchTrace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchTrace {
+    pub start_idx: usize,
+    pub end_idx: usize,
+    pub text: String,
+    pub confidence: f64,
+    // tokens the rule recognized, only built-in rules report them
+    pub tokens: Vec<TokenTrace>,
+    // the reason the match is not a valid date/time
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenTrace {
+    pub token: String,
+    pub priority: isize,
+    // edit distance between the word and the pattern and its weight by the typo model
+    pub dist: usize,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Removal {
+    pub start_idx: usize,
+    pub end_idx: usize,
+    pub text: String,
+    pub reason: RemovalReason,
+    // score of the candidate for "not_selected" ones, see "Parser::alternatives"
+    pub score: Option<f64>,
+}
+
+/// Why a candidate was dropped, serializes as "weak", "foreign", "outranked" and "not_selected"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemovalReason {
+    // single word without a date context, e.g. "may" in "you may go"
+    Weak,
+    // lost to an intersecting match of another language
+    Foreign,
+    // lost to an intersecting match of a rule with higher priority
+    Outranked,
+    // not a part of the best cover of the input
+    NotSelected,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Group {
+    pub start_idx: usize,
+    pub end_idx: usize,
+    pub text: String,
+    // bounds of the merged matches
+    pub parts: Vec<(usize, usize)>,
+    // components of the merged context parser resolved the date/time from
+    pub context: BTreeMap<String, String>,
+    // RFC 3339 date/time or the error
+    pub date_time: Option<String>,
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for RemovalReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            RemovalReason::Weak => "weak",
+            RemovalReason::Foreign => "foreign",
+            RemovalReason::Outranked => "outranked",
+            RemovalReason::NotSelected => "not_selected",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+impl Explanation {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "input: {:?}", self.input)?;
+
+        writeln!(f, "rules:")?;
+        for rule in &self.rules {
+            let language = rule.language.as_deref().unwrap_or("any");
+            writeln!(
+                f,
+                "  {} ({}, priority {})",
+                rule.name, language, rule.priority
+            )?;
+            for m in &rule.matches {
+                write!(
+                    f,
+                    "    {}..{} {:?} confidence {:.2}",
+                    m.start_idx, m.end_idx, m.text, m.confidence
+                )?;
+                match &m.error {
+                    Some(error) => writeln!(f, ", error: {}", error)?,
+                    None => writeln!(f)?,
+                }
+                for token in &m.tokens {
+                    writeln!(
+                        f,
+                        "      {} priority {} dist {} cost {:.2}",
+                        token.token, token.priority, token.dist, token.cost
+                    )?;
+                }
+            }
+        }
+
+        writeln!(f, "removed:")?;
+        for removal in &self.removed {
+            write!(
+                f,
+                "  {}..{} {:?} {}",
+                removal.start_idx, removal.end_idx, removal.text, removal.reason
+            )?;
+            match removal.score {
+                Some(score) => writeln!(f, ", score {:.2}", score)?,
+                None => writeln!(f)?,
+            }
+        }
+
+        writeln!(f, "groups:")?;
+        for group in &self.groups {
+            write!(
+                f,
+                "  {}..{} {:?}",
+                group.start_idx, group.end_idx, group.text
+            )?;
+            match (&group.date_time, &group.error) {
+                (Some(date_time), _) => writeln!(f, " -> {}", date_time)?,
+                (_, Some(error)) => writeln!(f, " -> error: {}", error)?,
+                _ => writeln!(f)?,
+            }
+            let parts: Vec<String> = group
+                .parts
+                .iter()
+                .map(|(start_idx, end_idx)| format!("{}..{}", start_idx, end_idx))
+                .collect();
+            if !parts.is_empty() {
+                writeln!(f, "    parts: {}", parts.join(", "))?;
+            }
+            let context: Vec<String> = group
+                .context
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            if !context.is_empty() {
+                writeln!(f, "    context: {}", context.join(", "))?;
+            }
+            for warning in &group.warnings {
+                writeln!(f, "    warning: {}", warning)?;
+            }
+        }
+
+        Ok(())
+    }
+}
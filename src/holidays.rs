@@ -0,0 +1,355 @@
+//! Holiday calendars used to resolve holiday names, e.g. "on christmas" or "the day after
+//! thanksgiving".
+//!
+//! Parser comes with `Calendar::common()` and `Calendar::us()` calendars, company specific
+//! holidays can be added by registering one more calendar:
+//!
+//! ```
+//! use chrono::Weekday;
+//! use when::holidays::{Calendar, HolidayDate};
+//!
+//! let parser = when::Parser::new(chrono_tz::Europe::Moscow).holiday_calendar(Box::new(
+//!     Calendar::new()
+//!         .holiday(&["founders day"], HolidayDate::Fixed { month: 6, day: 12 })
+//!         .holiday(
+//!             &["summer party"],
+//!             HolidayDate::NthWeekday { month: 7, weekday: Weekday::Fri, n: -1 },
+//!         ),
+//! ));
+//! ```
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Source of holiday dates, names are expected to be lowercase
+pub trait HolidayCalendar: Send + Sync {
+    /// All names (including alternative spellings) of holidays known to the calendar
+    fn names(&self) -> Vec<String>;
+
+    /// Date of the named holiday in the given year, None if the calendar doesn't know the name
+    /// or the holiday doesn't take place that year
+    fn date(&self, name: &str, year: i32) -> Option<NaiveDate>;
+
+    /// Whether any of the calendar holidays falls on the date, calendars with many holidays may
+    /// want to override it with something faster
+    fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.names()
+            .iter()
+            .any(|name| self.date(name, date.year()) == Some(date))
+    }
+}
+
+/// Decides which occurrence of a yearly event is meant when the year is omitted, e.g. whether
+/// "christmas" said in August means the upcoming one or the one in the last December
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YearPolicy {
+    /// the event in the current year
+    Current,
+    /// the nearest event which is not in the past
+    Future,
+    /// the nearest event which is not in the future
+    Past,
+}
+
+/// The way holiday date is computed for the given year
+#[derive(Debug, Clone, PartialEq)]
+pub enum HolidayDate {
+    /// same date every year, e.g. Christmas
+    Fixed { month: u32, day: u32 },
+    /// n-th weekday of a month, negative n counts from the end of the month, e.g. Memorial Day
+    /// is the last (n = -1) Monday of May
+    NthWeekday {
+        month: u32,
+        weekday: Weekday,
+        n: i32,
+    },
+    /// number of days from the (western) Easter Sunday, e.g. Good Friday is -2
+    Easter(i64),
+}
+
+impl HolidayDate {
+    pub fn in_year(&self, year: i32) -> Option<NaiveDate> {
+        match *self {
+            HolidayDate::Fixed { month, day } => NaiveDate::from_ymd_opt(year, month, day),
+            HolidayDate::NthWeekday { month, weekday, n } => nth_weekday(year, month, weekday, n),
+            HolidayDate::Easter(offset) => {
+                easter(year).and_then(|date| date.checked_add_signed(days(offset)?))
+            }
+        }
+    }
+}
+
+/// Longer shifts leave the supported dates range, which is about 262 thousand years
+const MAX_DAYS: i64 = 100_000_000;
+
+/// Duration of the given number of days, None if it can't move any date within the range
+fn days(n: i64) -> Option<Duration> {
+    if (-MAX_DAYS..=MAX_DAYS).contains(&n) {
+        Some(Duration::days(n))
+    } else {
+        None
+    }
+}
+
+/// Western Easter Sunday, computed using the anonymous Gregorian algorithm
+pub fn easter(year: i32) -> Option<NaiveDate> {
+    if year < 1583 {
+        // Gregorian calendar wasn't in use yet
+        return None;
+    }
+
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: i32) -> Option<NaiveDate> {
+    if n > 0 {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let shift =
+            (7 + weekday.num_days_from_monday() - first.weekday().num_days_from_monday()) % 7;
+        let date = first.checked_add_signed(days(i64::from(shift) + 7 * (i64::from(n) - 1))?)?;
+        Some(date).filter(|date| date.month() == month)
+    } else if n < 0 {
+        let (next_year, next_month) = if month == 12 {
+            (year.checked_add(1)?, 1)
+        } else {
+            (year, month + 1)
+        };
+        let last = NaiveDate::from_ymd_opt(next_year, next_month, 1)?.pred_opt()?;
+        let shift =
+            (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        let date = last.checked_sub_signed(days(i64::from(shift) + 7 * (-i64::from(n) - 1))?)?;
+        Some(date).filter(|date| date.month() == month)
+    } else {
+        None
+    }
+}
+
+/// Simple calendar made of holiday definitions
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    holidays: Vec<(Vec<String>, HolidayDate)>,
+}
+
+impl Calendar {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a holiday known under one or several names
+    pub fn holiday(mut self, names: &[&str], date: HolidayDate) -> Self {
+        let names = names.iter().map(|name| name.to_lowercase()).collect();
+        self.holidays.push((names, date));
+        self
+    }
+
+    /// Widely known holidays: New Year, Christmas, Easter, etc.
+    pub fn common() -> Self {
+        Calendar::new()
+            .holiday(
+                &["new year's day", "new years day", "new year"],
+                HolidayDate::Fixed { month: 1, day: 1 },
+            )
+            .holiday(
+                &["valentine's day", "valentines day"],
+                HolidayDate::Fixed { month: 2, day: 14 },
+            )
+            .holiday(
+                &[
+                    "st. patrick's day",
+                    "saint patrick's day",
+                    "st patricks day",
+                ],
+                HolidayDate::Fixed { month: 3, day: 17 },
+            )
+            .holiday(&["good friday"], HolidayDate::Easter(-2))
+            .holiday(&["easter", "easter sunday"], HolidayDate::Easter(0))
+            .holiday(&["easter monday"], HolidayDate::Easter(1))
+            .holiday(&["halloween"], HolidayDate::Fixed { month: 10, day: 31 })
+            .holiday(
+                &["christmas eve"],
+                HolidayDate::Fixed { month: 12, day: 24 },
+            )
+            .holiday(
+                &["christmas", "christmas day", "xmas"],
+                HolidayDate::Fixed { month: 12, day: 25 },
+            )
+            .holiday(&["boxing day"], HolidayDate::Fixed { month: 12, day: 26 })
+            .holiday(
+                &["new year's eve", "new years eve"],
+                HolidayDate::Fixed { month: 12, day: 31 },
+            )
+    }
+
+    /// United States holidays
+    pub fn us() -> Self {
+        Calendar::new()
+            .holiday(
+                &["martin luther king day", "mlk day"],
+                HolidayDate::NthWeekday {
+                    month: 1,
+                    weekday: Weekday::Mon,
+                    n: 3,
+                },
+            )
+            .holiday(
+                &["presidents day", "presidents' day", "washington's birthday"],
+                HolidayDate::NthWeekday {
+                    month: 2,
+                    weekday: Weekday::Mon,
+                    n: 3,
+                },
+            )
+            .holiday(
+                &["mother's day", "mothers day"],
+                HolidayDate::NthWeekday {
+                    month: 5,
+                    weekday: Weekday::Sun,
+                    n: 2,
+                },
+            )
+            .holiday(
+                &["memorial day"],
+                HolidayDate::NthWeekday {
+                    month: 5,
+                    weekday: Weekday::Mon,
+                    n: -1,
+                },
+            )
+            .holiday(
+                &["father's day", "fathers day"],
+                HolidayDate::NthWeekday {
+                    month: 6,
+                    weekday: Weekday::Sun,
+                    n: 3,
+                },
+            )
+            .holiday(
+                &["independence day"],
+                HolidayDate::Fixed { month: 7, day: 4 },
+            )
+            .holiday(
+                &["labor day", "labour day"],
+                HolidayDate::NthWeekday {
+                    month: 9,
+                    weekday: Weekday::Mon,
+                    n: 1,
+                },
+            )
+            .holiday(
+                &["columbus day"],
+                HolidayDate::NthWeekday {
+                    month: 10,
+                    weekday: Weekday::Mon,
+                    n: 2,
+                },
+            )
+            .holiday(&["veterans day"], HolidayDate::Fixed { month: 11, day: 11 })
+            .holiday(
+                &["thanksgiving", "thanksgiving day"],
+                HolidayDate::NthWeekday {
+                    month: 11,
+                    weekday: Weekday::Thu,
+                    n: 4,
+                },
+            )
+    }
+}
+
+impl HolidayCalendar for Calendar {
+    fn names(&self) -> Vec<String> {
+        self.holidays
+            .iter()
+            .flat_map(|(names, _)| names.iter().cloned())
+            .collect()
+    }
+
+    fn date(&self, name: &str, year: i32) -> Option<NaiveDate> {
+        self.holidays
+            .iter()
+            .find(|(names, _)| names.iter().any(|n| n == name))
+            .and_then(|(_, date)| date.in_year(year))
+    }
+}
+
+/// Calendars parser uses by default
+pub(crate) fn default_calendars() -> Vec<Box<dyn HolidayCalendar>> {
+    vec![Box::new(Calendar::common()), Box::new(Calendar::us())]
+}
+
+/// Flattens names of all calendars into a list of (calendar index, name) pairs, position in this
+/// list identifies a holiday during parsing
+pub(crate) fn holiday_names(calendars: &[Box<dyn HolidayCalendar>]) -> Vec<(usize, String)> {
+    calendars
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, calendar)| calendar.names().into_iter().map(move |name| (idx, name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{easter, Calendar, HolidayCalendar, HolidayDate};
+    use chrono::{NaiveDate, Weekday};
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter(2019), Some(NaiveDate::from_ymd(2019, 4, 21)));
+        assert_eq!(easter(2024), Some(NaiveDate::from_ymd(2024, 3, 31)));
+        assert_eq!(easter(2038), Some(NaiveDate::from_ymd(2038, 4, 25)));
+        assert_eq!(easter(1500), None);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert_eq!(HolidayDate::Easter(i64::MAX).in_year(2019), None);
+        assert_eq!(HolidayDate::Easter(-1_000_000_000).in_year(2019), None);
+        assert_eq!(HolidayDate::Easter(300).in_year(262_143), None);
+        let nth = |n| HolidayDate::NthWeekday {
+            month: 5,
+            weekday: Weekday::Mon,
+            n,
+        };
+        assert_eq!(nth(i32::MAX).in_year(2019), None);
+        assert_eq!(nth(i32::MIN).in_year(2019), None);
+        assert_eq!(nth(-1).in_year(i32::MAX), None);
+    }
+
+    #[test]
+    fn test_calendars() {
+        let us = Calendar::us();
+        assert_eq!(
+            us.date("thanksgiving", 2019),
+            Some(NaiveDate::from_ymd(2019, 11, 28))
+        );
+        assert_eq!(
+            us.date("memorial day", 2019),
+            Some(NaiveDate::from_ymd(2019, 5, 27))
+        );
+        assert_eq!(
+            us.date("labor day", 2020),
+            Some(NaiveDate::from_ymd(2020, 9, 7))
+        );
+
+        let common = Calendar::common();
+        assert_eq!(
+            common.date("good friday", 2019),
+            Some(NaiveDate::from_ymd(2019, 4, 19))
+        );
+        assert_eq!(common.date("xmas", 2019), common.date("christmas", 2019));
+        assert_eq!(common.date("thanksgiving", 2019), None);
+    }
+}
@@ -0,0 +1,285 @@
+//! Languages of the built-in rule sets and a lightweight offline language hint.
+//!
+//! Parser can be configured with several languages at once, every result then reports the
+//! language of the rules which matched it:
+//!
+//! ```
+//! use when::languages::Language;
+//!
+//! let parser = when::Parser::new(chrono_tz::Europe::Moscow)
+//!     .languages(&[Language::English, Language::German]);
+//! let matches = parser.parse_matches("see you next friday, bis morgen früh");
+//! assert_eq!(matches[0].as_ref().unwrap().language, Some(Language::English));
+//! assert_eq!(matches[1].as_ref().unwrap().language, Some(Language::German));
+//! ```
+
+use crate::rule::Rule;
+use crate::rules::{br, de, en, es, fr, ja, ru, zh};
+use chrono::TimeZone;
+
+/// Language of a built-in rule set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    English,
+    Russian,
+    Portuguese,
+    German,
+    French,
+    Spanish,
+    Chinese,
+    Japanese,
+}
+
+/// Which one of intersecting matches found by rules of different languages is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanguagePolicy {
+    /// match of the language given earlier in "Parser::languages" wins
+    Order,
+    /// longer match wins, matches of equal length are resolved as in "Order"
+    Longest,
+    /// intersections are reported as errors, the same way as for rules of one language
+    Strict,
+}
+
+const LANGUAGES: &[Language] = &[
+    Language::English,
+    Language::Russian,
+    Language::Portuguese,
+    Language::German,
+    Language::French,
+    Language::Spanish,
+    Language::Chinese,
+    Language::Japanese,
+];
+
+impl Language {
+    /// All languages with built-in rules
+    pub fn all() -> &'static [Language] {
+        LANGUAGES
+    }
+
+    /// Prefix of the built-in rule names, e.g. "en" for "en::weekdays"
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Russian => "ru",
+            Language::Portuguese => "br",
+            Language::German => "de",
+            Language::French => "fr",
+            Language::Spanish => "es",
+            Language::Chinese => "zh",
+            Language::Japanese => "ja",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Language> {
+        LANGUAGES
+            .iter()
+            .find(|language| language.code() == code)
+            .copied()
+    }
+
+    /// Built-in rules of the language
+    pub(crate) fn rules<'a, Tz: TimeZone + 'a>(self) -> Vec<Box<dyn Rule<Tz> + 'a>> {
+        match self {
+            Language::English => en::rules(),
+            Language::Russian => ru::rules(),
+            Language::Portuguese => br::rules(),
+            Language::German => de::rules(),
+            Language::French => fr::rules(),
+            Language::Spanish => es::rules(),
+            Language::Chinese => zh::rules(),
+            Language::Japanese => ja::rules(),
+        }
+    }
+
+    /// Frequent short words which tell languages written in latin script apart
+    fn stop_words(self) -> &'static [&'static str] {
+        match self {
+            Language::English => &[
+                "the", "and", "to", "is", "are", "at", "on", "of", "you", "we", "it", "for",
+                "next", "last", "ago", "this", "with", "will",
+            ],
+            Language::Portuguese => &[
+                "o", "os", "e", "é", "não", "um", "uma", "com", "para", "você", "às", "no", "na",
+                "que", "depois", "amanhã", "hoje",
+            ],
+            Language::German => &[
+                "der", "die", "das", "und", "ist", "nicht", "ich", "wir", "sie", "um", "am", "im",
+                "uhr", "mit", "bis", "morgen", "heute",
+            ],
+            Language::French => &[
+                "le", "les", "et", "est", "je", "nous", "vous", "pour", "avec", "à", "au", "du",
+                "dans", "il", "demain", "aujourd",
+            ],
+            Language::Spanish => &[
+                "el", "los", "las", "y", "es", "no", "un", "con", "para", "por", "del", "al",
+                "hace", "mañana", "hoy",
+            ],
+            _ => &[],
+        }
+    }
+
+    /// Month and weekday names which are also ordinary words, e.g. "may" in "you may go", such
+    /// words are dates only if the text around them says so
+    pub(crate) fn weak_words(self) -> &'static [&'static str] {
+        match self {
+            Language::English => &["may", "march", "mar", "sat", "sun", "mon", "wed", "second"],
+            _ => &[],
+        }
+    }
+
+    /// Words which make a weak word following them a date, e.g. "on" in "on sat"
+    pub(crate) fn context_words(self) -> &'static [&'static str] {
+        match self {
+            Language::English => &[
+                "on", "in", "by", "of", "from", "since", "until", "till", "before", "after",
+                "next", "last", "every", "early", "late", "mid",
+            ],
+            _ => &[],
+        }
+    }
+
+    /// Words which link a date with a time next to it, e.g. "at" in "tomorrow at 5pm" or
+    /// "some time around" in "tomorrow, some time around 5pm", matches separated only by such
+    /// words are merged
+    pub(crate) fn connective_words(self) -> &'static [&'static str] {
+        match self {
+            Language::English => &[
+                "at",
+                "on",
+                "by",
+                "in",
+                "of",
+                "the",
+                "from",
+                "around",
+                "about",
+                "approximately",
+                "roughly",
+                "some",
+                "sometime",
+                "time",
+            ],
+            Language::Russian => &["в", "во", "на", "к", "около", "примерно", "где", "то"],
+            Language::Portuguese => &[
+                "às", "as", "a", "à", "no", "na", "em", "de", "do", "da", "por", "volta", "lá",
+                "pelas", "cerca",
+            ],
+            Language::German => &["um", "am", "an", "im", "gegen", "etwa", "ungefähr", "so"],
+            Language::French => &[
+                "à", "a", "au", "le", "la", "vers", "environ", "dans", "en", "de", "du",
+            ],
+            Language::Spanish => &[
+                "a", "al", "las", "la", "el", "en", "de", "del", "sobre", "hacia", "eso", "por",
+            ],
+            Language::Chinese => &["的", "在", "于"],
+            Language::Japanese => &["の", "に", "は", "頃"],
+        }
+    }
+
+    /// Words which separate matches denoting different dates, e.g. "and" in "monday and
+    /// tuesday", matches separated by such words are never merged
+    pub(crate) fn separator_words(self) -> &'static [&'static str] {
+        match self {
+            Language::English => &["and", "or", "but", "then", "until", "till", "to"],
+            Language::Russian => &["и", "или", "а", "но", "потом", "до"],
+            Language::Portuguese => &["e", "ou", "mas", "até"],
+            Language::German => &["und", "oder", "aber", "bis", "dann"],
+            Language::French => &["et", "ou", "mais", "puis", "jusqu"],
+            Language::Spanish => &["y", "o", "pero", "hasta", "luego"],
+            Language::Chinese => &["和", "或", "到", "至"],
+            Language::Japanese => &["と", "や", "か"],
+        }
+    }
+}
+
+/// Guesses the language of a whole text, e.g. of an email, it is a hint rather than a reliable
+/// detection: the script decides for russian, chinese and japanese, latin script texts are
+/// told apart by frequent short words. Returns None if the text gives no clue.
+pub fn detect(text: &str) -> Option<Language> {
+    let (mut kana, mut han, mut cyrillic) = (0, 0, 0);
+
+    for c in text.chars() {
+        match c {
+            '\u{3040}'..='\u{30ff}' | '\u{ff66}'..='\u{ff9f}' => kana += 1,
+            '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' => han += 1,
+            '\u{0400}'..='\u{04ff}' => cyrillic += 1,
+            _ => (),
+        }
+    }
+
+    // japanese texts mix kanji with kana, chinese ones have no kana at all
+    if kana > 0 {
+        return Some(Language::Japanese);
+    }
+    if han > 0 {
+        return Some(Language::Chinese);
+    }
+    if cyrillic > 0 {
+        return Some(Language::Russian);
+    }
+
+    let text = text.to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut best = None;
+    let mut best_score = 0;
+    for &language in LANGUAGES {
+        let stop_words = language.stop_words();
+        let score = words
+            .iter()
+            .filter(|word| stop_words.contains(word))
+            .count();
+        if score > best_score {
+            best = Some(language);
+            best_score = score;
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect, Language};
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            detect("let's meet next friday at 5pm"),
+            Some(Language::English)
+        );
+        assert_eq!(
+            detect("treffen wir uns am Freitag um 5 Uhr"),
+            Some(Language::German)
+        );
+        assert_eq!(detect("on se voit demain à 14h"), Some(Language::French));
+        assert_eq!(
+            detect("nos vemos el martes por la tarde"),
+            Some(Language::Spanish)
+        );
+        assert_eq!(
+            detect("vamos nos ver amanhã às 15h"),
+            Some(Language::Portuguese)
+        );
+        assert_eq!(detect("встретимся в пятницу"), Some(Language::Russian));
+        assert_eq!(detect("明天下午3点见"), Some(Language::Chinese));
+        assert_eq!(
+            detect("来週の金曜日に会いましょう"),
+            Some(Language::Japanese)
+        );
+        assert_eq!(detect("12:30"), None);
+    }
+
+    #[test]
+    fn test_codes() {
+        for &language in Language::all() {
+            assert_eq!(Language::from_code(language.code()), Some(language));
+        }
+        assert_eq!(Language::from_code("common"), None);
+    }
+}
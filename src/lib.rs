@@ -2,9 +2,23 @@
 //!
 //! This library is inspired by https://github.com/olebedev/when
 
+pub mod aliases;
+pub mod data_rules;
+pub mod explain;
+pub mod holidays;
+pub mod languages;
 pub mod parser;
+pub mod rule;
 pub(crate) mod rules;
+pub mod typos;
 
 pub use parser::Parser;
+pub use rules::br::parse as br;
+pub use rules::de::parse as de;
 pub use rules::en::parse as en;
-pub use rules::errors::DateTimeError;
+pub use rules::errors::{DateTimeError, Diagnostic};
+pub use rules::es::parse as es;
+pub use rules::fr::parse as fr;
+pub use rules::ja::parse as ja;
+pub use rules::ru::parse as ru;
+pub use rules::zh::parse as zh;
@@ -1,34 +1,339 @@
-use crate::rules::errors::DateTimeError;
-use crate::rules::rules::{Context, MatchResult};
-use chrono::offset::{TimeZone, Utc};
-use chrono::{DateTime, Datelike, NaiveDateTime, Timelike};
+use crate::aliases::{Alias, AliasIndex};
+use crate::data_rules::RuleSet;
+use crate::explain::{Explanation, Group, MatchTrace, Removal, RuleTrace};
+use crate::holidays::{default_calendars, holiday_names, HolidayCalendar, YearPolicy};
+use crate::languages::{self, Language, LanguagePolicy};
+use crate::rule::{self, Rule, Word};
+use crate::rules::chart::Chart;
+use crate::rules::combinators;
+use crate::rules::consts;
+use crate::rules::errors::{DateTimeError, Diagnostic};
+use crate::rules::lattice::Correction;
+use crate::rules::rules::{Context, MatchResult, Period, Span};
+use crate::rules::session::{self, split_words, Session};
+use crate::rules::trace;
+use crate::rules::vocabulary::Vocabulary;
+use crate::typos::{EditDistance, TypoModel};
+use chrono::offset::{Offset, TimeZone, Utc};
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday,
+};
+use failure::Fail;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
 
-type ParserType<'a, Tz> =
-    &'a dyn Fn(DateTime<Tz>, &'a str, bool) -> Vec<Result<MatchResult, DateTimeError>>;
+type ParserType<Tz> =
+    dyn Fn(DateTime<Tz>, &str, bool) -> Vec<Result<MatchResult, DateTimeError>> + Send + Sync;
 
-pub struct Parser<'a, Tz: TimeZone + 'a> {
-    lang_parser:
-        Box<&'a dyn Fn(DateTime<Tz>, &'a str, bool) -> Vec<Result<MatchResult, DateTimeError>>>,
+/// Start and end (exclusive) of a parsed interval
+pub type DateTimeRange<Tz> = (DateTime<Tz>, DateTime<Tz>);
+
+/// Parsed date/time along with the part of the input it was found in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match<Tz: TimeZone> {
+    pub date_time: DateTime<Tz>,
+    // byte offsets in the input, the end is exclusive
+    pub start_idx: usize,
+    pub end_idx: usize,
+    // language of the rules which matched, None if only language independent rules did,
+    // e.g. "common::hh_mm" or custom rules
+    pub language: Option<Language>,
+    // from 0 to 1, words like "may" or "sun" get lower confidence, see "min_confidence"
+    pub confidence: f64,
+    // assumptions parser made and problems it ignored, ordered by their positions
+    pub warnings: Vec<Warning>,
+}
+
+/// Invalid parser setting, see "Parser::fiscal_year_start" and "Parser::weekend"
+#[derive(Debug, Fail, PartialEq, Clone)]
+pub enum ConfigError {
+    #[fail(
+        display = "fiscal year start month must be from 1 to 12, got {}",
+        month
+    )]
+    FiscalYearStart { month: u32 },
+    #[fail(display = "at least one day of a week must be a business day")]
+    NoBusinessDays,
+}
+
+/// Non fatal problem of a parse result, e.g. an assumption parser made, see "Match::warnings"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    // byte offsets of the part of the input the warning is about, the end is exclusive
+    pub start_idx: usize,
+    pub end_idx: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum WarningKind {
+    // stated weekday differs from the weekday of the date, e.g. "sunday, march 5" when
+    // march 5 is monday, the date wins
+    WeekdayMismatch { stated: String, actual: String },
+    // misspelled word was read as the given one
+    TypoCorrected { word: String, correction: String },
+    // year was not given, the current one or the one chosen by "year_policy" was assumed
+    YearInferred { year: i32 },
+    // time was derived from a part of the day, e.g. 18:00 from "evening"
+    TimeInferred { hour: u32, minute: u32 },
+    // a merged match set a component the result ignores, e.g. the week of "week 42, march 5"
+    ComponentDropped { what: String },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            WarningKind::WeekdayMismatch { stated, actual } => {
+                write!(f, "the date is {}, not {}", actual, stated)
+            }
+            WarningKind::TypoCorrected { word, correction } => {
+                write!(f, "assumed \"{}\" for \"{}\"", correction, word)
+            }
+            WarningKind::YearInferred { year } => write!(f, "assumed year {}", year),
+            WarningKind::TimeInferred { hour, minute } => {
+                write!(f, "assumed {:02}:{:02}", hour, minute)
+            }
+            WarningKind::ComponentDropped { what } => write!(f, "ignored {}", what),
+        }
+    }
+}
+
+/// Candidate match which rules found in the input, see "Parser::alternatives"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alternative<Tz: TimeZone> {
+    // date/time the candidate denotes by itself, without the matches it could be merged with
+    pub result: Result<DateTime<Tz>, DateTimeError>,
+    // byte offsets in the input, the end is exclusive
+    pub start_idx: usize,
+    pub end_idx: usize,
+    pub language: Option<Language>,
+    pub confidence: f64,
+    // words covered by the candidate weighted by its confidence, the parser selects
+    // non overlapping candidates with the best total score
+    pub score: f64,
+    // whether the candidate is a part of the parse results
+    pub selected: bool,
+}
+
+/// Name of a date/time component and whether a context sets it
+type Component = (&'static str, fn(&Context) -> bool);
+
+/// How the text between two consecutive matches links them
+enum Gap {
+    // only connective words, e.g. "at" in "tomorrow at 5pm"
+    Linked,
+    // punctuation, connective words along with a comma or a short text
+    Close,
+    // separator words, e.g. "and" in "monday and tuesday"
+    Separated,
+    Far,
+}
+
+/// Group of close matches merged into one result
+struct Merged {
+    context: Context,
+    start_idx: usize,
+    end_idx: usize,
+    language: Option<Language>,
+    confidence: f64,
+    warnings: Vec<Warning>,
+    // bounds of the merged matches
+    parts: Vec<(usize, usize)>,
+}
+
+/// Lowercased input along with the offsets of its bytes in the input, rules match the lowercased
+/// text, while results point into the input, e.g. "İ" takes 2 bytes and its lowercase form 3
+struct Lowered {
+    text: String,
+    // offset in the input of every byte of the text and of its end
+    origin: Vec<usize>,
+}
+
+impl Lowered {
+    fn new(input: &str) -> Self {
+        let mut text = String::with_capacity(input.len());
+        let mut origin = Vec::with_capacity(input.len() + 1);
+        for (idx, c) in input.char_indices() {
+            text.extend(c.to_lowercase());
+            origin.resize(text.len(), idx);
+        }
+        origin.push(input.len());
+        Lowered { text, origin }
+    }
+
+    /// Offset in the input of the byte of the text
+    fn origin(&self, idx: usize) -> usize {
+        self.origin
+            .get(idx)
+            .or_else(|| self.origin.last())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn bounds(&self, (start_idx, end_idx): (usize, usize)) -> (usize, usize) {
+        (self.origin(start_idx), self.origin(end_idx))
+    }
+
+    fn diagnostic(&self, diagnostic: Diagnostic) -> Diagnostic {
+        let (start_idx, end_idx) = self.bounds((diagnostic.start_idx, diagnostic.end_idx));
+        Diagnostic {
+            start_idx,
+            end_idx,
+            ..diagnostic
+        }
+    }
+}
+
+/// Parser owns its rules and configuration, so one parser may be built once, e.g. kept in a
+/// static or an "Arc", and parse inputs of any lifetime from several threads at once
+pub struct Parser<Tz: TimeZone> {
+    lang_parser: Option<Box<ParserType<Tz>>>,
+    builtin_rules: Vec<Box<dyn Rule<Tz>>>,
+    rules: Vec<Box<dyn Rule<Tz>>>,
+    languages: Vec<Language>,
+    language_policy: LanguagePolicy,
+    detect_language: bool,
+    min_confidence: f64,
+    // (rule name or group, enabled), the last matching entry wins
+    rule_filters: Vec<(String, bool)>,
+    // (rule name or group, priority), the last matching entry wins
+    rule_priorities: Vec<(String, i32)>,
     exact_match: bool,
+    typo_model: Arc<dyn TypoModel>,
     max_dist: usize,
+    week_start: Weekday,
+    fiscal_year_start: u32,
+    holidays: Vec<Box<dyn HolidayCalendar>>,
+    holiday_names: Vec<(usize, String)>,
+    year_policy: YearPolicy,
+    aliases: Arc<AliasIndex>,
+    // words of custom holidays and data rules, see "Session::vocabulary"
+    vocabulary: Arc<Vocabulary>,
+    weekend: Vec<Weekday>,
+    days_off: Vec<Box<dyn HolidayCalendar>>,
     tz: Tz,
 }
 
-impl<'a, Tz: TimeZone + 'a> Parser<'a, Tz> {
-    pub fn new(tz: Tz) -> Self {
+impl<Tz: TimeZone> Parser<Tz> {
+    pub fn new(tz: Tz) -> Self
+    where
+        Tz: 'static,
+    {
+        let holidays = default_calendars();
+        let holiday_names = holiday_names(&holidays);
         Parser {
-            lang_parser: Box::new(&super::en),
+            lang_parser: None,
+            builtin_rules: Language::English.rules(),
+            rules: Vec::new(),
+            languages: vec![Language::English],
+            language_policy: LanguagePolicy::Order,
+            detect_language: false,
+            min_confidence: session::DEFAULT_MIN_CONFIDENCE,
+            rule_filters: Vec::new(),
+            rule_priorities: Vec::new(),
             exact_match: false,
+            typo_model: Arc::new(EditDistance),
             max_dist: 5,
+            week_start: Weekday::Mon,
+            fiscal_year_start: 1,
+            holidays,
+            holiday_names,
+            year_policy: YearPolicy::Current,
+            aliases: Arc::new(AliasIndex::default()),
+            vocabulary: Arc::new(Vocabulary::default()),
+            weekend: vec![Weekday::Sat, Weekday::Sun],
+            days_off: Vec::new(),
             tz,
         }
     }
 
-    pub fn parser(mut self, parser_func: Box<ParserType<'a, Tz>>) -> Self {
-        self.lang_parser = parser_func;
+    /// Replaces rules with a language function, e.g. "when::en", rules registered with
+    /// "with_rule" are not applied in this case
+    pub fn parser<F>(mut self, parser_func: F) -> Self
+    where
+        F: Fn(DateTime<Tz>, &str, bool) -> Vec<Result<MatchResult, DateTimeError>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.lang_parser = Some(Box::new(parser_func));
+        self
+    }
+
+    /// Replaces built-in rules with the rules of the given languages, e.g. for an inbox where
+    /// messages are written in different languages. Every result reports the language of the
+    /// rules which matched it (see "parse_matches"), intersecting matches of different
+    /// languages are resolved according to "language_policy". An empty list keeps the current
+    /// languages, English by default.
+    pub fn languages(mut self, languages: &[Language]) -> Self
+    where
+        Tz: 'static,
+    {
+        if languages.is_empty() {
+            return self;
+        }
+        self.languages = languages.to_vec();
+        self.builtin_rules = Vec::new();
+        for language in languages {
+            for rule in language.rules() {
+                // rules like "common::hh_mm" are shared by several languages
+                if !self.builtin_rules.iter().any(|r| r.name() == rule.name()) {
+                    self.builtin_rules.push(rule);
+                }
+            }
+        }
+        self
+    }
+
+    /// Sets how intersecting matches of different languages are resolved, the language given
+    /// earlier in "languages" wins by default
+    pub fn language_policy(mut self, language_policy: LanguagePolicy) -> Self {
+        self.language_policy = language_policy;
+        self
+    }
+
+    /// Applies only rules of the language the whole input is most likely written in, see
+    /// "when::languages::detect", rules of all languages are applied if the guess is not
+    /// among the configured languages
+    pub fn detect_language(mut self, detect_language: bool) -> Self {
+        self.detect_language = detect_language;
+        self
+    }
+
+    /// Drops matches with lower confidence. Month and weekday names which are also ordinary
+    /// words (e.g. "may" in "you may go", "sun" in "sun is out") have low confidence unless
+    /// a number, a preposition or another date is next to them, so they are dropped by default,
+    /// 0 keeps all matches
+    pub fn min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Registers a custom rule which is applied along with the built-in ones, see "when::rule"
+    pub fn with_rule(mut self, rule: Box<dyn Rule<Tz>>) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Registers rules loaded from a data file, see "when::data_rules"
+    pub fn data_rules(mut self, rule_set: RuleSet) -> Self {
+        let vocabulary = Arc::make_mut(&mut self.vocabulary);
+        for rule in rule_set.rules {
+            for element in &rule.pattern {
+                for phrase in &element.words {
+                    phrase
+                        .split_whitespace()
+                        .for_each(|word| vocabulary.add(word));
+                }
+            }
+            self.rules.push(Box::new(rule));
+        }
         self
     }
 
+    /// Longest text in bytes between two matches which are merged even if it consists of
+    /// other than connective words (e.g. "at"), 5 by default
     pub fn max_dist(mut self, max_dist: usize) -> Self {
         self.max_dist = max_dist;
         self
@@ -39,128 +344,1169 @@ impl<'a, Tz: TimeZone + 'a> Parser<'a, Tz> {
         self
     }
 
+    /// Sets how typos are weighted in fuzzy parsing, plain edit distance is used by default,
+    /// see "when::typos"
+    pub fn typo_model<M: TypoModel + 'static>(mut self, typo_model: M) -> Self {
+        self.typo_model = Arc::new(typo_model);
+        self
+    }
+
+    /// Sets the first day of a week, it is used to resolve week numbers, for example "week 42"
+    /// points to Sunday before the ISO week 42 Monday if week starts on Sunday
+    pub fn week_start(mut self, week_start: Weekday) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    /// Sets the month (from 1 to 12) fiscal year starts with, it is used to resolve quarters and
+    /// halves, e.g. "q1" is October to December if fiscal year starts in October.
+    ///
+    /// Fiscal years are named after the calendar year they end in, so for the October start
+    /// "q1 fy20" and "q1 2020" both mean October to December 2019. Other months than 1 to 12
+    /// are rejected.
+    pub fn fiscal_year_start(mut self, month: u32) -> Result<Self, ConfigError> {
+        if !(1..=12).contains(&month) {
+            return Err(ConfigError::FiscalYearStart { month });
+        }
+        self.fiscal_year_start = month;
+        Ok(self)
+    }
+
+    /// Registers one more holiday calendar, e.g. with company holidays, in addition to the
+    /// default ones. Names found in several calendars are resolved using the calendar registered
+    /// last.
+    pub fn holiday_calendar(mut self, calendar: Box<dyn HolidayCalendar>) -> Self {
+        let vocabulary = Arc::make_mut(&mut self.vocabulary);
+        for name in calendar.names() {
+            split_words(&name)
+                .iter()
+                .for_each(|word| vocabulary.add(word));
+        }
+        self.holidays.push(calendar);
+        self.holiday_names = holiday_names(&self.holidays);
+        self
+    }
+
+    /// Sets which occurrence of a holiday is meant when the year is omitted, e.g. "christmas",
+    /// the current year one is used by default
+    pub fn year_policy(mut self, year_policy: YearPolicy) -> Self {
+        self.year_policy = year_policy;
+        self
+    }
+
+    /// Adds an extra surface form of a token, e.g. "tmrw" for "tomorrow", see "when::aliases"
+    pub fn alias(mut self, alias: Alias) -> Self {
+        Arc::make_mut(&mut self.aliases).add(alias);
+        self
+    }
+
+    /// Adds a list of aliases, e.g. "when::aliases::chat_slang()"
+    pub fn aliases(mut self, aliases: Vec<Alias>) -> Self {
+        let index = Arc::make_mut(&mut self.aliases);
+        for alias in aliases {
+            index.add(alias);
+        }
+        self
+    }
+
+    /// Sets days of a week which are not business days, Saturday and Sunday by default. Repeated
+    /// days are counted once, a weekend of all seven days is rejected.
+    pub fn weekend(mut self, weekend: &[Weekday]) -> Result<Self, ConfigError> {
+        let mut days = weekend.to_vec();
+        days.sort_by_key(|day| day.num_days_from_monday());
+        days.dedup();
+        if days.len() == 7 {
+            return Err(ConfigError::NoBusinessDays);
+        }
+        self.weekend = days;
+        Ok(self)
+    }
+
+    /// Registers a calendar of public holidays or other days off, business days arithmetic
+    /// (e.g. "in 3 business days") skips them along with weekends
+    pub fn days_off_calendar(mut self, calendar: Box<dyn HolidayCalendar>) -> Self {
+        self.days_off.push(calendar);
+        self
+    }
+
+    /// Turns rules on, names are either full rule names like "en::weekdays" or groups like
+    /// "en", later calls override earlier ones, e.g. excluding "en" and then including
+    /// "en::weekdays" leaves only the weekdays rule. Names of unknown rules are ignored.
+    pub fn include_rules(mut self, names: &[&str]) -> Self {
+        self.rule_filters
+            .extend(names.iter().map(|name| (name.to_string(), true)));
+        self
+    }
+
+    /// Turns rules off, e.g. "common::slash_dmy" for texts full of fractions like "3/4 cup",
+    /// see "include_rules" for the names format
+    pub fn exclude_rules(mut self, names: &[&str]) -> Self {
+        self.rule_filters
+            .extend(names.iter().map(|name| (name.to_string(), false)));
+        self
+    }
+
+    /// Sets priority of a rule or a group of rules, 0 by default. When matches of different
+    /// rules intersect, the match of the rule with the higher priority wins, intersections of
+    /// rules with equal priorities are resolved by scores, see "alternatives".
+    pub fn rule_priority(mut self, name: &str, priority: i32) -> Self {
+        self.rule_priorities.push((name.to_string(), priority));
+        self
+    }
+
+    /// Names of all registered rules, including disabled ones
+    pub fn rule_names(&self) -> Vec<&str> {
+        self.builtin_rules
+            .iter()
+            .chain(self.rules.iter())
+            .map(|rule| rule.name())
+            .collect()
+    }
+
+    /// Enabled rules along with their priorities, rules of other languages than the hinted one
+    /// are skipped
+    fn active_rules(&self, hint: Option<Language>) -> Vec<(&dyn Rule<Tz>, i32)> {
+        self.builtin_rules
+            .iter()
+            .chain(self.rules.iter())
+            .filter(|rule| match (rule.language(), hint) {
+                (Some(language), Some(hint)) => language == hint,
+                _ => true,
+            })
+            .filter(|rule| {
+                self.rule_filters
+                    .iter()
+                    .rev()
+                    .find(|(pattern, _)| rule_matches(rule.name(), pattern))
+                    .map(|(_, enabled)| *enabled)
+                    .unwrap_or(true)
+            })
+            .map(|rule| {
+                let priority = self
+                    .rule_priorities
+                    .iter()
+                    .rev()
+                    .find(|(pattern, _)| rule_matches(rule.name(), pattern))
+                    .map_or(0, |(_, priority)| *priority);
+                (rule.as_ref(), priority)
+            })
+            .collect()
+    }
+
     pub fn get_tz(&self) -> &Tz {
         &self.tz
     }
 
+    fn chart(&self, now: NaiveDateTime, input: &str, lowered: &str) -> (DateTime<Tz>, Chart) {
+        let tz_aware = self.tz.from_utc_datetime(&now);
+
+        let hint = if self.detect_language {
+            languages::detect(input).filter(|language| self.languages.contains(language))
+        } else {
+            None
+        };
+
+        let session = Session {
+            aliases: Arc::clone(&self.aliases),
+            vocabulary: Arc::clone(&self.vocabulary),
+            exact_match: self.exact_match,
+            languages: self.languages.clone(),
+            language_policy: self.language_policy,
+            min_confidence: self.min_confidence,
+            typo_model: self.typo_model.clone(),
+            ..Session::new(&self.holiday_names)
+        };
+        let chart = session::scoped(session, || match &self.lang_parser {
+            // language functions report only the selected matches and their errors have no
+            // positions
+            Some(lang_parser) => Chart {
+                selected: lang_parser(tz_aware.clone(), input, self.exact_match)
+                    .into_iter()
+                    .map(|item| item.map_err(|error| Diagnostic::locate(error, lowered)))
+                    .collect(),
+                ..Chart::default()
+            },
+            None => combinators::apply_chart(
+                tz_aware.clone(),
+                lowered,
+                &self.active_rules(hint),
+                self.exact_match,
+            ),
+        });
+
+        (tz_aware, chart)
+    }
+
     fn parser_helper(
         &self,
         now: NaiveDateTime,
-        input: &'a str,
-    ) -> (DateTime<Tz>, Vec<Result<Context, DateTimeError>>) {
-        let tz_aware = self.tz.from_utc_datetime(&now);
-
-        let res = (self.lang_parser)(tz_aware.clone(), input, self.exact_match);
-        let merged = self.merge(res);
+        input: &str,
+    ) -> (DateTime<Tz>, Vec<Result<Merged, Diagnostic>>, Lowered) {
+        let lowered = Lowered::new(input);
+        let (tz_aware, chart) = self.chart(now, input, &lowered.text);
+        let merged = self.merge(
+            &lowered.text,
+            &tz_aware,
+            chart.selected,
+            &chart.corrections,
+            &chart.stated,
+        );
 
-        (tz_aware, merged)
+        (tz_aware, merged, lowered)
     }
 
     // convert date/time to chrono
-    pub fn parse(&self, input: &'a str) -> Vec<Result<DateTime<Tz>, DateTimeError>> {
-        let (tz_aware, merged) = self.parser_helper(Utc::now().naive_utc(), input);
-        self.to_chrono(tz_aware, merged)
+    pub fn parse(&self, input: &str) -> Vec<Result<DateTime<Tz>, DateTimeError>> {
+        let (tz_aware, merged, lowered) = self.parser_helper(Utc::now().naive_utc(), input);
+        self.to_chrono(&lowered.text, tz_aware, merged)
     }
 
     // convert date/time to chrono
     pub fn parse_fixed_time(
         &self,
         now: NaiveDateTime,
-        input: &'a str,
+        input: &str,
     ) -> Vec<Result<DateTime<Tz>, DateTimeError>> {
-        let (tz_aware, merged) = self.parser_helper(now, input);
-        self.to_chrono(tz_aware, merged)
+        let (tz_aware, merged, lowered) = self.parser_helper(now, input);
+        self.to_chrono(&lowered.text, tz_aware, merged)
+    }
+
+    /// Same as "parse" but returns intervals, results which denote a moment rather than an
+    /// interval (e.g. "tomorrow at 5pm") are returned as intervals with the same start and end,
+    /// interval ends are exclusive
+    pub fn parse_range(&self, input: &str) -> Vec<Result<DateTimeRange<Tz>, DateTimeError>> {
+        let (tz_aware, merged, lowered) = self.parser_helper(Utc::now().naive_utc(), input);
+        self.to_chrono_range(&lowered.text, tz_aware, merged)
+    }
+
+    /// Same as "parse" but also returns where each date/time was found and in which language,
+    /// see "languages", errors are reported along with their positions, codes and suggestions
+    pub fn parse_matches(&self, input: &str) -> Vec<Result<Match<Tz>, Diagnostic>> {
+        let (tz_aware, merged, lowered) = self.parser_helper(Utc::now().naive_utc(), input);
+        self.to_matches(&lowered, tz_aware, merged)
     }
 
-    fn merge_group(&self, group: &[&MatchResult]) -> Context {
+    /// Same as "parse_fixed_time" but returns matches, see "parse_matches"
+    pub fn parse_matches_fixed_time(
+        &self,
+        now: NaiveDateTime,
+        input: &str,
+    ) -> Vec<Result<Match<Tz>, Diagnostic>> {
+        let (tz_aware, merged, lowered) = self.parser_helper(now, input);
+        self.to_matches(&lowered, tz_aware, merged)
+    }
+
+    /// Same as "parse_fixed_time" but returns intervals, see "parse_range"
+    pub fn parse_range_fixed_time(
+        &self,
+        now: NaiveDateTime,
+        input: &str,
+    ) -> Vec<Result<DateTimeRange<Tz>, DateTimeError>> {
+        let (tz_aware, merged, lowered) = self.parser_helper(now, input);
+        self.to_chrono_range(&lowered.text, tz_aware, merged)
+    }
+
+    /// Returns all candidate matches rules found in the input ordered by their positions,
+    /// including the ones which lost to better intersecting candidates, e.g. both "march 13"
+    /// and "13 seconds ago" in "march 13 seconds ago". Candidates are empty if rules are
+    /// replaced with a language function, see "parser".
+    pub fn alternatives(&self, input: &str) -> Vec<Alternative<Tz>> {
+        self.alternatives_fixed_time(Utc::now().naive_utc(), input)
+    }
+
+    /// Same as "alternatives" but relative to the given time, see "parse_fixed_time"
+    pub fn alternatives_fixed_time(&self, now: NaiveDateTime, input: &str) -> Vec<Alternative<Tz>> {
+        let lowered = Lowered::new(input);
+        let (tz_aware, chart) = self.chart(now, input, &lowered.text);
+        chart
+            .entries
+            .into_iter()
+            .map(|entry| Alternative {
+                result: entry
+                    .item
+                    .clone()
+                    .map_err(|diagnostic| diagnostic.error)
+                    .and_then(|m| {
+                        let text = lowered
+                            .text
+                            .get(entry.start_idx..entry.end_idx)
+                            .unwrap_or("");
+                        self.resolve(tz_aware.clone(), m.get_timeshift(), text)
+                    }),
+                start_idx: lowered.origin(entry.start_idx),
+                end_idx: lowered.origin(entry.end_idx),
+                language: entry.item.as_ref().ok().and_then(|m| m.get_language()),
+                confidence: entry.item.as_ref().map_or(1.0, |m| m.get_confidence()),
+                score: entry.score,
+                selected: entry.selected,
+            })
+            .collect()
+    }
+
+    /// Explains how the input is parsed: which rules matched it, which matches were dropped and
+    /// how the rest were merged into results, see "when::explain"
+    pub fn explain(&self, input: &str) -> Explanation {
+        self.explain_fixed_time(Utc::now().naive_utc(), input)
+    }
+
+    /// Same as "explain" but relative to the given time, see "parse_fixed_time"
+    pub fn explain_fixed_time(&self, now: NaiveDateTime, input: &str) -> Explanation {
+        let ((tz_aware, merged, lowered), trace) = trace::scoped(|| self.parser_helper(now, input));
+        // rules report offsets in the lowercased input, the explanation points into the input
+        let span = |start_idx: usize, end_idx: usize| {
+            let (start_idx, end_idx) = lowered.bounds((start_idx, end_idx));
+            let text = input.get(start_idx..end_idx).unwrap_or("").to_owned();
+            (start_idx, end_idx, text)
+        };
+
+        let groups = merged
+            .into_iter()
+            .map(|m| match m {
+                Ok(m) => {
+                    let result = self.resolve(
+                        tz_aware.clone(),
+                        &m.context,
+                        lowered.text.get(m.start_idx..m.end_idx).unwrap_or(""),
+                    );
+                    let (start_idx, end_idx, text) = span(m.start_idx, m.end_idx);
+                    Group {
+                        start_idx,
+                        end_idx,
+                        text,
+                        parts: m.parts.iter().map(|&part| lowered.bounds(part)).collect(),
+                        context: m
+                            .context
+                            .components()
+                            .into_iter()
+                            .map(|(name, value)| (name.to_owned(), value))
+                            .collect(),
+                        date_time: result.as_ref().ok().map(|date_time| {
+                            date_time
+                                .with_timezone(&date_time.offset().fix())
+                                .to_rfc3339()
+                        }),
+                        error: result.err().map(|error| error.to_string()),
+                        warnings: m.warnings.iter().map(|w| w.to_string()).collect(),
+                    }
+                }
+                Err(diagnostic) => {
+                    let (start_idx, end_idx, text) = span(diagnostic.start_idx, diagnostic.end_idx);
+                    Group {
+                        start_idx,
+                        end_idx,
+                        text,
+                        parts: Vec::new(),
+                        context: Default::default(),
+                        date_time: None,
+                        error: Some(diagnostic.to_string()),
+                        warnings: Vec::new(),
+                    }
+                }
+            })
+            .collect();
+
+        let rules = trace
+            .rules
+            .into_iter()
+            .map(|rule| RuleTrace {
+                matches: rule
+                    .matches
+                    .into_iter()
+                    .map(|m| {
+                        let (start_idx, end_idx, text) = span(m.start_idx, m.end_idx);
+                        MatchTrace {
+                            start_idx,
+                            end_idx,
+                            text,
+                            ..m
+                        }
+                    })
+                    .collect(),
+                ..rule
+            })
+            .collect();
+        let removed = trace
+            .removed
+            .into_iter()
+            .map(|removal| {
+                let (start_idx, end_idx, text) = span(removal.start_idx, removal.end_idx);
+                Removal {
+                    start_idx,
+                    end_idx,
+                    text,
+                    ..removal
+                }
+            })
+            .collect();
+
+        Explanation {
+            input: input.to_owned(),
+            rules,
+            removed,
+            groups,
+        }
+    }
+
+    fn merge_group(
+        &self,
+        input: &str,
+        date_time: &DateTime<Tz>,
+        group: &[&MatchResult],
+        corrections: &[Correction],
+    ) -> Merged {
         let mut result = Context::default();
         for item in group.iter() {
             result.update(item.get_timeshift());
         }
-        result
+        Merged {
+            warnings: self.warnings(input, date_time, group, &result, corrections),
+            parts: group
+                .iter()
+                .map(|item| (item.get_start_idx(), item.get_end_idx()))
+                .collect(),
+            context: result,
+            start_idx: group[0].get_start_idx(),
+            end_idx: group[group.len() - 1].get_end_idx(),
+            // the first match which has a language, e.g. "15:30" doesn't
+            language: group.iter().find_map(|item| item.get_language()),
+            // the least confident part
+            confidence: group
+                .iter()
+                .map(|item| item.get_confidence())
+                .fold(1.0, f64::min),
+        }
     }
 
+    /// Assumptions made for the merged group of matches and its parts the result ignores
+    fn warnings(
+        &self,
+        input: &str,
+        date_time: &DateTime<Tz>,
+        group: &[&MatchResult],
+        ctx: &Context,
+        corrections: &[Correction],
+    ) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        let mut warn = |kind, (start_idx, end_idx)| {
+            warnings.push(Warning {
+                kind,
+                start_idx,
+                end_idx,
+            })
+        };
+        // bounds of the first match which sets the component
+        let part = |is_set: &dyn Fn(&Context) -> bool| {
+            group
+                .iter()
+                .find(|item| is_set(item.get_timeshift()))
+                .map_or((group[0].get_start_idx(), group[0].get_end_idx()), |item| {
+                    (item.get_start_idx(), item.get_end_idx())
+                })
+        };
+
+        for correction in corrections.iter().filter(|correction| {
+            group.iter().any(|item| {
+                item.get_start_idx() <= correction.start_idx
+                    && correction.end_idx <= item.get_end_idx()
+            })
+        }) {
+            let word = &input[correction.start_idx..correction.end_idx];
+            warn(
+                WarningKind::TypoCorrected {
+                    word: word.to_owned(),
+                    correction: correction.pattern.clone(),
+                },
+                (correction.start_idx, correction.end_idx),
+            );
+        }
+
+        // sources of the date in the order "resolve" prefers them, month and day are applied
+        // together
+        let sources: [Component; 6] = [
+            ("holiday", |ctx| ctx.holiday.is_some()),
+            ("period", |ctx| ctx.period.is_some()),
+            ("business days", |ctx| ctx.business_days.is_some()),
+            ("week", |ctx| ctx.week.is_some()),
+            ("month", |ctx| ctx.month.is_some()),
+            ("day", |ctx| ctx.day.is_some()),
+        ];
+        // the first source which is set wins, unless it is the month and the day
+        if let Some(used) = sources
+            .iter()
+            .position(|(_, is_set)| is_set(ctx))
+            .filter(|&used| used < 4)
+        {
+            for (what, is_set) in sources.iter().skip(used + 1) {
+                if is_set(ctx) {
+                    let kind = WarningKind::ComponentDropped {
+                        what: (*what).to_owned(),
+                    };
+                    warn(kind, part(is_set));
+                }
+            }
+        }
+
+        let text = input
+            .get(group[0].get_start_idx()..group[group.len() - 1].get_end_idx())
+            .unwrap_or("");
+        if let Ok(resolved) = self.resolve(date_time.clone(), ctx, text) {
+            if let Some(stated) = ctx.weekday {
+                if ctx.week.is_none() && ctx.offset.is_none() && resolved.weekday() != stated {
+                    let kind = WarningKind::WeekdayMismatch {
+                        stated: weekday_name(stated).to_owned(),
+                        actual: weekday_name(resolved.weekday()).to_owned(),
+                    };
+                    warn(kind, part(&|ctx| ctx.weekday.is_some()));
+                }
+            }
+
+            let has_date = |ctx: &Context| {
+                ctx.month.is_some()
+                    || ctx.holiday.is_some()
+                    || ctx.period.is_some()
+                    || ctx.week.is_some()
+            };
+            if ctx.year.is_none() && has_date(ctx) {
+                let kind = WarningKind::YearInferred {
+                    year: resolved.year(),
+                };
+                warn(kind, part(&has_date));
+            }
+
+            if ctx.part_of_day && ctx.hour.is_some() {
+                let kind = WarningKind::TimeInferred {
+                    hour: resolved.hour(),
+                    minute: resolved.minute(),
+                };
+                warn(kind, part(&|ctx| ctx.part_of_day));
+            }
+        }
+
+        warnings.sort_by_key(|warning| warning.start_idx);
+        warnings
+    }
+
+    /// Tells how the text between two consecutive matches links them, words are looked up in
+    /// the given languages
+    fn gap(&self, text: &str, languages: &[Language]) -> Gap {
+        let words = rule::words(text);
+        let is_any = |word: &Word, list: fn(Language) -> &'static [&'static str]| {
+            languages
+                .iter()
+                .any(|&language| list(language).contains(&word.text))
+        };
+
+        if words
+            .iter()
+            .any(|word| is_any(word, Language::separator_words))
+        {
+            Gap::Separated
+        } else if words
+            .iter()
+            .all(|word| is_any(word, Language::connective_words))
+        {
+            // a comma lists dates rather than links parts of one, e.g. "monday, tuesday"
+            if words.is_empty() || text.contains(',') {
+                Gap::Close
+            } else {
+                Gap::Linked
+            }
+        } else if text.len() <= self.max_dist {
+            Gap::Close
+        } else {
+            Gap::Far
+        }
+    }
+
+    /// Merges consecutive matches which denote parts of one date/time, e.g. a date and a time,
+    /// matches are merged if they are linked by connective words (e.g. "at", "on") or are close
+    /// to each other and none of their components conflict. Conflicting matches linked by
+    /// connective words (e.g. "tomorrow at friday") are reported as errors.
+    ///
+    /// Errors which state something anyway, e.g. "tuesday" said on friday, are merged as well,
+    /// but only an explicit date in the group resolves them, otherwise they are reported.
     fn merge(
         &self,
-        parsed: Vec<Result<MatchResult, DateTimeError>>,
-    ) -> Vec<Result<Context, DateTimeError>> {
-        let mut group: Vec<&MatchResult> = Vec::new();
+        input: &str,
+        date_time: &DateTime<Tz>,
+        parsed: Vec<Result<MatchResult, Diagnostic>>,
+        corrections: &[Correction],
+        stated: &[(usize, usize, Context)],
+    ) -> Vec<Result<Merged, Diagnostic>> {
+        let items: Vec<Result<MatchResult, &Diagnostic>> = parsed
+            .iter()
+            .map(|item| match item {
+                Ok(match_result) => Ok(*match_result),
+                Err(diagnostic) => stated
+                    .iter()
+                    .find(|(start_idx, end_idx, _)| {
+                        (*start_idx, *end_idx) == (diagnostic.start_idx, diagnostic.end_idx)
+                    })
+                    .map(|&(start_idx, end_idx, ctx)| MatchResult::new(ctx, start_idx, end_idx))
+                    .ok_or(diagnostic),
+            })
+            .collect();
+
+        let mut group: Vec<usize> = Vec::new();
+        let mut context = Context::default();
         let mut merged = Vec::new();
 
+        // a group without an explicit date keeps the errors, matches between them are merged
+        let finish = |group: &[usize], merged: &mut Vec<Result<Merged, Diagnostic>>| {
+            let mut result = Context::default();
+            for &idx in group {
+                result.update(items[idx].as_ref().unwrap().get_timeshift());
+            }
+            let has_date = result.month.is_some()
+                || result.day.is_some()
+                || result.holiday.is_some()
+                || result.period.is_some()
+                || result.week.is_some();
+
+            let mut part: Vec<&MatchResult> = Vec::new();
+            for &idx in group {
+                match &parsed[idx] {
+                    Err(diagnostic) if !has_date => {
+                        if !part.is_empty() {
+                            merged.push(Ok(self.merge_group(input, date_time, &part, corrections)));
+                            part.clear();
+                        }
+                        merged.push(Err(diagnostic.clone()));
+                    }
+                    _ => part.push(items[idx].as_ref().unwrap()),
+                }
+            }
+            if !part.is_empty() {
+                merged.push(Ok(self.merge_group(input, date_time, &part, corrections)));
+            }
+        };
+
         // parse results are ordered from leftmost match to the rightmost
-        for item in parsed.iter() {
+        for (idx, item) in items.iter().enumerate() {
             match item {
                 Ok(match_result) => {
-                    let last = group.last();
-                    if last.is_some()
-                        && match_result.get_start_idx() - last.unwrap().get_end_idx()
-                            > self.max_dist
-                    {
-                        // distance is bigger than allowed threshold, finish previous group
-                        merged.push(Ok(self.merge_group(&group)));
-                        group.clear();
+                    if let Some(&last) = group.last() {
+                        let last = items[last].as_ref().unwrap();
+                        let mut languages = self.languages.clone();
+                        languages.extend(last.get_language());
+                        languages.extend(match_result.get_language());
+                        let text = input
+                            .get(last.get_end_idx()..match_result.get_start_idx())
+                            .unwrap_or("");
+                        let conflicts = context.conflicts(match_result.get_timeshift());
+
+                        match self.gap(text, &languages) {
+                            Gap::Linked | Gap::Close if conflicts.is_empty() => (),
+                            Gap::Linked => {
+                                let first = items[group[0]].as_ref().unwrap();
+                                let (start_idx, end_idx) =
+                                    (first.get_start_idx(), match_result.get_end_idx());
+                                let error = DateTimeError::ConflictError {
+                                    text: input.get(start_idx..end_idx).unwrap_or("").to_owned(),
+                                    what: conflicts.join(", "),
+                                };
+                                // the match which conflicts with the ones before it
+                                let token = input
+                                    .get(match_result.get_start_idx()..end_idx)
+                                    .unwrap_or("")
+                                    .to_owned();
+                                merged.push(Err(Diagnostic {
+                                    token,
+                                    ..Diagnostic::new(error, input, start_idx, end_idx)
+                                }));
+                                group.clear();
+                                context = Context::default();
+                                continue;
+                            }
+                            _ => {
+                                // matches denote different dates, finish previous group
+                                finish(&group, &mut merged);
+                                group.clear();
+                                context = Context::default();
+                            }
+                        }
                     }
                     // and start building a new one
-                    group.push(match_result);
+                    context.update(match_result.get_timeshift());
+                    group.push(idx);
                 }
                 Err(e) => {
-                    merged.push(Err(e.clone()));
+                    finish(&group, &mut merged);
+                    merged.push(Err((*e).clone()));
                     group.clear();
+                    context = Context::default();
                 }
             }
         }
 
         // merge everything that left
-        if !group.is_empty() {
-            merged.push(Ok(self.merge_group(&group)));
-        }
+        finish(&group, &mut merged);
 
         merged
     }
 
     fn to_chrono(
         &self,
+        input: &str,
         date_time: DateTime<Tz>,
-        merged: Vec<Result<Context, DateTimeError>>,
+        merged: Vec<Result<Merged, Diagnostic>>,
     ) -> Vec<Result<DateTime<Tz>, DateTimeError>> {
-        let mut ready: Vec<Result<DateTime<Tz>, DateTimeError>> = Vec::new();
+        merged
+            .into_iter()
+            .map(|m| m.map_err(|diagnostic| diagnostic.error))
+            .map(|m| {
+                m.and_then(|m| {
+                    let text = input.get(m.start_idx..m.end_idx).unwrap_or("");
+                    self.resolve(date_time.clone(), &m.context, text)
+                })
+            })
+            .collect()
+    }
 
-        for ctx in merged {
-            if ctx.is_err() {
-                ready.push(Err(ctx.unwrap_err()));
-                continue;
-            }
+    fn to_matches(
+        &self,
+        lowered: &Lowered,
+        date_time: DateTime<Tz>,
+        merged: Vec<Result<Merged, Diagnostic>>,
+    ) -> Vec<Result<Match<Tz>, Diagnostic>> {
+        let input = lowered.text.as_str();
+        merged
+            .into_iter()
+            .map(|m| {
+                m.and_then(|m| {
+                    let date_time = self
+                        .resolve(
+                            date_time.clone(),
+                            &m.context,
+                            input.get(m.start_idx..m.end_idx).unwrap_or(""),
+                        )
+                        .map_err(|error| Diagnostic::new(error, input, m.start_idx, m.end_idx))?;
+                    let (start_idx, end_idx) = lowered.bounds((m.start_idx, m.end_idx));
+                    Ok(Match {
+                        date_time,
+                        start_idx,
+                        end_idx,
+                        language: m.language,
+                        confidence: m.confidence,
+                        warnings: m
+                            .warnings
+                            .into_iter()
+                            .map(|warning| {
+                                let (start_idx, end_idx) =
+                                    lowered.bounds((warning.start_idx, warning.end_idx));
+                                Warning {
+                                    start_idx,
+                                    end_idx,
+                                    ..warning
+                                }
+                            })
+                            .collect(),
+                    })
+                })
+                .map_err(|diagnostic| lowered.diagnostic(diagnostic))
+            })
+            .collect()
+    }
 
-            let ctx = ctx.unwrap();
+    fn to_chrono_range(
+        &self,
+        input: &str,
+        date_time: DateTime<Tz>,
+        merged: Vec<Result<Merged, Diagnostic>>,
+    ) -> Vec<Result<DateTimeRange<Tz>, DateTimeError>> {
+        merged
+            .into_iter()
+            .map(|m| m.map_err(|diagnostic| diagnostic.error))
+            .map(|m| {
+                m.and_then(|m| {
+                    let text = input.get(m.start_idx..m.end_idx).unwrap_or("");
+                    let ctx = m.context;
+                    let start = self.resolve(date_time.clone(), &ctx, text)?;
+                    let end = match ctx.span {
+                        Some(Span::Days(n)) => start
+                            .clone()
+                            .checked_add_signed(Duration::days(n))
+                            .ok_or_else(|| DateTimeError::InvalidTime {
+                                text: text.to_owned(),
+                                what: "days".to_owned(),
+                                value: saturate(n),
+                            })?,
+                        Some(Span::Months(n)) => {
+                            let date =
+                                add_months(start.naive_local().date(), n).ok_or_else(|| {
+                                    DateTimeError::InvalidTime {
+                                        text: text.to_owned(),
+                                        what: "months".to_owned(),
+                                        value: n,
+                                    }
+                                })?;
+                            with_date(&start, date).ok_or_else(|| DateTimeError::InvalidTime {
+                                text: text.to_owned(),
+                                what: "hour".to_owned(),
+                                value: start.hour() as i32,
+                            })?
+                        }
+                        None => start.clone(),
+                    };
+                    Ok((start, end))
+                })
+            })
+            .collect()
+    }
+
+    /// Number of days between the first day of a week and Monday
+    fn week_start_offset(&self) -> i64 {
+        (7 - i64::from(self.week_start.num_days_from_monday())) % 7
+    }
+
+    /// Fiscal year the date belongs to
+    fn fiscal_year(&self, date: NaiveDate) -> i32 {
+        if self.fiscal_year_start == 1 || date.month() < self.fiscal_year_start {
+            date.year()
+        } else {
+            date.year() + 1
+        }
+    }
+
+    /// First day of the given fiscal year, None if it is out of the supported range
+    fn fiscal_year_first_day(&self, fiscal_year: i32) -> Option<NaiveDate> {
+        if self.fiscal_year_start == 1 {
+            NaiveDate::from_ymd_opt(fiscal_year, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(fiscal_year.checked_sub(1)?, self.fiscal_year_start, 1)
+        }
+    }
+
+    /// Returns the first day of a quarter or a half and its length in months, None if it is out
+    /// of the supported range
+    fn period_start(
+        &self,
+        today: NaiveDate,
+        period: Period,
+        year: Option<i32>,
+    ) -> Option<(NaiveDate, i32)> {
+        let fiscal_year = year.unwrap_or_else(|| self.fiscal_year(today));
+        let first_day = self.fiscal_year_first_day(fiscal_year)?;
+
+        // months passed since the beginning of the current fiscal year
+        let elapsed = (today.month() as i32 - self.fiscal_year_start as i32 + 12) % 12;
+        let current = self.fiscal_year_first_day(self.fiscal_year(today))?;
+        let shift = |n: i32, months: i32| {
+            let months = n
+                .checked_mul(months)?
+                .checked_add(elapsed / months * months)?;
+            add_months(current, months)
+        };
+
+        match period {
+            Period::Quarter(n) => Some((add_months(first_day, 3 * (n - 1))?, 3)),
+            Period::Half(n) => Some((add_months(first_day, 6 * (n - 1))?, 6)),
+            Period::QuarterShift(n) => Some((shift(n, 3)?, 3)),
+            Period::HalfShift(n) => Some((shift(n, 6)?, 6)),
+        }
+    }
 
-            let mut tz_aware = date_time.clone();
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        !self.weekend.contains(&date.weekday())
+            && !self
+                .days_off
+                .iter()
+                .any(|calendar| calendar.is_holiday(date))
+    }
 
-            tz_aware = tz_aware + ctx.duration;
+    /// Moves the date by the given number of business days, zero moves it to the nearest
+    /// business day which is not in the past. None if the resulting date is out of range.
+    fn add_business_days(&self, mut date: NaiveDate, days: i32) -> Option<NaiveDate> {
+        let sign = if days < 0 { -1 } else { 1 };
+        let step = Duration::days(sign);
+        let mut left = i64::from(days).abs();
 
-            if ctx.duration.num_seconds() % 60 == 0 {
-                tz_aware = tz_aware.with_second(0).unwrap();
+        // days off calendars may leave no business days at all, give up after a long enough
+        // run of days off instead of walking to the end of the dates range
+        let mut days_off_run = 0;
+        let mut next_day = |date: NaiveDate| {
+            let next = date.checked_add_signed(step)?;
+            if self.is_business_day(next) {
+                days_off_run = 0;
+            } else {
+                days_off_run += 1;
+            }
+            if days_off_run > MAX_DAYS_OFF {
+                None
+            } else {
+                Some(next)
             }
+        };
 
-            if ctx.year.is_some() {
-                tz_aware = tz_aware.with_year(ctx.year.unwrap()).unwrap();
+        if left == 0 {
+            while !self.is_business_day(date) {
+                date = next_day(date)?;
             }
-            if ctx.month.is_some() {
-                tz_aware = tz_aware.with_month(ctx.month.unwrap() as u32).unwrap();
+        }
+
+        // skip whole weeks, days off within them are given back, so at least one business day
+        // is always left to be counted one by one
+        let per_week = 7 - self.weekend.len() as i64;
+        while left > per_week {
+            let weeks = (left - 1) / per_week;
+            let next = date.checked_add_signed(Duration::weeks(sign * weeks))?;
+            let days_off = if self.days_off.is_empty() {
+                0
+            } else {
+                (1..=weeks * 7)
+                    .filter_map(|n| date.checked_add_signed(Duration::days(sign * n)))
+                    .filter(|&day| {
+                        !self.weekend.contains(&day.weekday()) && !self.is_business_day(day)
+                    })
+                    .count() as i64
+            };
+            if days_off == weeks * per_week {
+                break;
             }
-            if ctx.day.is_some() {
-                tz_aware = tz_aware.with_day(ctx.day.unwrap() as u32).unwrap();
+            left -= weeks * per_week - days_off;
+            date = next;
+        }
+
+        while left > 0 {
+            date = next_day(date)?;
+            if self.is_business_day(date) {
+                left -= 1;
             }
-            if ctx.hour.is_some() {
-                tz_aware = tz_aware.with_hour(ctx.hour.unwrap() as u32).unwrap();
+        }
+
+        Some(date)
+    }
+
+    /// Finds the first component of the context which is out of its range, rules are not
+    /// trusted here because custom ones may set any values
+    fn check(&self, ctx: &Context) -> Result<(), (&'static str, i32)> {
+        let within = |what, value: Option<i32>, min, max| match value {
+            Some(value) if value < min || value > max => Err((what, value)),
+            _ => Ok(()),
+        };
+        within("month", ctx.month, 1, 12)?;
+        within("day", ctx.day, 1, 31)?;
+        within("hour", ctx.hour, 0, 23)?;
+        within("minute", ctx.minute, 0, 59)?;
+        within("week", ctx.week, 1, 53)?;
+        within(
+            "business days",
+            ctx.business_days,
+            -consts::MAX_BUSINESS_DAYS,
+            consts::MAX_BUSINESS_DAYS,
+        )?;
+        match ctx.period {
+            Some(Period::Quarter(n)) => within("quarter", Some(n), 1, 4)?,
+            Some(Period::Half(n)) => within("half", Some(n), 1, 2)?,
+            _ => (),
+        }
+        if let Some(holiday) = ctx.holiday {
+            if holiday.id >= self.holiday_names.len() {
+                return Err(("holiday", holiday.id.min(i32::MAX as usize) as i32));
+            }
+            if holiday.day_offset.abs() > MAX_DAYS {
+                return Err(("days", saturate(holiday.day_offset)));
             }
-            if ctx.minute.is_some() {
-                tz_aware = tz_aware.with_minute(ctx.minute.unwrap() as u32).unwrap();
+        }
+        if let Some(Span::Days(n)) = ctx.span {
+            if n.abs() > MAX_DAYS {
+                return Err(("days", saturate(n)));
             }
+        }
+        Ok(())
+    }
+
+    /// Applies the context to the current time, text is the part of the input the context was
+    /// parsed from, it is reported along with errors
+    fn resolve(
+        &self,
+        date_time: DateTime<Tz>,
+        ctx: &Context,
+        text: &str,
+    ) -> Result<DateTime<Tz>, DateTimeError> {
+        let invalid = |what: &str, value: i32| DateTimeError::InvalidTime {
+            text: text.to_owned(),
+            what: what.to_owned(),
+            value,
+        };
+        // the local time may not exist on the new date, e.g. when clocks are moved forward
+        let missing_hour =
+            |date_time: &DateTime<Tz>| invalid("hour", date_time.naive_local().hour() as i32);
+        self.check(ctx)
+            .map_err(|(what, value)| invalid(what, value))?;
+
+        let mut tz_aware = date_time
+            .checked_add_signed(ctx.duration)
+            .ok_or_else(|| invalid("days", days(ctx.duration)))?;
 
-            tz_aware = tz_aware.with_nanosecond(0).unwrap();
+        if let Some(holiday) = ctx.holiday {
+            let (calendar, name) = &self.holiday_names[holiday.id];
+            let date_in = |year| {
+                self.holidays[*calendar]
+                    .date(name, year)
+                    .and_then(|date| date.checked_add_signed(Duration::days(holiday.day_offset)))
+            };
 
-            ready.push(Ok(tz_aware));
+            let today = tz_aware.naive_local().date();
+            let policy = holiday.policy.unwrap_or(self.year_policy);
+            let year = ctx
+                .year
+                .unwrap_or_else(|| match (policy, date_in(today.year())) {
+                    (YearPolicy::Future, Some(date)) if date < today => today.year() + 1,
+                    (YearPolicy::Past, Some(date)) if date > today => today.year() - 1,
+                    _ => today.year(),
+                });
+
+            let date = date_in(year).ok_or_else(|| DateTimeError::InvalidTime {
+                text: name.to_owned(),
+                what: "year".to_owned(),
+                value: year,
+            })?;
+            tz_aware = with_date(&tz_aware, date).ok_or_else(|| missing_hour(&tz_aware))?;
+        } else if let Some(period) = ctx.period {
+            let today = tz_aware.naive_local().date();
+            let year = ctx.year.unwrap_or_else(|| self.fiscal_year(today));
+            let (first_day, months) = self
+                .period_start(today, period, ctx.year)
+                .ok_or_else(|| invalid("year", year))?;
+            let date = if ctx.period_end {
+                add_months(first_day, months)
+                    .and_then(|date| date.pred_opt())
+                    .ok_or_else(|| invalid("year", year))?
+            } else {
+                first_day
+            };
+            tz_aware = with_date(&tz_aware, date).ok_or_else(|| missing_hour(&tz_aware))?;
+        } else if let Some(days) = ctx.business_days {
+            let date = self
+                .add_business_days(tz_aware.naive_local().date(), days)
+                .ok_or_else(|| invalid("business days", days))?;
+            tz_aware = with_date(&tz_aware, date).ok_or_else(|| missing_hour(&tz_aware))?;
+        } else if let Some(week) = ctx.week {
+            let year = ctx.year.unwrap_or_else(|| tz_aware.year());
+            // week and year may come from different matches, e.g. "w53 of 10/03/2019", and not
+            // every year has 53 weeks
+            let monday = NaiveDate::from_isoywd_opt(year, week as u32, Weekday::Mon)
+                .ok_or_else(|| invalid("week", week))?;
+            let date = match ctx.weekday {
+                Some(weekday) => monday
+                    .checked_add_signed(Duration::days(i64::from(weekday.num_days_from_monday()))),
+                None => monday.checked_sub_signed(Duration::days(self.week_start_offset())),
+            };
+            let date = date.ok_or_else(|| invalid("year", year))?;
+            tz_aware = with_date(&tz_aware, date).ok_or_else(|| missing_hour(&tz_aware))?;
+        } else if ctx.year.is_some() || ctx.month.is_some() || ctx.day.is_some() {
+            let today = tz_aware.naive_local().date();
+            let year = ctx.year.unwrap_or_else(|| today.year());
+            let month = ctx.month.map_or(today.month(), |month| month as u32);
+            let date = match ctx.day {
+                Some(day) => NaiveDate::from_ymd_opt(year, month, day as u32),
+                // the current day is clamped to the length of the month, e.g. "in june" said
+                // on May 31st
+                None => (1..=today.day())
+                    .rev()
+                    .find_map(|day| NaiveDate::from_ymd_opt(year, month, day)),
+            };
+            let date = date.ok_or_else(|| match ctx.day {
+                Some(day) if NaiveDate::from_ymd_opt(year, 1, 1).is_some() => invalid("day", day),
+                _ => invalid("year", year),
+            })?;
+            tz_aware = with_date(&tz_aware, date).ok_or_else(|| missing_hour(&tz_aware))?;
         }
-        ready
+
+        // intervals start at midnight unless time is given explicitly
+        let (hour, minute) = match ctx.span {
+            Some(_) => (ctx.hour.or(Some(0)), ctx.minute.or(Some(0))),
+            None => (ctx.hour, ctx.minute),
+        };
+
+        let time = tz_aware.naive_local().time();
+        let second =
+            if ctx.duration.num_seconds() % 60 == 0 || (ctx.span.is_some() && ctx.hour.is_none()) {
+                0
+            } else {
+                time.second()
+            };
+        let new_time = NaiveTime::from_hms(
+            hour.map_or(time.hour(), |hour| hour as u32),
+            minute.map_or(time.minute(), |minute| minute as u32),
+            second,
+        );
+        if new_time != time.with_nanosecond(0).unwrap_or(time) {
+            // the local time may not exist, e.g. when clocks are moved forward
+            tz_aware = with_time(&tz_aware, new_time)
+                .ok_or_else(|| invalid("hour", new_time.hour() as i32))?;
+        }
+
+        if let Some(offset) = ctx.offset {
+            let date = add_months(tz_aware.naive_local().date(), offset.months)
+                .ok_or_else(|| invalid("months", offset.months))?;
+            tz_aware = with_date(&tz_aware, date)
+                .ok_or_else(|| missing_hour(&tz_aware))?
+                .checked_add_signed(offset.duration)
+                .ok_or_else(|| invalid("days", days(offset.duration)))?;
+        }
+
+        let nanos = Duration::nanoseconds(i64::from(tz_aware.nanosecond()));
+        Ok(tz_aware - nanos)
+    }
+}
+
+/// Checks whether the rule name is the pattern itself or belongs to the group named by it,
+/// e.g. "en::weekdays" belongs to "en"
+fn rule_matches(name: &str, pattern: &str) -> bool {
+    name == pattern || (name.starts_with(pattern) && name[pattern.len()..].starts_with("::"))
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
     }
 }
+
+/// Replaces time part of date/time keeping the local date unchanged, None if there is no such
+/// local time
+fn with_time<Tz: TimeZone>(date_time: &DateTime<Tz>, time: NaiveTime) -> Option<DateTime<Tz>> {
+    let naive = date_time.naive_local().date().and_time(time);
+    date_time.timezone().from_local_datetime(&naive).earliest()
+}
+
+/// Replaces date part of date/time keeping the local time unchanged, None if there is no such
+/// local time on that date
+fn with_date<Tz: TimeZone>(date_time: &DateTime<Tz>, date: NaiveDate) -> Option<DateTime<Tz>> {
+    let naive = date.and_time(date_time.naive_local().time());
+    date_time.timezone().from_local_datetime(&naive).earliest()
+}
+
+/// Adds given number of months to a date, the day is clamped to the last day of the resulting
+/// month, i.e. January 31st plus one month is February 28th (or 29th). None if the resulting
+/// date is out of range.
+pub(crate) fn add_months(date: NaiveDate, months: i32) -> Option<NaiveDate> {
+    let total = (date.year() * 12 + date.month0() as i32).checked_add(months)?;
+    let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+    (1..=date.day())
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+}
+
+/// Business days are not searched for further than this many days off in a row, it's ten years
+const MAX_DAYS_OFF: i64 = 3660;
+
+/// Longer intervals don't fit into the supported dates range, it's about 262 thousand years
+const MAX_DAYS: i64 = 100_000_000;
+
+/// Whole days of the duration for error reports, see "saturate"
+fn days(duration: Duration) -> i32 {
+    saturate(duration.num_days())
+}
+
+/// Value clamped to the i32 range for error reports
+fn saturate(value: i64) -> i32 {
+    value.clamp(i64::from(i32::MIN), i64::from(i32::MAX)) as i32
+}
@@ -0,0 +1,228 @@
+//! Custom rules, they make parser understand domain specific expressions, e.g. "next sprint",
+//! without forking the crate.
+//!
+//! Rule gets lowercased input text and returns all its matches, helpers below split the text
+//! into words and fuzzy match words the same way built-in rules do:
+//!
+//! ```
+//! use chrono::{DateTime, TimeZone};
+//! use when::rule::{recognize_phrase, words, Context, Rule, RuleMatch};
+//!
+//! // sprints are two weeks long
+//! struct NextSprint;
+//!
+//! impl<Tz: TimeZone> Rule<Tz> for NextSprint {
+//!     fn name(&self) -> &str {
+//!         "next_sprint"
+//!     }
+//!
+//!     fn apply(&self, input: &str, exact_match: bool, _now: &DateTime<Tz>) -> Vec<RuleMatch> {
+//!         words(input)
+//!             .iter()
+//!             .filter_map(|word| {
+//!                 let text = &input[word.start_idx..];
+//!                 let (len, _dist) = recognize_phrase(text, &["next", "sprint"], exact_match)?;
+//!                 let mut context = Context::default();
+//!                 context.set_duration(14 * 24 * 3600);
+//!                 Some(RuleMatch::new(word.start_idx, word.start_idx + len, Ok(context)))
+//!             })
+//!             .collect()
+//!     }
+//! }
+//!
+//! let parser = when::Parser::new(chrono_tz::Europe::Moscow).with_rule(Box::new(NextSprint));
+//! assert_eq!(parser.parse("let's do it next sprnt").len(), 1);
+//! ```
+
+use crate::languages::Language;
+use crate::rules::combinators;
+use crate::rules::errors::DateTimeError;
+use chrono::{DateTime, TimeZone};
+use nom::types::CompleteStr;
+
+pub use crate::rules::rules::{Context, HolidayRef, Offset, Period, Span};
+
+/// Single match of a rule, indices are byte offsets in the input text
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleMatch {
+    pub start_idx: usize,
+    pub end_idx: usize,
+    /// meaning of the matched text or the reason why it is not a valid date/time
+    pub result: Result<Context, DateTimeError>,
+    /// from 0 to 1, see "confidence"
+    pub confidence: f64,
+    /// valid expressions close to an erroneous one, e.g. "28th of february" for
+    /// "30th of february", see "Diagnostic"
+    pub suggestions: Vec<String>,
+    /// what an erroneous match states anyway, e.g. the weekday of "tuesday" which is ambiguous
+    /// on its own, parser merges it with an explicit date next to it, e.g. "tuesday, march 5"
+    pub stated: Option<Context>,
+}
+
+impl RuleMatch {
+    pub fn new(start_idx: usize, end_idx: usize, result: Result<Context, DateTimeError>) -> Self {
+        RuleMatch {
+            start_idx,
+            end_idx,
+            result,
+            confidence: 1.0,
+            suggestions: Vec::new(),
+            stated: None,
+        }
+    }
+
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    pub fn with_stated(mut self, stated: Option<Context>) -> Self {
+        self.stated = stated;
+        self
+    }
+}
+
+/// Rule recognizes one kind of expressions, e.g. weekdays, matches of all rules are combined
+/// by parser: the best non overlapping matches are selected (see "Parser::alternatives"),
+/// equally good overlapping matches are reported as errors and close matches are merged.
+/// Rules are shared by threads parsing with the same parser, so they must be thread safe.
+pub trait Rule<Tz: TimeZone>: Send + Sync {
+    /// Name which identifies the rule, built-in rules are named like "en::weekdays"
+    fn name(&self) -> &str;
+
+    /// Returns all matches found in the lowercased input, they may overlap, e.g. both
+    /// "next sprint" and "sprint" may be returned. Fuzzy matching should be disabled if
+    /// exact_match is set, now is the current time in the parser time zone.
+    fn apply(&self, input: &str, exact_match: bool, now: &DateTime<Tz>) -> Vec<RuleMatch>;
+
+    /// Language of the expressions the rule recognizes, it is reported along with results and
+    /// used to resolve intersections with matches of other languages, see "Parser::languages"
+    fn language(&self) -> Option<Language> {
+        None
+    }
+}
+
+/// Word of the input text with its position, digits, '.' and ':' are parts of words,
+/// e.g. "10:30" and "a.m." are words
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Word<'a> {
+    pub text: &'a str,
+    pub start_idx: usize,
+    pub end_idx: usize,
+}
+
+/// Splits text into words the same way built-in rules do
+pub fn words(input: &str) -> Vec<Word<'_>> {
+    let mut result = Vec::new();
+    let mut rest = CompleteStr(input);
+
+    while let Some((tail, word)) = combinators::next_word(rest) {
+        let end_idx = input.len() - tail.len();
+        if !word.is_empty() {
+            result.push(Word {
+                text: *word,
+                start_idx: end_idx - word.len(),
+                end_idx,
+            });
+        }
+        rest = tail;
+    }
+
+    result
+}
+
+/// Fuzzy matches the first word of the input against the pattern, the word may differ from the
+/// pattern in at most max_dist edits unless exact match is required. Returns number of consumed
+/// bytes, including separators before the word, and the distance.
+pub fn recognize_word(
+    input: &str,
+    pattern: &str,
+    max_dist: usize,
+    exact_match: bool,
+) -> Option<(usize, usize)> {
+    let max_dist = combinators::Dist(if exact_match { 0 } else { max_dist });
+    combinators::recognize_word(
+        CompleteStr(input),
+        CompleteStr(pattern),
+        max_dist,
+        crate::rules::tokens::PToken::Stub,
+    )
+    .ok()
+    .map(|(tail, desc)| (input.len() - tail.len(), desc.dist.0))
+}
+
+/// Same as "recognize_word" but for a sequence of words, e.g. ["release", "freeze"], allowed
+/// distance for every word depends on its length. Returns number of consumed bytes and the total
+/// distance.
+pub fn recognize_phrase(input: &str, words: &[&str], exact_match: bool) -> Option<(usize, usize)> {
+    combinators::recognize_phrase(CompleteStr(input), words, exact_match)
+        .map(|(tail, dist)| (input.len() - tail.len(), dist.0))
+}
+
+/// Confidence of a match the same way as built-in rules compute it, accepts typos cost (the
+/// distance for the default typo model) and length in letters of every matched word (numbers
+/// have zero length): typos in short words and matches of a single word are less likely to be
+/// dates
+pub fn confidence(evidence: &[(f64, usize)]) -> f64 {
+    combinators::match_confidence(evidence)
+}
+
+/// Recognizes an integer at the beginning of the input, e.g. "-15", returns number of consumed
+/// bytes and the number
+pub fn recognize_int(input: &str) -> Option<(usize, i32)> {
+    combinators::recognize_int(CompleteStr(input))
+        .ok()
+        .map(|(tail, n)| (input.len() - tail.len(), n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{confidence, recognize_int, recognize_phrase, recognize_word, words};
+
+    #[test]
+    fn test_words() {
+        let found: Vec<_> = words("meet me, at 10:30 - ok?")
+            .iter()
+            .map(|word| (word.text, word.start_idx, word.end_idx))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("meet", 0, 4),
+                ("me", 5, 7),
+                ("at", 9, 11),
+                ("10:30", 12, 17),
+                ("ok", 20, 22)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recognizers() {
+        assert_eq!(
+            recognize_word("  sprnt review", "sprint", 1, false),
+            Some((7, 1))
+        );
+        assert_eq!(recognize_word("sprnt review", "sprint", 1, true), None);
+        assert_eq!(
+            recognize_phrase("release freze starts", &["release", "freeze"], false),
+            Some((13, 1))
+        );
+        assert_eq!(recognize_int(" 42 days"), Some((3, 42)));
+        assert_eq!(recognize_int("days"), None);
+    }
+
+    #[test]
+    fn test_confidence() {
+        assert_eq!(confidence(&[(0.0, 4), (0.0, 6)]), 1.0);
+        assert_eq!(confidence(&[(0.0, 6)]), 0.9);
+        assert_eq!(confidence(&[(1.0, 4), (0.0, 6)]), 0.8);
+        assert_eq!(confidence(&[(1.0, 4)]), 0.45);
+        assert_eq!(confidence(&[(3.0, 4)]), 0.0);
+    }
+}
@@ -0,0 +1,150 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_time_of_day;
+use super::consts;
+use super::errors::SemanticError;
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, Pronouns, TimeOfDay, Token, When};
+use chrono::prelude::*;
+
+use nom::{
+    alt, apply, call, many_till, named_args, opt, preceded, terminated, tuple, types::CompleteStr,
+};
+
+define!(now: (Token::When(When::Now), Priority(0)), "agora", Dist(1));
+
+define!(today: (Token::When(When::Today), Priority(1)), "hoje", Dist(1));
+define!(tomorrow: (Token::When(When::Tomorrow), Priority(1)), "amanhã", Dist(1));
+define!(yesterday: (Token::When(When::Yesterday), Priority(1)), "ontem", Dist(1));
+define!(
+    day_before_yesterday: (Token::When(When::DayBeforeYesterday), Priority(1)), "anteontem", Dist(2)
+);
+
+define!(after: (Token::When(When::DayAfterTomorrow), Priority(1)), "depois", Dist(1));
+define!(of: (Token::Char, Priority(4)), "de", Dist(0));
+define!(tomorrow_word: (Token::Char, Priority(4)), "amanhã", Dist(1));
+
+// e.g. "depois de amanhã"
+named_args!(day_after_tomorrow<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(after, exact_match),
+                preceded!(apply!(of, true), apply!(tomorrow_word, exact_match)))
+);
+
+combine!(when => day_after_tomorrow | today | tomorrow | yesterday | day_before_yesterday);
+
+// e.g. "esta noite", "ontem à noite"
+
+define!(
+    this:
+    [(Token::Pronouns(Pronouns::This), Priority(1)), "esta", Dist(0)] |
+    [(Token::Pronouns(Pronouns::This), Priority(1)), "nesta", Dist(0)]
+);
+
+define!(morning: (Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "manhã", Dist(1));
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(2)), "tarde", Dist(1));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "noite", Dist(1));
+
+define!(half: (Token::TimeOfDay(TimeOfDay::Noon), Priority(2)), "meio", Dist(0));
+define_char!(dash: Priority(4), '-');
+define!(day: (Token::Char, Priority(4)), "dia", Dist(0));
+
+// e.g. "meio-dia", "meio dia"
+named_args!(noon<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(half, exact_match), preceded!(opt!(dash), apply!(day, true)))
+);
+
+combine!(time_of_day => morning | afternoon | evening | noon);
+
+// prepositions before parts of the day: "à noite", "de manhã", "pela manhã", "ao meio-dia"
+
+define!(
+    prep:
+    [(Token::Char, Priority(3)), "à", Dist(0)] |
+    [(Token::Char, Priority(3)), "a", Dist(0)] |
+    [(Token::Char, Priority(3)), "de", Dist(0)] |
+    [(Token::Char, Priority(3)), "pela", Dist(0)] |
+    [(Token::Char, Priority(3)), "ao", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // amanhã à noite, hoje de manhã, ontem ao meio-dia, etc.
+            tuple!(apply!(when, exact_match),
+                   preceded!(opt!(apply!(prep, true)), apply!(time_of_day, exact_match))) |
+            // esta noite, nesta manhã
+            tuple!(apply!(this, true), apply!(time_of_day, exact_match)) |
+            // hoje, amanhã, depois de amanhã, etc.
+            tuple!(apply!(when, exact_match), stub) |
+            // agora
+            tuple!(apply!(now, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    _input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::When(When::Yesterday)) => ctx.set_duration(-consts::DAY),
+        Some(Token::When(When::Tomorrow)) => ctx.set_duration(consts::DAY),
+        Some(Token::When(When::DayAfterTomorrow)) => ctx.set_duration(2 * consts::DAY),
+        Some(Token::When(When::DayBeforeYesterday)) => ctx.set_duration(-2 * consts::DAY),
+        _ => (),
+    }
+
+    if let Some(hour) = match_time_of_day(res.token_by_priority(Priority(2))) {
+        ctx.hour = Some(hour);
+        ctx.minute = Some(0);
+        ctx.part_of_day = true;
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_casual_date() {
+        let result = interpret("me liga amanhã à noite", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(8, 24)));
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 18);
+
+        let result = interpret("hoje de manha", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 0);
+        assert_eq!(result.get_hours(), 8);
+
+        let result = interpret("depois de amanhã", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * consts::DAY as i64);
+
+        let result = interpret("foi anteontem", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -2 * consts::DAY as i64);
+
+        let result = interpret("ontem ao meio-dia", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -consts::DAY as i64);
+        assert_eq!(result.get_hours(), 12);
+
+        let result = interpret("esta tarde", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("agora mesmo", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 5)));
+        assert_eq!(result.get_duration_sec(), 0);
+    }
+}
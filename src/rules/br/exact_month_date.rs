@@ -0,0 +1,115 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::month_date as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Month, Priority, Token, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+define_num!(day_num: (Token::Number, Priority(0)));
+
+// the first day of a month is usually written as an ordinal, e.g. "1º de maio"
+define_suffixed_num!(day_ord: (Token::Number, Priority(0)), "º" | "o");
+
+define!(day_word: (Token::Char, Priority(2)), "dia", Dist(0));
+
+define!(january: (Token::Month(Month::January), Priority(1)), "janeiro", Dist(1));
+define!(february: (Token::Month(Month::February), Priority(1)), "fevereiro", Dist(1));
+define!(march: (Token::Month(Month::March), Priority(1)), "março", Dist(1));
+define!(april: (Token::Month(Month::April), Priority(1)), "abril", Dist(1));
+define!(may: (Token::Month(Month::May), Priority(1)), "maio", Dist(0));
+define!(june: (Token::Month(Month::June), Priority(1)), "junho", Dist(0));
+define!(july: (Token::Month(Month::July), Priority(1)), "julho", Dist(0));
+define!(august: (Token::Month(Month::August), Priority(1)), "agosto", Dist(1));
+define!(september: (Token::Month(Month::September), Priority(1)), "setembro", Dist(1));
+define!(october: (Token::Month(Month::October), Priority(1)), "outubro", Dist(1));
+define!(november: (Token::Month(Month::November), Priority(1)), "novembro", Dist(1));
+define!(december: (Token::Month(Month::December), Priority(1)), "dezembro", Dist(1));
+
+combine!(month => january | february | march | april | may | june | july | august | september |
+                  october | november | december);
+
+// abbreviations like "mar" or "dez" are ordinary words as well, so they are only accepted
+// after the day number, e.g. "5 mar"
+
+define!(
+    month_abbr:
+    [(Token::Month(Month::January), Priority(1)), "jan", Dist(0)] |
+    [(Token::Month(Month::February), Priority(1)), "fev", Dist(0)] |
+    [(Token::Month(Month::March), Priority(1)), "mar", Dist(0)] |
+    [(Token::Month(Month::April), Priority(1)), "abr", Dist(0)] |
+    [(Token::Month(Month::May), Priority(1)), "mai", Dist(0)] |
+    [(Token::Month(Month::June), Priority(1)), "jun", Dist(0)] |
+    [(Token::Month(Month::July), Priority(1)), "jul", Dist(0)] |
+    [(Token::Month(Month::August), Priority(1)), "ago", Dist(0)] |
+    [(Token::Month(Month::September), Priority(1)), "set", Dist(0)] |
+    [(Token::Month(Month::October), Priority(1)), "out", Dist(0)] |
+    [(Token::Month(Month::November), Priority(1)), "nov", Dist(0)] |
+    [(Token::Month(Month::December), Priority(1)), "dez", Dist(0)]
+);
+
+define!(of: (Token::Char, Priority(2)), "de", Dist(0));
+
+define!(
+    in_:
+    [(Token::When(When::In), Priority(2)), "em", Dist(0)] |
+    [(Token::When(When::In), Priority(2)), "no", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 5 de março, dia 1º de maio, 10 dez
+            tuple!(preceded!(opt!(apply!(day_word, true)), alt!(day_num | day_ord)),
+                   preceded!(opt!(apply!(of, true)),
+                             alt!(apply!(month, exact_match) | apply!(month_abbr, true)))) |
+            // março, em janeiro
+            tuple!(preceded!(opt!(apply!(in_, true)), apply!(month, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_exact_month() {
+        let result = interpret("a festa é 5 de março", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(11, 22)));
+        assert_eq!(result.get_day(), 5);
+        assert_eq!(result.get_month(), 3);
+
+        let result = interpret("dia 1º de maio", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 1);
+        assert_eq!(result.get_month(), 5);
+
+        let result = interpret("25 dez", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 25);
+        assert_eq!(result.get_month(), 12);
+
+        let result = interpret("em setembro", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 1);
+        assert_eq!(result.get_month(), 9);
+
+        let result = interpret("dez reais", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+
+        let result = interpret("30 de fevereiro", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("30 de fevereiro", "day", 30).extract_error()
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::past_time as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Adverbs, IntWord, Priority, TimeInterval, Token};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, terminated, tuple, types::CompleteStr};
+
+define!(
+    ago:
+    [(Token::Ago, Priority(0)), "há", Dist(0)] |
+    [(Token::Ago, Priority(0)), "ha", Dist(0)] |
+    [(Token::Ago, Priority(0)), "faz", Dist(0)]
+);
+
+define!(
+    back:
+    [(Token::Ago, Priority(0)), "atrás", Dist(1)] |
+    [(Token::Ago, Priority(0)), "atras", Dist(1)]
+);
+
+define!(
+    one:
+    [(Token::IntWord(IntWord::One), Priority(1)), "um", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "uma", Dist(0)]
+);
+define!(
+    two:
+    [(Token::IntWord(IntWord::Two), Priority(1)), "dois", Dist(0)] |
+    [(Token::IntWord(IntWord::Two), Priority(1)), "duas", Dist(0)]
+);
+define!(three: (Token::IntWord(IntWord::Three), Priority(1)), "três", Dist(1));
+define!(four: (Token::IntWord(IntWord::Four), Priority(1)), "quatro", Dist(1));
+define!(five: (Token::IntWord(IntWord::Five), Priority(1)), "cinco", Dist(1));
+define!(six: (Token::IntWord(IntWord::Six), Priority(1)), "seis", Dist(0));
+define!(seven: (Token::IntWord(IntWord::Seven), Priority(1)), "sete", Dist(0));
+define!(eight: (Token::IntWord(IntWord::Eight), Priority(1)), "oito", Dist(0));
+define!(nine: (Token::IntWord(IntWord::Nine), Priority(1)), "nove", Dist(0));
+define!(ten: (Token::IntWord(IntWord::Ten), Priority(1)), "dez", Dist(0));
+define!(eleven: (Token::IntWord(IntWord::Eleven), Priority(1)), "onze", Dist(0));
+define!(twelve: (Token::IntWord(IntWord::Twelve), Priority(1)), "doze", Dist(0));
+
+combine!(int_word => one | two | three | four | five | six | seven | eight | nine | ten
+                         | eleven | twelve);
+
+define_num!(number: (Token::Number, Priority(1)));
+
+define!(
+    few:
+    [(Token::Adverbs(Adverbs::Few), Priority(1)), "alguns", Dist(1)] |
+    [(Token::Adverbs(Adverbs::Few), Priority(1)), "algumas", Dist(1)]
+);
+
+define!(half: (Token::Adverbs(Adverbs::Half), Priority(1)), "meia", Dist(0));
+define!(hour: (Token::Char, Priority(2)), "hora", Dist(0));
+
+// e.g. "meia hora"
+named_args!(half_hour<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(half, exact_match), apply!(hour, exact_match))
+);
+
+define!(
+    seconds:
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "segundo", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "segundos", Dist(1)]
+);
+define!(
+    minutes:
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "minuto", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "minutos", Dist(1)]
+);
+define!(
+    hours:
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "hora", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "horas", Dist(1)]
+);
+define!(
+    days:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "dia", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "dias", Dist(0)]
+);
+define!(
+    weeks:
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "semana", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "semanas", Dist(1)]
+);
+define!(
+    months:
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "mês", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "meses", Dist(1)]
+);
+define!(
+    years:
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "ano", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "anos", Dist(0)]
+);
+
+combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // há 3 horas, faz dois dias, há alguns minutos
+            tuple!(apply!(ago, true),
+                   alt!(number | apply!(int_word, exact_match) | apply!(few, exact_match)),
+                   apply!(time_interval, exact_match)) |
+            // há meia hora
+            tuple!(apply!(ago, true), apply!(half_hour, exact_match), stub) |
+            // 3 horas atrás, cinco minutos atrás
+            tuple!(alt!(number | apply!(int_word, exact_match) | apply!(few, exact_match)),
+                   apply!(time_interval, exact_match), apply!(back, exact_match)) |
+            // meia hora atrás
+            tuple!(apply!(half_hour, exact_match), stub, apply!(back, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_past_time() {
+        let result = interpret("ele saiu há 3 horas", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(9, 20)));
+        assert_eq!(result.get_duration_sec() as i32, -3 * consts::HOUR);
+
+        let result = interpret("cinco minutos atrás", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -5 * consts::MINUTE);
+
+        let result = interpret("faz duas semanas", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -2 * consts::WEEK);
+
+        let result = interpret("há meia hora", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -30 * consts::MINUTE);
+
+        let result = interpret("ha alguns dias", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -3 * consts::DAY);
+
+        let result = interpret("há um mês", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -31 * consts::DAY);
+    }
+}
@@ -0,0 +1,165 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, TimeOfDay, Token, When};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, preceded, tuple, types::CompleteStr};
+
+define!(
+    at:
+    [(Token::When(When::In), Priority(0)), "às", Dist(0)] |
+    [(Token::When(When::In), Priority(0)), "as", Dist(0)] |
+    [(Token::When(When::In), Priority(0)), "ás", Dist(0)]
+);
+
+define_num!(hours: (Token::Number, Priority(1)));
+
+define_char!(colon: Priority(2), ':');
+
+define_num!(minutes: (Token::Number, Priority(3)));
+
+// hours glued to "h" and optional minutes, e.g. "15h", "15h30"
+
+define_hours_h!(hours_h: (Token::Number, Priority(1)));
+define_glued_num!(glued_minutes: (Token::Number, Priority(3)));
+
+define!(
+    hour_word:
+    [(Token::Char, Priority(4)), "hora", Dist(0)] |
+    [(Token::Char, Priority(4)), "horas", Dist(1)]
+);
+
+// parts of the day, e.g. "às 3 da tarde", "às 8 da manhã"
+
+define!(
+    of:
+    [(Token::Char, Priority(5)), "da", Dist(0)] |
+    [(Token::Char, Priority(5)), "de", Dist(0)]
+);
+
+define!(morning: (Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "manhã", Dist(1));
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(5)), "tarde", Dist(1));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(5)), "noite", Dist(1));
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(5)), "madrugada", Dist(2));
+
+combine!(time_of_day => morning | afternoon | evening | night);
+
+named_args!(day_part<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    preceded!(apply!(of, true), apply!(time_of_day, exact_match))
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // hours:minutes and part of the day, e.g. "às 10:30 da noite", "17:45"
+            tuple!(alt!(apply!(at, true) | stub), hours, colon, minutes, stub,
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // hours glued to "h", e.g. "às 15h30", "15h"
+            tuple!(alt!(apply!(at, true) | stub), hours_h, stub, alt!(glued_minutes | stub), stub,
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // e.g. "às 10 horas", "às 3 horas da tarde"
+            tuple!(apply!(at, true), hours, stub, stub, apply!(hour_word, exact_match),
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // e.g. "às 3 da tarde", "8 da manhã"
+            tuple!(alt!(apply!(at, true) | stub), hours, stub, stub, stub,
+                   apply!(day_part, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 6);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let mut hrs = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    let minutes = match res.token_by_priority(Priority(3)) {
+        Some(Token::Number(n)) => n,
+        _ => 0,
+    };
+
+    if !(0..=23).contains(&hrs) {
+        return Err(invalid_time_error(input, "hours", hrs));
+    }
+    if !(0..=59).contains(&minutes) {
+        return Err(invalid_time_error(input, "minutes", minutes));
+    }
+
+    match res.token_by_priority(Priority(5)) {
+        // "12 da noite" is midnight
+        Some(Token::TimeOfDay(TimeOfDay::Evening)) if hrs == 12 => hrs = 0,
+        Some(Token::TimeOfDay(TimeOfDay::Afternoon))
+        | Some(Token::TimeOfDay(TimeOfDay::Evening))
+            if hrs < 12 =>
+        {
+            hrs += 12
+        }
+        _ => (),
+    }
+
+    ctx.hour = Some(hrs);
+    ctx.minute = Some(minutes);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_time() {
+        let result = interpret("reunião às 15h30", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(9, 18)));
+        assert_eq!(result.get_hours(), 15);
+        assert_eq!(result.get_minutes(), 30);
+
+        let result = interpret("às 9h", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 9);
+        assert_eq!(result.get_minutes(), 0);
+
+        let result = interpret("as 17:45", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 17);
+        assert_eq!(result.get_minutes(), 45);
+
+        let result = interpret("às 3 da tarde", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("às 10 horas da noite", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 22);
+
+        let result = interpret("8 da manha", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 8);
+
+        let result = interpret("12 da noite", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 0);
+
+        let result = interpret("em 3 horas", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+
+    #[test]
+    fn test_wrong_times() {
+        let result = interpret("25h10", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("25h10", "hours", 25).extract_error()
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::weekday as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Priority, Token, Weekday as Day, When};
+use chrono::prelude::*;
+
+use nom::{
+    alt, apply, call, many_till, named_args, opt, preceded, terminated, tuple, types::CompleteStr,
+};
+
+define!(
+    monday:
+    [(Token::Weekday(Day::Monday), Priority(0)), "segunda", Dist(0)] |
+    [(Token::Weekday(Day::Monday), Priority(0)), "seg", Dist(0)]
+);
+
+define!(
+    tuesday:
+    [(Token::Weekday(Day::Tuesday), Priority(0)), "terça", Dist(1)] |
+    [(Token::Weekday(Day::Tuesday), Priority(0)), "ter", Dist(0)]
+);
+
+define!(
+    wednesday:
+    [(Token::Weekday(Day::Wednesday), Priority(0)), "quarta", Dist(1)] |
+    [(Token::Weekday(Day::Wednesday), Priority(0)), "qua", Dist(0)]
+);
+
+define!(
+    thursday:
+    [(Token::Weekday(Day::Thursday), Priority(0)), "quinta", Dist(1)] |
+    [(Token::Weekday(Day::Thursday), Priority(0)), "qui", Dist(0)]
+);
+
+define!(
+    friday:
+    [(Token::Weekday(Day::Friday), Priority(0)), "sexta", Dist(1)] |
+    [(Token::Weekday(Day::Friday), Priority(0)), "sex", Dist(0)]
+);
+
+define!(
+    saturday:
+    [(Token::Weekday(Day::Saturday), Priority(0)), "sábado", Dist(1)] |
+    [(Token::Weekday(Day::Saturday), Priority(0)), "sáb", Dist(0)] |
+    [(Token::Weekday(Day::Saturday), Priority(0)), "sab", Dist(0)]
+);
+
+define!(
+    sunday:
+    [(Token::Weekday(Day::Sunday), Priority(0)), "domingo", Dist(1)] |
+    [(Token::Weekday(Day::Sunday), Priority(0)), "dom", Dist(0)]
+);
+
+combine!(day_of_week => monday | tuesday | wednesday | thursday | friday | saturday | sunday);
+
+// weekdays from monday to friday are usually followed by "-feira", e.g. "sexta-feira"
+
+define_char!(dash: Priority(3), '-');
+define!(feira: (Token::Char, Priority(3)), "feira", Dist(1));
+
+named_args!(weekday<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(day_of_week, exact_match),
+                opt!(preceded!(opt!(dash), apply!(feira, exact_match))))
+);
+
+// adjectives agree with the gender of the day: "próxima sexta", "próximo sábado"
+
+define!(
+    this:
+    [(Token::When(When::This), Priority(1)), "esta", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "este", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "nesta", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "neste", Dist(0)]
+);
+
+define!(
+    next:
+    [(Token::When(When::Next), Priority(1)), "próxima", Dist(1)] |
+    [(Token::When(When::Next), Priority(1)), "próximo", Dist(1)]
+);
+
+define!(
+    last:
+    [(Token::When(When::Last), Priority(1)), "última", Dist(1)] |
+    [(Token::When(When::Last), Priority(1)), "último", Dist(1)]
+);
+
+combine!(when => this | next | last);
+
+// adjectives which follow the day, e.g. "sexta passada", "sábado que vem"
+
+define!(
+    past:
+    [(Token::When(When::Last), Priority(1)), "passada", Dist(1)] |
+    [(Token::When(When::Last), Priority(1)), "passado", Dist(1)]
+);
+
+define!(that: (Token::When(When::Next), Priority(1)), "que", Dist(0));
+define!(comes: (Token::When(When::Next), Priority(1)), "vem", Dist(0));
+
+// e.g. "que vem"
+named_args!(coming<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    preceded!(apply!(that, true), apply!(comes, exact_match))
+);
+
+define!(
+    prep:
+    [(Token::Char, Priority(2)), "na", Dist(0)] |
+    [(Token::Char, Priority(2)), "no", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // when and then any day of week, e.g. "na próxima sexta"
+            tuple!(preceded!(opt!(apply!(prep, true)), apply!(when, exact_match)),
+                   apply!(weekday, exact_match)) |
+            // day of week and then when, e.g. "sexta-feira passada", "no sábado que vem"
+            tuple!(preceded!(opt!(apply!(prep, true)), apply!(weekday, exact_match)),
+                   alt!(apply!(past, exact_match) | apply!(coming, exact_match))) |
+            // day of week, e.g. "na segunda"
+            tuple!(preceded!(opt!(apply!(prep, true)), apply!(weekday, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::ambiguous_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_weekdays() {
+        let result = interpret("até a próxima sexta", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(7, 21)));
+        assert_eq!(result.get_duration_sec(), 3 * 86400);
+
+        let result = interpret("na segunda-feira passada", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -86400);
+
+        let result = interpret("sabado que vem", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 4 * 86400);
+
+        let result = interpret("na quarta feira", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 86400);
+
+        let result = interpret("última terça", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -7 * 86400);
+
+        let result = interpret("no domingo", true, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 5 * 86400);
+
+        let result = interpret("segunda", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            ambiguous_time_error("segunda").extract_error()
+        );
+    }
+}
@@ -0,0 +1,413 @@
+use super::errors::{intersection_error, Diagnostic, SemanticError};
+use super::lattice::Correction;
+use super::rules::{Context, MatchBounds, MatchResult};
+use crate::rule::{words, Word};
+
+/// Score a match loses for every match selected, so a match which covers words together beats
+/// several matches which cover the same words separately, e.g. "review tomorrow" as a whole
+/// rather than "review" and "tomorrow"
+const SPECIFICITY_PENALTY: f64 = 0.1;
+
+/// Scores closer than that are considered equal
+const EPSILON: f64 = 1e-9;
+
+/// Candidate match or error of a rule along with its score and whether it is a part of the
+/// parse result
+#[derive(Debug, Clone)]
+pub(crate) struct ChartEntry {
+    pub item: Result<MatchResult, Diagnostic>,
+    pub start_idx: usize,
+    pub end_idx: usize,
+    pub score: f64,
+    pub selected: bool,
+}
+
+/// All candidates which rules found in the input and the best non overlapping cover of the
+/// input selected among them
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Chart {
+    pub entries: Vec<ChartEntry>,
+    // selected matches and errors ordered by their positions, intersections which couldn't be
+    // resolved are reported as errors
+    pub selected: Vec<Result<MatchResult, Diagnostic>>,
+    // words rules recognized only with typos
+    pub corrections: Vec<Correction>,
+    // bounds of the selected errors which state something anyway, e.g. the weekday of
+    // "tuesday" said on friday, along with what they state
+    pub stated: Vec<(usize, usize, Context)>,
+}
+
+fn item_bounds(item: &Result<MatchResult, SemanticError>) -> (usize, usize) {
+    match item {
+        Ok(x) => (x.get_start_idx(), x.get_end_idx()),
+        Err(x) => (x.get_start_idx(), x.get_end_idx()),
+    }
+}
+
+/// Score of a candidate: number of words it covers weighted by its confidence, errors are as
+/// good as confident matches
+fn score(words: &[Word], item: &Result<MatchResult, SemanticError>) -> f64 {
+    let (start_idx, end_idx) = item_bounds(item);
+    let covered = words
+        .iter()
+        .filter(|word| word.start_idx >= start_idx && word.end_idx <= end_idx)
+        .count()
+        .max(1);
+    let confidence = item.as_ref().map_or(1.0, |m| m.get_confidence());
+    confidence * covered as f64 - SPECIFICITY_PENALTY
+}
+
+/// Shrinks bounds of the candidate to the words it covers, so candidates which differ only in
+/// separators around them, e.g. " 6pm" and "6pm", are the same
+fn trim_to_words<'a>(
+    words: &[Word],
+    item: Result<MatchResult, SemanticError<'a>>,
+) -> Result<MatchResult, SemanticError<'a>> {
+    let (start_idx, end_idx) = item_bounds(&item);
+    let mut covered = words
+        .iter()
+        .filter(|word| word.start_idx >= start_idx && word.end_idx <= end_idx);
+
+    let first = match covered.next() {
+        Some(first) => first,
+        None => return item,
+    };
+    let last = covered.next_back().unwrap_or(first);
+    let bounds = MatchBounds::new(first.start_idx, last.end_idx);
+
+    match item {
+        Ok(item) => Ok(item.with_bounds(bounds)),
+        Err(mut err) => {
+            err.set_bounds(bounds);
+            Err(err)
+        }
+    }
+}
+
+fn same_candidate(
+    first: &Result<MatchResult, SemanticError>,
+    second: &Result<MatchResult, SemanticError>,
+) -> bool {
+    item_bounds(first) == item_bounds(second)
+        && match (first, second) {
+            (Ok(first), Ok(second)) => first.get_timeshift() == second.get_timeshift(),
+            (Err(first), Err(second)) => first.extract_error() == second.extract_error(),
+            _ => false,
+        }
+}
+
+/// Selects candidates of one cluster of overlapping candidates (given by their indices in
+/// entries) which maximize the total score, None if several selections are equally good
+fn best_cover(entries: &[ChartEntry], cluster: &[usize]) -> Option<Vec<usize>> {
+    let mut by_end = cluster.to_vec();
+    by_end.sort_by_key(|&idx| (entries[idx].end_idx, entries[idx].start_idx));
+
+    // best[i] is the best total score of candidates among the first i ones, ways[i] is the
+    // number of selections which have that score (capped at 2, more doesn't matter)
+    let mut best = vec![0.0];
+    let mut ways = vec![1];
+    // number of candidates which end before the i-th one starts
+    let mut previous = Vec::new();
+
+    for (i, &idx) in by_end.iter().enumerate() {
+        let start_idx = entries[idx].start_idx;
+        let p = by_end[..i]
+            .iter()
+            .take_while(|&&other| entries[other].end_idx <= start_idx)
+            .count();
+        previous.push(p);
+
+        let skip = best[i];
+        let take = entries[idx].score + best[p];
+        if (take - skip).abs() < EPSILON {
+            best.push(skip);
+            ways.push((ways[i] + ways[p]).min(2));
+        } else if take > skip {
+            best.push(take);
+            ways.push(ways[p]);
+        } else {
+            best.push(skip);
+            ways.push(ways[i]);
+        }
+    }
+
+    if ways[by_end.len()] > 1 {
+        return None;
+    }
+
+    let mut selected = Vec::new();
+    let mut i = by_end.len();
+    while i > 0 {
+        if (best[i] - best[i - 1]).abs() < EPSILON {
+            i -= 1;
+        } else {
+            selected.push(by_end[i - 1]);
+            i = previous[i - 1];
+        }
+    }
+    selected.reverse();
+
+    Some(selected)
+}
+
+/// Resolves intersections of candidates globally: candidates are split into clusters of
+/// overlapping ones and in every cluster the selection of non overlapping candidates with the
+/// best total score wins, the score favors longer, more confident and more specific matches.
+/// If several selections are equally good, e.g. two rules matched "review tomorrow" and
+/// "tomorrow evening" equally well, the cluster is reported as an intersection error, it is
+/// reported as well for intersections of different languages if they are resolved strictly.
+pub(crate) fn resolve(
+    source_str: &str,
+    candidates: Vec<Result<MatchResult, SemanticError>>,
+    strict_languages: bool,
+) -> Chart {
+    let words = words(source_str);
+
+    let mut unique: Vec<Result<MatchResult, SemanticError>> = Vec::new();
+    for item in candidates {
+        let item = trim_to_words(&words, item);
+        if !unique.iter().any(|other| same_candidate(other, &item)) {
+            unique.push(item);
+        }
+    }
+    unique.sort_by_key(item_bounds);
+
+    let mut entries: Vec<ChartEntry> = unique
+        .iter()
+        .map(|item| {
+            let (start_idx, end_idx) = item_bounds(item);
+            ChartEntry {
+                item: item
+                    .as_ref()
+                    .copied()
+                    .map_err(|e| e.to_diagnostic(source_str)),
+                start_idx,
+                end_idx,
+                score: score(&words, item),
+                selected: false,
+            }
+        })
+        .collect();
+
+    // candidates which don't improve the score are never selected
+    let order: Vec<usize> = (0..entries.len())
+        .filter(|&idx| entries[idx].score > EPSILON)
+        .collect();
+
+    let mut selected = Vec::new();
+    let mut stated = Vec::new();
+    let mut cluster: Vec<usize> = Vec::new();
+    let mut cluster_end = 0;
+
+    let mut flush = |cluster: &mut Vec<usize>, entries: &mut Vec<ChartEntry>| {
+        if cluster.is_empty() {
+            return;
+        }
+        let start_idx = entries[cluster[0]].start_idx;
+        let end_idx = cluster
+            .iter()
+            .map(|&idx| entries[idx].end_idx)
+            .max()
+            .unwrap_or(start_idx);
+
+        let foreign = strict_languages
+            && cluster.iter().any(|&first| {
+                cluster.iter().any(|&second| {
+                    let (first, second) = (&entries[first], &entries[second]);
+                    let language = |entry: &ChartEntry| {
+                        entry.item.as_ref().ok().and_then(|m| m.get_language())
+                    };
+                    matches!(
+                        (language(first), language(second)),
+                        (Some(a), Some(b)) if a != b
+                    ) && first.start_idx < second.end_idx
+                        && second.start_idx < first.end_idx
+                })
+            });
+
+        match best_cover(entries, cluster).filter(|_| !foreign) {
+            Some(cover) => {
+                for idx in cover {
+                    entries[idx].selected = true;
+                    selected.push(entries[idx].item.clone());
+                    if let Err(error) = &unique[idx] {
+                        if let Some(context) = error.get_stated() {
+                            stated.push((entries[idx].start_idx, entries[idx].end_idx, context));
+                        }
+                    }
+                }
+            }
+            None => selected.push(Err(Diagnostic::new(
+                intersection_error(&source_str[start_idx..end_idx]).extract_error(),
+                source_str,
+                start_idx,
+                end_idx,
+            ))),
+        }
+        cluster.clear();
+    };
+
+    for idx in order {
+        if !cluster.is_empty() && entries[idx].start_idx >= cluster_end {
+            flush(&mut cluster, &mut entries);
+        }
+        cluster_end = if cluster.is_empty() {
+            entries[idx].end_idx
+        } else {
+            cluster_end.max(entries[idx].end_idx)
+        };
+        cluster.push(idx);
+    }
+    flush(&mut cluster, &mut entries);
+
+    Chart {
+        entries,
+        selected,
+        corrections: Vec::new(),
+        stated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve;
+    use crate::rules::errors::{DateTimeError, SemanticError};
+    use crate::rules::rules::{Context, MatchBounds, MatchResult};
+
+    fn candidate(
+        start_idx: usize,
+        end_idx: usize,
+        hour: i32,
+        confidence: f64,
+    ) -> Result<MatchResult, SemanticError<'static>> {
+        let context = Context {
+            hour: Some(hour),
+            ..Context::default()
+        };
+        Ok(MatchResult::new(context, start_idx, end_idx).with_confidence(confidence))
+    }
+
+    fn selected(input: &str, candidates: Vec<Result<MatchResult, SemanticError>>) -> Vec<String> {
+        resolve(input, candidates, false)
+            .selected
+            .iter()
+            .map(|item| match item {
+                Ok(m) => input[m.get_start_idx()..m.get_end_idx()].to_owned(),
+                Err(e) => format!("{}", e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_coverage() {
+        // "friday at 5pm" covers more words than "next friday"
+        let input = "next friday at 5pm";
+        assert_eq!(
+            selected(
+                input,
+                vec![candidate(0, 11, 1, 1.0), candidate(5, 18, 2, 1.0)]
+            ),
+            vec!["friday at 5pm"]
+        );
+
+        // "next friday" and "at 5pm" cover all words
+        assert_eq!(
+            selected(
+                input,
+                vec![
+                    candidate(0, 11, 1, 1.0),
+                    candidate(5, 18, 2, 1.0),
+                    candidate(12, 18, 3, 1.0)
+                ]
+            ),
+            vec!["next friday", "at 5pm"]
+        );
+
+        // one match which covers all words is more specific
+        assert_eq!(
+            selected(
+                input,
+                vec![
+                    candidate(0, 11, 1, 1.0),
+                    candidate(0, 18, 2, 1.0),
+                    candidate(12, 18, 3, 1.0)
+                ]
+            ),
+            vec!["next friday at 5pm"]
+        );
+    }
+
+    #[test]
+    fn test_confidence() {
+        let input = "may 5 pm";
+        assert_eq!(
+            selected(
+                input,
+                vec![candidate(0, 5, 1, 0.3), candidate(4, 8, 2, 1.0)]
+            ),
+            vec!["5 pm"]
+        );
+        assert_eq!(
+            selected(
+                input,
+                vec![candidate(0, 5, 1, 1.0), candidate(4, 8, 2, 0.3)]
+            ),
+            vec!["may 5"]
+        );
+    }
+
+    #[test]
+    fn test_ambiguity() {
+        let input = "review tomorrow evening";
+        let chart = resolve(
+            input,
+            vec![candidate(0, 15, 1, 1.0), candidate(7, 23, 2, 1.0)],
+            false,
+        );
+        assert_eq!(chart.selected.len(), 1);
+        let error = chart.selected[0].as_ref().unwrap_err();
+        assert_eq!(
+            error.error,
+            DateTimeError::IntersectionError {
+                text: "review tomorrow evening".to_owned()
+            }
+        );
+        assert_eq!((error.start_idx, error.end_idx), (0, 23));
+        assert!(chart.entries.iter().all(|entry| !entry.selected));
+
+        // the same match found twice is not ambiguous
+        assert_eq!(
+            selected(
+                input,
+                vec![candidate(7, 23, 2, 1.0), candidate(7, 23, 2, 1.0)]
+            ),
+            vec!["tomorrow evening"]
+        );
+
+        // errors compete with matches
+        let error = SemanticError::new(
+            DateTimeError::AmbiguousTime {
+                msg: "review tomorrow".to_owned(),
+            },
+            MatchBounds::new(0, 15),
+        );
+        assert_eq!(
+            selected(input, vec![Err(error), candidate(7, 23, 2, 0.9)]),
+            vec!["can't parse time unambiguously in: review tomorrow"]
+        );
+
+        // separators around a match don't make it a different candidate
+        let input = "5pm 6pm";
+        assert_eq!(
+            selected(
+                input,
+                vec![
+                    candidate(0, 3, 17, 1.0),
+                    candidate(3, 7, 18, 1.0),
+                    candidate(4, 7, 18, 1.0)
+                ]
+            ),
+            vec!["5pm", "6pm"]
+        );
+    }
+}
@@ -1,15 +1,23 @@
+use super::chart::{self, Chart};
+use super::lattice::{self, normalize, Lattice};
 use super::rules::{FnRule, MatchBounds, MatchResult, MyResult, RuleResult, TokenDesc};
+use super::tokens::PToken;
+use super::trace;
+use crate::explain::{RemovalReason, TokenTrace};
+use crate::languages::{Language, LanguagePolicy};
+use crate::rule::{words, Rule, RuleMatch, Word};
 
-use core::borrow::BorrowMut;
 use nom::{
     self, alt, char, map, map_res, named, named_args, opt, pair, preceded, recognize, tag,
-    take_while, tuple, types::CompleteStr, ErrorKind,
+    take_while, types::CompleteStr, ErrorKind,
 };
+use std::borrow::Cow;
+use std::cmp::Reverse;
 
 use chrono::{DateTime, TimeZone};
 use strsim::damerau_levenshtein;
 
-pub use super::errors::{intersection_error, DateTimeError, SemanticError};
+pub use super::errors::{DateTimeError, SemanticError};
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct Dist(pub usize);
@@ -43,8 +51,8 @@ macro_rules! set {
 ///    )
 /// );
 ///
-/// It's important to note that variants must be mutually exclusive, otherwise first (in order of
-/// declaration) match will be selected.
+/// The variant closest to the input word is selected, if several variants are equally close, the
+/// first one (in order of declaration) wins.
 ///
 /// For example:
 ///
@@ -54,11 +62,8 @@ macro_rules! set {
 ///    [(Token::Bar(Bar), Priority(0)), "bar", Dist(3)]
 /// );
 ///
-/// passing string "bar" to foo_bar! combinator will lead to "Foo" token recognition, because
-/// there is 3 units editing distance between strings "foo" and "bar" and therefor first match will
-/// be chosen, which is "Foo".
-///
-/// Use "combine!" combinator if you want to choose the best fit word among the list.
+/// passing string "bar" to foo_bar! combinator will lead to "Bar" token recognition, because it
+/// matches "bar" exactly, while there is 3 units editing distance between "foo" and "bar".
 macro_rules! define {
     ( $func_name: ident: ($token: expr, $p: expr), $repr: expr, $max_dist: expr ) => (
         named_args!(pub $func_name<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
@@ -67,12 +72,13 @@ macro_rules! define {
         );
     );
     ( $func_name: ident: $([($token: expr, $p: expr), $repr: expr, $max_dist: expr])|* ) => (
-        named_args!(pub $func_name<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
-            alt!(
-                $(call!(crate::rules::combinators::recognize_word, CompleteStr($repr), set!(max_dist=$max_dist,
-                        exact_match), crate::rules::tokens::PToken::PToken($token, $p))) |*
-            )
-        );
+        pub fn $func_name(input: CompleteStr, exact_match: bool) -> crate::rules::rules::MyResult {
+            crate::rules::combinators::recognize_closest(input, &[
+                $(&|input| crate::rules::combinators::recognize_word(input, CompleteStr($repr),
+                    set!(max_dist=$max_dist, exact_match),
+                    crate::rules::tokens::PToken::PToken($token, $p))),*
+            ])
+        }
     );
 }
 
@@ -116,6 +122,165 @@ macro_rules! define_num {
     };
 }
 
+/// Macro simplifies parsers definition for numbers glued to one of the given prefixes.
+///
+/// Example:
+///
+/// define_prefixed_num!(week_code: (Token::Number, Priority(1)), "w" | "cw");
+///
+/// recognizes "w42" and "cw42" as Token::Number(42).
+macro_rules! define_prefixed_num {
+    ( $func_name: ident: ($ctor: expr, $p: expr), $($prefix: literal)|+ ) => {
+        fn $func_name(input: CompleteStr) -> crate::rules::rules::MyResult {
+            if let Some((tail, n)) =
+                crate::rules::combinators::recognize_affixed_int(input, &[$($prefix),+], &[""])
+            {
+                return Ok((
+                    tail,
+                    TokenDesc::new(
+                        crate::rules::tokens::PToken::PToken($ctor(n), $p),
+                        crate::rules::combinators::Dist(0),
+                    ),
+                ));
+            }
+            crate::rules::combinators::wrap_error(input, crate::rules::errors::UNKNOWN)
+        }
+    };
+}
+
+/// Macro simplifies parsers definition for numbers followed by one of the given suffixes.
+///
+/// Example:
+///
+/// define_suffixed_num!(day_ord: (Token::Number, Priority(1)), "st" | "nd" | "rd" | "th");
+///
+/// recognizes "1st" and "100th" as Token::Number(1) and Token::Number(100).
+macro_rules! define_suffixed_num {
+    ( $func_name: ident: ($ctor: expr, $p: expr), $($suffix: literal)|+ ) => {
+        fn $func_name(input: CompleteStr) -> crate::rules::rules::MyResult {
+            if let Some((tail, n)) =
+                crate::rules::combinators::recognize_affixed_int(input, &[""], &[$($suffix),+])
+            {
+                return Ok((
+                    tail,
+                    TokenDesc::new(
+                        crate::rules::tokens::PToken::PToken($ctor(n), $p),
+                        crate::rules::combinators::Dist(0),
+                    ),
+                ));
+            }
+            crate::rules::combinators::wrap_error(input, crate::rules::errors::UNKNOWN)
+        }
+    };
+}
+
+/// Macro simplifies year parsers definition, only standalone four digit numbers are
+/// considered to be years, so "5pm" or "10:30" won't be mistaken for a year.
+///
+/// Example:
+///
+/// define_year!(year: (Token::Number, Priority(5)));
+///
+macro_rules! define_year {
+    ( $func_name: ident: ($ctor: expr, $p: expr) ) => {
+        fn $func_name(input: CompleteStr) -> crate::rules::rules::MyResult {
+            if let Some((tail, n)) = crate::rules::combinators::recognize_year(input) {
+                return Ok((
+                    tail,
+                    TokenDesc::new(
+                        crate::rules::tokens::PToken::PToken($ctor(n), $p),
+                        crate::rules::combinators::Dist(0),
+                    ),
+                ));
+            }
+            crate::rules::combinators::wrap_error(input, crate::rules::errors::UNKNOWN)
+        }
+    };
+}
+
+/// Macro simplifies parsers definition for hours glued to "h", e.g. "15h" or "15h30", the parser
+/// stops right after "h", so minutes can be recognized by a parser defined with define_glued_num!
+///
+/// Example:
+///
+/// define_hours_h!(hours: (Token::Number, Priority(1)));
+///
+macro_rules! define_hours_h {
+    ( $func_name: ident: ($ctor: expr, $p: expr) ) => {
+        fn $func_name(input: CompleteStr) -> crate::rules::rules::MyResult {
+            if let Some((tail, n)) = crate::rules::combinators::recognize_hours_h(input) {
+                return Ok((
+                    tail,
+                    TokenDesc::new(
+                        crate::rules::tokens::PToken::PToken($ctor(n), $p),
+                        crate::rules::combinators::Dist(0),
+                    ),
+                ));
+            }
+            crate::rules::combinators::wrap_error(input, crate::rules::errors::UNKNOWN)
+        }
+    };
+}
+
+/// Same as define_num!, but the number must immediately follow the previous token, e.g. "30"
+/// in "15h30"
+///
+/// Example:
+///
+/// define_glued_num!(minutes: (Token::Number, Priority(3)));
+///
+macro_rules! define_glued_num {
+    ( $func_name: ident: ($ctor: expr, $p: expr) ) => {
+        fn $func_name(input: CompleteStr) -> crate::rules::rules::MyResult {
+            if input.starts_with(|c: char| c.is_ascii_digit()) {
+                if let Ok((tail, n)) = crate::rules::combinators::recognize_int(input) {
+                    return Ok((
+                        tail,
+                        TokenDesc::new(
+                            crate::rules::tokens::PToken::PToken($ctor(n), $p),
+                            crate::rules::combinators::Dist(0),
+                        ),
+                    ));
+                }
+            }
+            crate::rules::combinators::wrap_error(input, crate::rules::errors::UNKNOWN)
+        }
+    };
+}
+
+/// Same as define_num!, but also recognizes full-width digits and numbers written with CJK
+/// numerals, e.g. "十五", unless only digits are allowed
+///
+/// Example:
+///
+/// define_cjk_num!(hours: (Token::Number, Priority(1)));
+/// define_cjk_num!(digit_hours: (Token::Number, Priority(1)), digits);
+///
+macro_rules! define_cjk_num {
+    ( $func_name: ident: ($ctor: expr, $p: expr) ) => {
+        define_cjk_num!($func_name: ($ctor, $p), false);
+    };
+    ( $func_name: ident: ($ctor: expr, $p: expr), digits ) => {
+        define_cjk_num!($func_name: ($ctor, $p), true);
+    };
+    ( $func_name: ident: ($ctor: expr, $p: expr), $digits_only: expr ) => {
+        fn $func_name(input: CompleteStr) -> crate::rules::rules::MyResult {
+            if let Some((tail, n)) =
+                crate::rules::combinators::recognize_cjk_int(input, $digits_only)
+            {
+                return Ok((
+                    tail,
+                    TokenDesc::new(
+                        crate::rules::tokens::PToken::PToken($ctor(n), $p),
+                        crate::rules::combinators::Dist(0),
+                    ),
+                ));
+            }
+            crate::rules::combinators::wrap_error(input, crate::rules::errors::UNKNOWN)
+        }
+    };
+}
+
 /// Macro helps to combine tokens defined by define! macro into one, i.e.
 ///
 /// combine!(day_of_week => monday | tuesday | wednesday | thursday | friday | saturday | sunday);
@@ -172,36 +337,96 @@ macro_rules! make_interpreter {
 }
 
 fn is_ignorable(c: char) -> bool {
-    !(c == '/' || c == ':' || c == '-' || c.is_alphanumeric())
+    !(c == '/' || c == ':' || c == '：' || c == '-' || c.is_alphanumeric())
 }
 
 /// Trim spaces, special symbols and commas until any non-whitespace character appears
-named!(trim<CompleteStr, CompleteStr>,
+named!(pub trim<CompleteStr, CompleteStr>,
     take_while!(is_ignorable)
 );
 
 fn is_word_symbol(c: char) -> bool {
-    c == '.' || c == ':' || c.is_alphanumeric()
+    c == '.' || c == ':' || c == '：' || c.is_alphanumeric()
+}
+
+/// Chinese and Japanese are written without spaces between words, so every symbol of these
+/// scripts is considered to be a separate word and patterns are matched as prefixes
+pub(crate) fn is_cjk(c: char) -> bool {
+    match c {
+        '\u{3005}' | '\u{3007}' => true, // iteration mark and ideographic zero
+        '\u{3040}'..='\u{30ff}' => true, // hiragana and katakana
+        '\u{3400}'..='\u{4dbf}' => true, // CJK unified ideographs extension A
+        '\u{4e00}'..='\u{9fff}' => true, // CJK unified ideographs
+        '\u{f900}'..='\u{faff}' => true, // CJK compatibility ideographs
+        '\u{ff66}'..='\u{ff9f}' => true, // halfwidth katakana
+        _ => false,
+    }
 }
 
 /// Ignores whitespaces using "trim" and then consumes alphabetical characters in a string until
-/// any non alpha-numeric character appears or the string has been exhausted:
+/// any non alpha-numeric character appears or the string has been exhausted, a CJK symbol is
+/// a word by itself and it also ends any other word:
 ///
 /// "  , abracadabra  " -> "abracadabra"
-named!(tokenize_word<CompleteStr, CompleteStr>,
-    preceded!(trim, take_while!(is_word_symbol))
-);
+/// "3点钟" -> "3"
+fn tokenize_word(input: CompleteStr) -> nom::IResult<CompleteStr, CompleteStr> {
+    let (rest, _) = trim(input)?;
+
+    let len = match rest.chars().next() {
+        Some(c) if is_cjk(c) => c.len_utf8(),
+        _ => rest
+            .find(|c: char| !is_word_symbol(c) || is_cjk(c))
+            .unwrap_or_else(|| rest.len()),
+    };
+
+    Ok((CompleteStr(&rest[len..]), CompleteStr(&rest[..len])))
+}
 
 /// Consumes all spaces before a word, the word itself and all spaces after the word and returns
 /// total number of consumed characters:
 ///
 /// "  , abracadabra  " -> 17
-named!(pub(crate) tokenize_count_symbols<CompleteStr, usize>,
-    map!(tuple!(trim, take_while!(|c: char| c == '.' || c == ':' || c.is_alphanumeric()), trim),
-    |(prefix, word, suffix)| {
-        prefix.len() + word.len() + suffix.len()
-    })
-);
+fn count_word_symbols(input: CompleteStr) -> nom::IResult<CompleteStr, usize> {
+    let (tail, _) = tokenize_word(input)?;
+    let (tail, _) = trim(tail)?;
+    Ok((tail, input.len() - tail.len()))
+}
+
+/// Same as "skip_word", but looks the word up in the lattice of the input when there is one
+pub(crate) fn tokenize_count_symbols(input: CompleteStr) -> nom::IResult<CompleteStr, usize> {
+    match lattice::current().and_then(|lattice| lattice.skip(input)) {
+        Some(skipped) => Ok(skipped),
+        None => skip_word(input),
+    }
+}
+
+/// Same as "count_word_symbols", but if the input starts with a symbol which is neither a part
+/// of a word nor ignorable (e.g. "-" in "w42-3") skips exactly that symbol, otherwise rules
+/// would stop scanning the rest of the input at such symbols.
+pub(crate) fn skip_word(input: CompleteStr) -> nom::IResult<CompleteStr, usize> {
+    let (tail, count) = count_word_symbols(input)?;
+    if count == 0 {
+        if let Some(c) = input.chars().next() {
+            return Ok((CompleteStr(&input[c.len_utf8()..]), c.len_utf8()));
+        }
+    }
+    Ok((tail, count))
+}
+
+/// Returns the next word of the input and the rest of it, symbols which are neither parts of
+/// words nor ignorable are skipped one at a time and returned as empty words
+pub(crate) fn next_word(input: CompleteStr) -> Option<(CompleteStr, CompleteStr)> {
+    if input.is_empty() {
+        return None;
+    }
+    match tokenize_word(input) {
+        Ok((tail, word)) if tail.len() < input.len() => Some((tail, word)),
+        _ => {
+            let len = input.chars().next().map_or(0, char::len_utf8);
+            Some((CompleteStr(&input[len..]), CompleteStr("")))
+        }
+    }
+}
 
 /// Ignores whitespaces using "trim" and then consumes digits in a string until
 /// any non digit character appears or the string has been exhausted, and in case of success
@@ -219,6 +444,132 @@ named!(pub(crate) recognize_int<CompleteStr, i32>,
      })
 );
 
+/// Recognizes a word which consists of one of the prefixes, a number and one of the suffixes,
+/// returns the number and the rest of the input:
+///
+/// "  w42 2019", prefixes ["w"], suffixes [""] -> (" 2019", 42)
+/// "  100th day", prefixes [""], suffixes ["th"] -> (" day", 100)
+pub(crate) fn recognize_affixed_int<'a>(
+    input: CompleteStr<'a>,
+    prefixes: &[&str],
+    suffixes: &[&str],
+) -> Option<(CompleteStr<'a>, i32)> {
+    let (tail, word) = tokenize_word(input).ok()?;
+    let word = word.trim_end_matches('.');
+
+    for prefix in prefixes {
+        for suffix in suffixes {
+            if word.len() <= prefix.len() + suffix.len()
+                || !word.starts_with(prefix)
+                || !word.ends_with(suffix)
+            {
+                continue;
+            }
+            let digits = &word[prefix.len()..word.len() - suffix.len()];
+            if digits.chars().all(|c| c.is_ascii_digit()) {
+                if let Ok(n) = digits.parse::<i32>() {
+                    return Some((tail, n));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Recognizes a standalone four digit number, e.g. "2019", and returns it along with the rest
+/// of the input
+pub(crate) fn recognize_year(input: CompleteStr) -> Option<(CompleteStr, i32)> {
+    let (tail, word) = tokenize_word(input).ok()?;
+    let word = word.trim_end_matches('.');
+
+    if word.len() == 4 && word.chars().all(|c| c.is_ascii_digit()) {
+        return word.parse::<i32>().ok().map(|n| (tail, n));
+    }
+
+    None
+}
+
+/// Recognizes hours glued to "h" with optional minutes, e.g. "15h" or "15h30", returns the hours
+/// and the rest of the input right after "h":
+///
+/// "  15h30 amanhã" -> ("30 amanhã", 15)
+pub(crate) fn recognize_hours_h(input: CompleteStr) -> Option<(CompleteStr, i32)> {
+    let (tail, word) = tokenize_word(input).ok()?;
+    let start = input.len() - tail.len() - word.len();
+    let pos = word.find('h')?;
+    let (digits, rest) = (&word[..pos], &word[pos + 1..]);
+
+    if !digits.is_empty()
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && rest.chars().all(|c| c.is_ascii_digit())
+    {
+        return digits
+            .parse::<i32>()
+            .ok()
+            .map(|n| (CompleteStr(&input[start + pos + 1..]), n));
+    }
+
+    None
+}
+
+/// Recognizes a non negative number written either with arabic digits (including full-width
+/// ones) or with CJK numerals (up to 99, or digit by digit as in years) and returns it along
+/// with the rest of the input, CJK numerals are not accepted if only digits are allowed:
+///
+/// "15点" -> ("点", 15)
+/// "二十三天" -> ("天", 23)
+/// "二〇二三年" -> ("年", 2023)
+/// "１５時" -> ("時", 15)
+pub(crate) fn recognize_cjk_int(
+    input: CompleteStr,
+    digits_only: bool,
+) -> Option<(CompleteStr, i32)> {
+    let (rest, _) = trim(input).ok()?;
+
+    if rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return recognize_int(rest).ok();
+    }
+    if digits_only && !rest.starts_with(|c| ('０'..='９').contains(&c)) {
+        return None;
+    }
+
+    let (mut total, mut current, mut len) = (0i32, 0i32, 0);
+
+    // numbers which don't fit into i32 are not recognized
+    for c in rest.chars() {
+        let digit = match c {
+            '十' => {
+                let tens = if current == 0 {
+                    10
+                } else {
+                    current.checked_mul(10)?
+                };
+                total = total.checked_add(tens)?;
+                current = 0;
+                None
+            }
+            '０'..='９' => Some(c as i32 - '０' as i32),
+            '零' => Some(0),
+            '两' | '兩' => Some(2),
+            _ => match "〇一二三四五六七八九".chars().position(|d| d == c) {
+                Some(digit) => Some(digit as i32),
+                None => break,
+            },
+        };
+        if let Some(digit) = digit {
+            current = current.checked_mul(10)?.checked_add(digit)?;
+        }
+        len += c.len_utf8();
+    }
+
+    if len == 0 {
+        return None;
+    }
+
+    Some((CompleteStr(&rest[len..]), total.checked_add(current)?))
+}
+
 named_args!(pub recognize_symbol<'a>(c: char)<CompleteStr<'a>, char>,
     preceded!(trim, char!(c))
 );
@@ -261,29 +612,94 @@ pub(crate) fn wrap_error(input: CompleteStr, error_code: u32) -> MyResult {
     )))
 }
 
+/// Applies word parsers to the input and returns the match with the smallest typos cost given by
+/// the typo model, the first one wins ties, so an exact match stops the search
+pub(crate) fn recognize_closest<'a>(
+    input: CompleteStr<'a>,
+    variants: &[&dyn Fn(CompleteStr<'a>) -> MyResult<'a>],
+) -> MyResult<'a> {
+    let mut closest: Option<(CompleteStr<'a>, TokenDesc)> = None;
+
+    for variant in variants {
+        if let Ok((tail, desc)) = variant(input) {
+            if desc.dist == Dist(0) {
+                return Ok((tail, desc));
+            }
+            let closer = match &closest {
+                Some((_, best)) => desc.cost < best.cost,
+                None => true,
+            };
+            if closer {
+                closest = Some((tail, desc));
+            }
+        }
+    }
+
+    match closest {
+        Some(found) => Ok(found),
+        None => wrap_error(input, crate::rules::errors::UNKNOWN),
+    }
+}
+
 /// Tries to recognize a word using Domerau-Levenshtein algorithm, it is both simple enough and
 /// efficient.
 pub(crate) fn recognize_word<'a>(
     input: CompleteStr<'a>,
-    pattern: CompleteStr<'a>,
+    pattern: CompleteStr,
     max_dist: crate::rules::combinators::Dist,
     token: super::tokens::PToken,
 ) -> MyResult<'a> {
-    if let Ok((tail, mut word)) = tokenize_word(input) {
-        // TODO: add comment why we replace "." with "" (examples a.m., p.m.)
-        let normalized_word = word.borrow_mut().replace(".", "");
+    if pattern.starts_with(is_cjk) {
+        return recognize_cjk_word(input, pattern, token);
+    }
+
+    // words of the input are tokenized once and shared by all rules
+    let lattice = lattice::current();
+    let word = match lattice.as_ref().and_then(|lattice| lattice.word(input)) {
+        Some((tail, word)) => Some((tail, Cow::Borrowed(word))),
+        None => tokenize_word(input)
+            .ok()
+            .map(|(tail, word)| (tail, Cow::Owned(normalize(*word)))),
+    };
+
+    if let Some((tail, normalized_word)) = word {
+        let len = normalized_word.chars().count();
         if max_dist == crate::rules::combinators::Dist(0) {
             // when max_dist is 0 perform just plain string comparison
             if normalized_word == *pattern {
+                if let Some(lattice) = &lattice {
+                    lattice.recognize(tail, *pattern, 0.0);
+                }
                 return Ok((
                     tail,
-                    TokenDesc::new(token, crate::rules::combinators::Dist(0)),
+                    TokenDesc::new(token, crate::rules::combinators::Dist(0)).with_len(len),
                 ));
             }
         } else {
-            let dist = Dist(damerau_levenshtein(&normalized_word, *pattern));
-            if dist <= max_dist {
-                return Ok((tail, TokenDesc::new(token, dist)));
+            let typos = super::session::current().typo_model.clone();
+            let compute = || typos.cost(&normalized_word, *pattern);
+            let max_edits = typos.max_edits(*pattern, max_dist.0);
+            // tokens of the word are looked up in the lattice, words it doesn't find close to
+            // the pattern are skipped
+            let cost = match &lattice {
+                Some(lattice) => lattice.cost(tail, &normalized_word, *pattern, max_edits, compute),
+                None => Some(compute()),
+            };
+            if let Some(cost) = cost.filter(|cost| *cost <= typos.max_cost(*pattern, max_dist.0)) {
+                if let Some(lattice) = &lattice {
+                    lattice.recognize(tail, *pattern, cost);
+                }
+                let dist = Dist(damerau_levenshtein(&normalized_word, *pattern));
+                return Ok((
+                    tail,
+                    TokenDesc::new(token, dist).with_cost(cost).with_len(len),
+                ));
+            }
+        }
+
+        if let super::tokens::PToken::PToken(ref target, _) = token {
+            if let Some(dist) = recognize_alias(&normalized_word, target) {
+                return Ok((tail, TokenDesc::new(token, dist).with_len(len)));
             }
         }
     }
@@ -291,174 +707,608 @@ pub(crate) fn recognize_word<'a>(
     wrap_error(input, crate::rules::errors::UNKNOWN)
 }
 
-/// Finds a minimal distance between an input word by applying all combinators from funcs.
+/// Words in CJK scripts are not separated by spaces, so the pattern is looked up as a prefix
+/// of the input, typos are not recognized
+fn recognize_cjk_word<'a>(
+    input: CompleteStr<'a>,
+    pattern: CompleteStr,
+    token: super::tokens::PToken,
+) -> MyResult<'a> {
+    if let Ok((rest, _)) = trim(input) {
+        if rest.starts_with(*pattern) {
+            return Ok((
+                CompleteStr(&rest[pattern.len()..]),
+                TokenDesc::new(token, crate::rules::combinators::Dist(0))
+                    .with_len(pattern.chars().count()),
+            ));
+        }
+    }
+
+    wrap_error(input, crate::rules::errors::UNKNOWN)
+}
+
+/// Looks for the word among aliases of the token registered in the current session, returns
+/// the smallest distance
+fn recognize_alias(word: &str, target: &super::tokens::Token) -> Option<Dist> {
+    let session = super::session::current();
+
+    session
+        .aliases
+        .find(word, target, session.exact_match)
+        .map(Dist)
+}
+
+/// Returns maximum allowed editing distance for a word which is not defined by a rule explicitly,
+/// e.g. holiday names, the longer the word is the more typos are allowed
+pub(crate) fn default_dist(word: &str) -> Dist {
+    match word.chars().count() {
+        0..=3 => Dist(0),
+        4..=6 => Dist(1),
+        _ => Dist(2),
+    }
+}
+
+/// Recognizes a sequence of words, e.g. "new year s eve", each word is matched using
+/// "recognize_word" with its default distance, returns the rest of the input and the total
+/// distance
+pub(crate) fn recognize_phrase<'a, S: AsRef<str>>(
+    input: CompleteStr<'a>,
+    words: &[S],
+    exact_match: bool,
+) -> Option<(CompleteStr<'a>, Dist)> {
+    let mut tail = input;
+    let mut total = 0;
+
+    for word in words {
+        let word = word.as_ref();
+        let max_dist = set!(max_dist = default_dist(word), exact_match);
+        match recognize_word(
+            tail,
+            CompleteStr(word),
+            max_dist,
+            super::tokens::PToken::Stub,
+        ) {
+            Ok((rest, TokenDesc { dist, .. })) => {
+                tail = rest;
+                total += dist.0;
+            }
+            Err(_) => return None,
+        }
+    }
+
+    Some((tail, Dist(total)))
+}
+
+/// Finds a minimal typos cost between an input word by applying all combinators from funcs.
 /// Each function accepts an input string and a flag which denotes whether exact match is required.
 pub(crate) fn best_fit<'a>(
     input: CompleteStr<'a>,
     exact_match: bool,
     combinators: Vec<&dyn Fn(CompleteStr<'a>, bool) -> MyResult<'a>>,
 ) -> MyResult<'a> {
-    let mut min_dist = Dist(std::usize::MAX);
+    let mut min_cost = std::f64::MAX;
 
-    let mut selected_token = crate::rules::tokens::PToken::Stub;
+    let mut selected = None;
     let mut selected_count = 0;
-    let mut selected_tail = CompleteStr("");
 
     for comb in combinators {
-        if let Ok((tail, TokenDesc { token, dist })) = comb(input, exact_match) {
-            if min_dist > dist {
-                selected_token = token;
-                selected_tail = tail;
+        if let Ok((tail, desc)) = comb(input, exact_match) {
+            if min_cost > desc.cost {
+                min_cost = desc.cost;
+                selected = Some((tail, desc));
                 selected_count = 1;
-                min_dist = dist;
-            } else if min_dist == dist {
+            } else if min_cost == desc.cost {
                 selected_count += 1;
             }
         }
     }
 
-    if selected_count == 1 {
-        return Ok((selected_tail, TokenDesc::new(selected_token, min_dist)));
+    match selected {
+        Some(selected) if selected_count == 1 => Ok(selected),
+        _ => wrap_error(input, crate::rules::errors::UNKNOWN),
     }
-
-    wrap_error(input, crate::rules::errors::UNKNOWN)
 }
 
-pub(crate) fn remove_overlapped<'a>(
-    source_str: &'a str,
-    matched_tokens: &'a [Result<MatchResult, SemanticError<'a>>],
+/// Attaches offsets (e.g. "3 days after") to the match which immediately follows them
+/// (e.g. "christmas"), so the offset shifts the date that match denotes. Offsets are attached
+/// from right to left, so they can be chained: "a day after 2 weeks before christmas".
+/// Matches the offsets were attached to are kept as candidates, offsets not followed by a date
+/// are dropped.
+pub(crate) fn attach_offsets<'a>(
+    source_str: &str,
+    matched_tokens: Vec<Result<MatchResult, SemanticError<'a>>>,
 ) -> Vec<Result<MatchResult, SemanticError<'a>>> {
+    // items are collected in reverse order
     let mut result: Vec<Result<MatchResult, SemanticError>> = Vec::new();
-    let mut overlap: Option<MatchBounds> = None;
 
-    let mut min_idx = 0;
-    let mut max_idx = None;
+    for item in matched_tokens.into_iter().rev() {
+        let offset = match item {
+            Ok(ref m) if m.get_timeshift().offset.is_some() => *m,
+            _ => {
+                result.push(item);
+                continue;
+            }
+        };
 
-    let mut prev_elem = None;
+        // the longest of matches which start right after the offset
+        let anchor = result
+            .iter()
+            .filter_map(|item| match item {
+                Ok(anchor) if anchor.get_start_idx() >= offset.get_end_idx() => Some(anchor),
+                _ => None,
+            })
+            .min_by_key(|anchor| (anchor.get_start_idx(), Reverse(anchor.get_end_idx())))
+            .filter(|anchor| {
+                source_str[offset.get_end_idx()..anchor.get_start_idx()]
+                    .trim()
+                    .is_empty()
+            })
+            .copied();
 
-    let mut f = |item: &'a Result<MatchResult, SemanticError>, start_idx, end_idx| {
-        if max_idx.map_or(false, |x| x >= start_idx) {
-            // maintain maximum position in text for overlapped interval
-            overlap = match overlap {
-                None => Some(MatchBounds::new(min_idx, end_idx)),
-                Some(bounds) => Some(MatchBounds::new(bounds.start_idx, end_idx)),
-            };
-        } else if overlap.is_some() {
-            result.push(Err(intersection_error(
-                &source_str[overlap.unwrap().start_idx..overlap.unwrap().end_idx],
-            )));
-            overlap = None;
-        } else if prev_elem.is_some() {
-            result.push(prev_elem.take().unwrap());
+        if let Some(anchor) = anchor {
+            let mut context = *anchor.get_timeshift();
+            context.update(offset.get_timeshift());
+            result.push(Ok(MatchResult::new(
+                context,
+                offset.get_start_idx(),
+                anchor.get_end_idx(),
+            )
+            .with_language(anchor.get_language())
+            .with_confidence(anchor.get_confidence())));
         }
+    }
 
-        if max_idx.map_or(true, |x| end_idx > x) {
-            min_idx = start_idx;
-            max_idx = Some(end_idx);
+    result.reverse();
+    result
+}
 
-            prev_elem = Some(item.clone());
-        }
-    };
+/// Rule defined in this crate using "make_interpreter!"
+pub(crate) struct BuiltinRule<Tz: TimeZone> {
+    pub name: &'static str,
+    pub interpret: FnRule<Tz>,
+}
 
-    // handle possible remaining items
-    let mut last_item = None;
-    for item in matched_tokens.iter() {
-        match item {
-            Ok(token) => f(&item, token.get_start_idx(), token.get_end_idx()),
-            Err(token) => f(&item, token.get_start_idx(), token.get_end_idx()),
-        }
-        last_item = Some(item);
+impl<Tz: TimeZone> Rule<Tz> for BuiltinRule<Tz> {
+    fn name(&self) -> &str {
+        self.name
     }
 
-    if overlap.is_none() {
-        if last_item.is_some() {
-            result.push(last_item.unwrap().clone());
-        }
-    } else {
-        result.push(Err(intersection_error(
-            &source_str[overlap.unwrap().start_idx..overlap.unwrap().end_idx],
-        )));
+    // built-in rules are named after their language, e.g. "en::weekdays", while rules shared
+    // by several languages are "common"
+    fn language(&self) -> Option<Language> {
+        self.name.split("::").next().and_then(Language::from_code)
     }
 
-    result
-}
+    fn apply(&self, input: &str, exact_match: bool, now: &DateTime<Tz>) -> Vec<RuleMatch> {
+        let mut matches = Vec::new();
 
-/// Generic rules applier, accepts a string to parse as its input and a slice of rules,
-/// then it tries to apply each rule from the list one by one, appending parsed tokens (if succeed)
-/// to the output vector.
-///
-/// Let's consider an example:
-///
-/// input: "You can call me this friday or next monday."
-///
-/// output will be as follows: [[When(This), Weekday(Friday)], [When(Next), Weekday(Monday)]]
-#[inline]
-pub(crate) fn apply_generic<'a, Tz: TimeZone + 'a>(
-    date_time: DateTime<Tz>,
-    source_str: &'a str,
-    rules: &'a [FnRule<Tz>],
-    exact_match: bool,
-) -> Vec<Result<MatchResult, DateTimeError>> {
-    // empty vector of matched tokens and errors
-    let mut matched_tokens = Vec::new();
-
-    for rule in rules {
-        let mut input = source_str;
-        let mut end_of_last_match_idx = 0;
+        // start of the rest of the input, a scan continues after the end of the previous match,
+        // shorter matches inside it are left to other rules, e.g. "friday" inside "next friday".
+        // An error is not a match, so the scan continues from its second word, e.g. "march 5"
+        // is found after "42, march" in "week 42, march 5".
+        let mut start = 0;
 
-        // try to apply one rule as many times as possible
+        // try to apply the rule as many times as possible
         loop {
-            match rule(input, exact_match, date_time.clone()) {
+            start = match (self.interpret)(&input[start..], exact_match, now.clone()) {
                 Ok(RuleResult {
-                    tail,
+                    tokens,
+                    evidence,
+                    dists,
                     bounds: Some(bounds),
                     context,
                     ..
                 }) => {
                     // applied rule had a match
-                    matched_tokens.push(Ok(MatchResult::new(
-                        context,
-                        end_of_last_match_idx + bounds.start_idx,
-                        end_of_last_match_idx + bounds.end_idx,
-                    )));
-
-                    // continue with the rest of the string
-                    end_of_last_match_idx += bounds.end_idx;
-                    input = tail;
+                    let bounds = MatchBounds::new(start + bounds.start_idx, start + bounds.end_idx);
+                    trace::record(|trace| {
+                        let tokens = tokens.unwrap_or_default();
+                        let recognized = tokens.iter().zip(&evidence).zip(&dists).filter_map(
+                            |((token, (cost, _)), dist)| match token {
+                                PToken::PToken(token, priority) => Some(TokenTrace {
+                                    token: format!("{:?}", token),
+                                    priority: priority.0,
+                                    dist: dist.0,
+                                    cost: *cost,
+                                }),
+                                PToken::Stub => None,
+                            },
+                        );
+                        trace
+                            .tokens
+                            .insert((bounds.start_idx, bounds.end_idx), recognized.collect());
+                    });
+                    matches.push(
+                        RuleMatch::new(bounds.start_idx, bounds.end_idx, Ok(context))
+                            .with_confidence(match_confidence(&evidence)),
+                    );
+                    bounds.end_idx
                 }
                 Ok(RuleResult { bounds: None, .. }) => {
                     // being inside this branch means that no more matches were found, we consider
-                    // current rule as fully applied and must continue with the next rule
+                    // the rule as fully applied
                     break;
                 }
                 Err(err) => {
-                    input = err.get_tail();
-                    matched_tokens.push(Err(err));
+                    let bounds =
+                        MatchBounds::new(start + err.get_start_idx(), start + err.get_end_idx());
+                    matches.push(
+                        RuleMatch::new(bounds.start_idx, bounds.end_idx, Err(err.extract_error()))
+                            .with_suggestions(err.get_suggestions().to_vec())
+                            .with_stated(err.get_stated()),
+                    );
+                    let rest = next_word(CompleteStr(&input[bounds.start_idx..]))
+                        .map_or("", |(tail, _)| *tail);
+                    input.len() - rest.len().max(err.get_tail().len())
                 }
-            }
+            };
         }
+
+        matches
     }
+}
 
-    // all rules were applied at this step and the results were saved into matched_tokens vector
+fn item_bounds(item: &Result<MatchResult, SemanticError>) -> (usize, usize) {
+    match item {
+        Ok(x) => (x.get_start_idx(), x.get_end_idx()),
+        Err(x) => (x.get_start_idx(), x.get_end_idx()),
+    }
+}
 
-    // first of all we sort resulting vector by tokens start offsets
-    matched_tokens.sort_by_key(|k| match k {
-        Ok(x) => x.get_start_idx(),
-        Err(x) => x.get_start_idx(),
-    });
+/// Drops matches and errors which intersect with a winning match of another language according
+/// to the policy, matches of rules without a language are left for the following steps
+pub(crate) fn remove_foreign<'a>(
+    matched_tokens: Vec<(
+        i32,
+        Option<Language>,
+        Result<MatchResult, SemanticError<'a>>,
+    )>,
+    languages: &[Language],
+    policy: LanguagePolicy,
+) -> Vec<(i32, Result<MatchResult, SemanticError<'a>>)> {
+    let rank = |language: Language| {
+        languages
+            .iter()
+            .position(|&other| other == language)
+            .unwrap_or(languages.len())
+    };
 
-    // then look for tokens bounds intersections, and treat them as errors
-    let tmp = remove_overlapped(source_str, &matched_tokens);
+    let winners: Vec<(Language, (usize, usize))> = matched_tokens
+        .iter()
+        .filter(|(_, _, item)| item.is_ok())
+        .filter_map(|(_, language, item)| language.map(|language| (language, item_bounds(item))))
+        .collect();
 
-    // unbox errors
-    tmp.iter()
-        .map(|item| match item {
-            Err(x) => Err(x.extract_error()),
-            Ok(x) => Ok(*x),
+    matched_tokens
+        .into_iter()
+        .filter(|(_, language, item)| {
+            let language = match language {
+                Some(language) if policy != LanguagePolicy::Strict => *language,
+                _ => return true,
+            };
+            let (start_idx, end_idx) = item_bounds(item);
+            !winners.iter().any(|&(other, (start, end))| {
+                let beats = match policy {
+                    LanguagePolicy::Longest if end - start != end_idx - start_idx => {
+                        end - start > end_idx - start_idx
+                    }
+                    _ => rank(other) < rank(language),
+                };
+                other != language && start < end_idx && start_idx < end && beats
+            })
         })
+        .map(|(priority, _, item)| (priority, item))
         .collect()
 }
 
+/// Confidence of a match which consists of a single token, e.g. "friday" alone is weaker
+/// evidence than "next friday"
+pub(crate) const SINGLE_TOKEN_CONFIDENCE: f64 = 0.9;
+
+/// Confidence of a match given typos cost and length of every matched token, typos cost more in
+/// short words, e.g. "moon" for "noon" is less likely a date than "fridy" for "friday"
+pub(crate) fn match_confidence(evidence: &[(f64, usize)]) -> f64 {
+    let cost: f64 = evidence.iter().map(|(cost, _)| cost).sum();
+    let len: usize = evidence.iter().map(|(_, len)| len).sum();
+
+    let typos = if cost == 0.0 {
+        1.0
+    } else {
+        (1.0 - 2.0 * cost / len.max(1) as f64).max(0.0)
+    };
+    let specificity = if evidence.len() > 1 {
+        1.0
+    } else {
+        SINGLE_TOKEN_CONFIDENCE
+    };
+
+    typos * specificity
+}
+
+/// Confidence of a weak word match supported by the text around it, e.g. "on sat"
+pub(crate) const SUPPORTED_CONFIDENCE: f64 = 0.8;
+/// Confidence of a weak word match without any date context, e.g. "sat" in "I sat down"
+pub(crate) const WEAK_CONFIDENCE: f64 = 0.3;
+
+/// Checks whether the words around a match make it a date: a context word or a number right
+/// before it, e.g. "in may", "5 may", or a number right after it, e.g. "may 2019"
+fn has_context(words: &[Word], language: Language, (start_idx, end_idx): (usize, usize)) -> bool {
+    let is_number = |word: &Word| word.text.starts_with(|c: char| c.is_ascii_digit());
+
+    let before = words.iter().rev().find(|word| word.end_idx <= start_idx);
+    let after = words.iter().find(|word| word.start_idx >= end_idx);
+
+    before
+        .iter()
+        .any(|word| is_number(word) || language.context_words().contains(&word.text))
+        || after.iter().any(|word| is_number(word))
+}
+
+/// Checks whether two matches intersect or are separated by at most one word, e.g. "and"
+fn is_near(words: &[Word], first: (usize, usize), second: (usize, usize)) -> bool {
+    let (left, right) = if first.0 <= second.0 {
+        (first, second)
+    } else {
+        (second, first)
+    };
+
+    right.0 < left.1
+        || words
+            .iter()
+            .filter(|word| word.start_idx >= left.1 && word.end_idx <= right.0)
+            .count()
+            <= 1
+}
+
+/// Scores matches which consist of weak words only (e.g. "may", "sun", see
+/// "Language::weak_words"), such a match is a date only if it is supported by a context word,
+/// a number or another date next to it. Confidence of weak matches is lowered accordingly,
+/// matches and errors below the threshold are dropped.
+pub(crate) fn remove_weak<'a>(
+    source_str: &str,
+    matched_tokens: Vec<(
+        i32,
+        Option<Language>,
+        Result<MatchResult, SemanticError<'a>>,
+    )>,
+    min_confidence: f64,
+) -> Vec<(
+    i32,
+    Option<Language>,
+    Result<MatchResult, SemanticError<'a>>,
+)> {
+    let words = words(source_str);
+
+    let is_weak = |language: Option<Language>, (start_idx, end_idx): (usize, usize)| {
+        let weak_words = match language {
+            Some(language) => language.weak_words(),
+            None => return false,
+        };
+        let mut inside = words
+            .iter()
+            .filter(|word| word.start_idx >= start_idx && word.end_idx <= end_idx)
+            .peekable();
+        inside.peek().is_some() && inside.all(|word| weak_words.contains(&word.text))
+    };
+
+    let weak: Vec<bool> = matched_tokens
+        .iter()
+        .map(|(_, language, item)| is_weak(*language, item_bounds(item)))
+        .collect();
+
+    let mut supported: Vec<bool> = matched_tokens
+        .iter()
+        .zip(&weak)
+        .map(|((_, language, item), &weak)| match language {
+            Some(language) if weak => has_context(&words, *language, item_bounds(item)),
+            _ => true,
+        })
+        .collect();
+
+    // a weak match next to a date is a date too, e.g. "sun" in "monday and sun", support
+    // spreads along chains like "on sat and sun"
+    loop {
+        let mut changed = false;
+        for idx in 0..matched_tokens.len() {
+            if supported[idx] {
+                continue;
+            }
+            let bounds = item_bounds(&matched_tokens[idx].2);
+            let near = matched_tokens
+                .iter()
+                .enumerate()
+                .any(|(other, (_, _, item))| {
+                    other != idx
+                        && supported[other]
+                        && item
+                            .as_ref()
+                            .map_or(true, |m| m.get_confidence() >= min_confidence)
+                        && is_near(&words, bounds, item_bounds(item))
+                });
+            if near {
+                supported[idx] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    matched_tokens
+        .into_iter()
+        .zip(weak.into_iter().zip(supported))
+        .filter_map(|((priority, language, item), (weak, supported))| {
+            let context = match (weak, supported) {
+                (false, _) => 1.0,
+                (true, true) => SUPPORTED_CONFIDENCE,
+                (true, false) => WEAK_CONFIDENCE,
+            };
+            let confidence = item.as_ref().map_or(1.0, |m| m.get_confidence()) * context;
+            if confidence < min_confidence {
+                return None;
+            }
+            Some((
+                priority,
+                language,
+                item.map(|m| m.with_confidence(confidence)),
+            ))
+        })
+        .collect()
+}
+
+/// Drops matches and errors which intersect with a match of a rule with higher priority, so
+/// only intersections of rules with equal priorities are treated as errors
+pub(crate) fn remove_outranked<'a>(
+    matched_tokens: Vec<(i32, Result<MatchResult, SemanticError<'a>>)>,
+) -> Vec<Result<MatchResult, SemanticError<'a>>> {
+    let winners: Vec<(i32, (usize, usize))> = matched_tokens
+        .iter()
+        .filter(|(_, item)| item.is_ok())
+        .map(|(priority, item)| (*priority, item_bounds(item)))
+        .collect();
+
+    matched_tokens
+        .into_iter()
+        .filter(|(priority, item)| {
+            let (start_idx, end_idx) = item_bounds(item);
+            !winners.iter().any(|&(other, (start, end))| {
+                other > *priority && start < end_idx && start_idx < end
+            })
+        })
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// Generic rules applier, accepts a string to parse as its input and a slice of rules along with
+/// their priorities, then it tries to apply each rule from the list one by one, appending parsed tokens (if succeed)
+/// to the output vector.
+///
+/// Let's consider an example:
+///
+/// input: "You can call me this friday or next monday."
+///
+/// output will be as follows: [[When(This), Weekday(Friday)], [When(Next), Weekday(Monday)]]
+pub(crate) fn apply_generic<Tz: TimeZone>(
+    date_time: DateTime<Tz>,
+    source_str: &str,
+    rules: &[(&(dyn Rule<Tz> + '_), i32)],
+    exact_match: bool,
+) -> Vec<Result<MatchResult, DateTimeError>> {
+    // unbox errors, language functions report them without positions
+    apply_chart(date_time, source_str, rules, exact_match)
+        .selected
+        .into_iter()
+        .map(|item| item.map_err(|diagnostic| diagnostic.error))
+        .collect()
+}
+
+/// Same as "apply_generic" but returns all candidates rules found along with the selected ones
+pub(crate) fn apply_chart<Tz: TimeZone>(
+    date_time: DateTime<Tz>,
+    source_str: &str,
+    rules: &[(&(dyn Rule<Tz> + '_), i32)],
+    exact_match: bool,
+) -> Chart {
+    // empty vector of matched tokens and errors
+    let mut matched_tokens = Vec::new();
+
+    // the input is tokenized once, rules share its words and tokens
+    let mut languages: Vec<Language> = rules
+        .iter()
+        .filter_map(|(rule, _)| rule.language())
+        .collect();
+    languages.sort_by_key(|language| language.code());
+    languages.dedup();
+    let corrections = lattice::scoped(Lattice::new(source_str, &languages), |lattice| {
+        for (rule, priority) in rules {
+            let language = rule.language();
+            lattice.set_language(language);
+            let matches = rule.apply(source_str, exact_match, &date_time);
+            trace::rule_applied(rule.name(), language, *priority, source_str, &matches);
+            for item in matches {
+                let bounds = MatchBounds::new(item.start_idx, item.end_idx);
+                matched_tokens.push((
+                    *priority,
+                    language,
+                    match item.result {
+                        Ok(context) => Ok(MatchResult::new(context, item.start_idx, item.end_idx)
+                            .with_language(language)
+                            .with_confidence(item.confidence)),
+                        Err(error) => {
+                            let error = SemanticError::new(error, bounds)
+                                .with_suggestions(item.suggestions);
+                            Err(match item.stated {
+                                Some(stated) => error.with_stated(stated),
+                                None => error,
+                            })
+                        }
+                    },
+                ));
+            }
+        }
+        lattice.corrections()
+    });
+
+    // all rules were applied at this step and the results were saved into matched_tokens vector
+
+    let session = super::session::current();
+
+    let bounds = |items: &[(i32, Option<Language>, Result<MatchResult, SemanticError>)]| {
+        items
+            .iter()
+            .map(|(_, _, item)| item_bounds(item))
+            .collect::<Vec<_>>()
+    };
+
+    // weak words are dates only in a date context, e.g. "may" in "you may go" is not
+    let before = bounds(&matched_tokens);
+    let matched_tokens = remove_weak(source_str, matched_tokens, session.min_confidence);
+    trace::removed(
+        RemovalReason::Weak,
+        source_str,
+        &before,
+        &bounds(&matched_tokens),
+    );
+
+    // intersections of matches in different languages are resolved by the language policy
+    let before = bounds(&matched_tokens);
+    let matched_tokens =
+        remove_foreign(matched_tokens, &session.languages, session.language_policy);
+    let after: Vec<_> = matched_tokens
+        .iter()
+        .map(|(_, item)| item_bounds(item))
+        .collect();
+    trace::removed(RemovalReason::Foreign, source_str, &before, &after);
+
+    // matches of rules with higher priorities win over intersecting ones
+    let mut matched_tokens = remove_outranked(matched_tokens);
+    let before = after;
+    let after: Vec<_> = matched_tokens.iter().map(item_bounds).collect();
+    trace::removed(RemovalReason::Outranked, source_str, &before, &after);
+
+    // then we sort resulting vector by tokens start offsets
+    matched_tokens.sort_by_key(|k| item_bounds(k).0);
+
+    // combine offsets with dates they refer to
+    let matched_tokens = attach_offsets(source_str, matched_tokens);
+
+    // then select the best non overlapping matches, intersections which can't be resolved
+    // are treated as errors
+    let chart = chart::resolve(
+        source_str,
+        matched_tokens,
+        session.language_policy == LanguagePolicy::Strict,
+    );
+
+    trace::unselected(source_str, &chart);
+
+    Chart {
+        corrections,
+        ..chart
+    }
+}
+
 /// Returns start and end indices of a match, accepts following arguments:
 ///
 /// prefix - vector of characters before match start
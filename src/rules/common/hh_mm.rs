@@ -0,0 +1,88 @@
+use super::combinators::tokenize_count_symbols;
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, Token};
+use chrono::prelude::*;
+use nom::{many_till, named_args, tuple, types::CompleteStr};
+
+// 24-hour clock time, e.g. "14:30", languages which use it by default share this rule
+
+define_num!(hours: (Token::Number, Priority(0)));
+define_num!(minutes: (Token::Number, Priority(1)));
+
+define_char!(colon: Priority(10), ':');
+
+named_args!(parse<'a>(_exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+
+    many_till!(tokenize_count_symbols, tuple!(hours, colon, minutes))
+);
+
+make_interpreter!(positions = 3);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let hours = match res.token_by_priority(Priority(0)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    let minutes = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    if !(0..=23).contains(&hours) {
+        return Err(invalid_time_error(input, "hours", hours));
+    }
+    if !(0..=59).contains(&minutes) {
+        return Err(invalid_time_error(input, "minutes", minutes));
+    }
+
+    ctx.hour = Some(hours);
+    ctx.minute = Some(minutes);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_hh_mm() {
+        let result = interpret("Termin 14:30", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(7, 12)));
+        assert_eq!(result.get_hours(), 14);
+        assert_eq!(result.get_minutes(), 30);
+
+        let result = interpret("0:05", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 0);
+        assert_eq!(result.get_minutes(), 5);
+
+        let result = interpret("24:10", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("24:10", "hours", 24).extract_error()
+        );
+
+        let result = interpret("10:60", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("10:60", "minutes", 60).extract_error()
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use super::common_matchers::match_num;
+use super::consts;
+use super::errors::{ambiguous_time_error, invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult};
+use super::tokens::{Adverbs, Month, Priority, TimeInterval, Token, Weekday as Day, When};
+use super::{is_leap_year, DAYS_IN_MONTH};
+use crate::parser::add_months;
+use chrono::prelude::*;
+use num_traits::FromPrimitive;
+use time::Duration;
+
+// Interpreters shared by languages whose rules differ only in vocabulary, a rule module defines
+// its words and imports the interpreter as "make_time"
+
+/// Interval from now, e.g. "in 5 minutes": (number, adverb or int word) at Priority(1) and time
+/// interval at Priority(2)
+pub(crate) fn deadline<'b, Tz: TimeZone>(
+    res: &RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    interval(res, tz_aware, input, 1)
+}
+
+/// Interval before now, e.g. "5 minutes ago", tokens are the same as in "deadline"
+pub(crate) fn past_time<'b, Tz: TimeZone>(
+    res: &RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    interval(res, tz_aware, input, -1)
+}
+
+fn interval<'b, Tz: TimeZone>(
+    res: &RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+    sign: i32,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let token = res.token_by_priority(Priority(1));
+
+    let num = match token {
+        Some(Token::Adverbs(Adverbs::Half)) => {
+            ctx.set_duration(sign * 30 * consts::MINUTE);
+            return Ok(ctx);
+        }
+        Some(Token::Adverbs(Adverbs::Few)) => 3,
+        None => 1,
+        token => match_num(token).unwrap(),
+    };
+
+    if num < 0 {
+        return Err(invalid_time_error(input, "number", num));
+    }
+
+    let signed = sign * num;
+
+    let overflow = || invalid_time_error(input, "number", num);
+    let seconds = |unit: i32| signed.checked_mul(unit).ok_or_else(overflow);
+
+    match res.token_by_priority(Priority(2)) {
+        Some(Token::TimeInterval(TimeInterval::Second)) => ctx.set_duration(signed),
+        Some(Token::TimeInterval(TimeInterval::Minute)) => {
+            ctx.set_duration(seconds(consts::MINUTE)?)
+        }
+        Some(Token::TimeInterval(TimeInterval::Hour)) => ctx.set_duration(seconds(consts::HOUR)?),
+        Some(Token::TimeInterval(TimeInterval::Day)) => ctx.set_duration(seconds(consts::DAY)?),
+        Some(Token::TimeInterval(TimeInterval::Week)) => ctx.set_duration(seconds(consts::WEEK)?),
+        Some(Token::TimeInterval(TimeInterval::Month)) => {
+            let today = tz_aware.naive_local().date();
+            let date = add_months(today, signed).ok_or_else(overflow)?;
+            ctx.duration = date - today;
+        }
+        Some(Token::TimeInterval(TimeInterval::Year)) => {
+            let today = tz_aware.naive_local().date();
+            let date = signed
+                .checked_mul(12)
+                .and_then(|months| add_months(today, months))
+                .ok_or_else(overflow)?;
+            ctx.duration = date - today;
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(ctx)
+}
+
+/// Day of a week, e.g. "next friday": weekday at Priority(0) and optional "next" or "last" at
+/// Priority(1)
+pub(crate) fn weekday<'b, Tz: TimeZone>(
+    res: &RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let day = day_of_week(res);
+    let weekday = i64::from(tz_aware.weekday().num_days_from_monday());
+    // stated weekday, parser checks it against the date it is merged with
+    ctx.weekday = Weekday::from_i64(day);
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::When(When::Next)) => {
+            let delta = day - weekday;
+            let delta = if delta > 0 { delta } else { 7 + delta };
+            ctx.set_duration(Duration::days(delta).num_seconds());
+        }
+        Some(Token::When(When::Last)) => {
+            let delta = weekday - day;
+            let delta = if delta > 0 { delta } else { 7 + delta };
+            ctx.set_duration(-Duration::days(delta).num_seconds());
+        }
+        _ => ctx.set_duration(upcoming_day(&ctx, day, weekday, input)?),
+    }
+
+    Ok(ctx)
+}
+
+/// Day of the current, next or previous calendar week, e.g. "下周五" is friday of the next week
+/// rather than the nearest friday: tokens are the same as in "weekday" plus "this"
+pub(crate) fn week_day<'b, Tz: TimeZone>(
+    res: &RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let day = day_of_week(res);
+    let weekday = i64::from(tz_aware.weekday().num_days_from_monday());
+    ctx.weekday = Weekday::from_i64(day);
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::When(When::Next)) => {
+            ctx.set_duration(Duration::days(7 - weekday + day).num_seconds());
+        }
+        Some(Token::When(When::Last)) => {
+            ctx.set_duration(Duration::days(day - weekday - 7).num_seconds());
+        }
+        Some(Token::When(When::This)) => {
+            ctx.set_duration(Duration::days(day - weekday).num_seconds());
+        }
+        _ => ctx.set_duration(upcoming_day(&ctx, day, weekday, input)?),
+    }
+
+    Ok(ctx)
+}
+
+/// Number of the day of a week at Priority(0), Monday is 0
+fn day_of_week(res: &RuleResult) -> i64 {
+    match res.token_by_priority(Priority(0)) {
+        Some(Token::Weekday(Day::Monday)) => 0,
+        Some(Token::Weekday(Day::Tuesday)) => 1,
+        Some(Token::Weekday(Day::Wednesday)) => 2,
+        Some(Token::Weekday(Day::Thursday)) => 3,
+        Some(Token::Weekday(Day::Friday)) => 4,
+        Some(Token::Weekday(Day::Saturday)) => 5,
+        Some(Token::Weekday(Day::Sunday)) => 6,
+        _ => unreachable!(),
+    }
+}
+
+/// Seconds until the day of the current week, a day which has passed is ambiguous unless an
+/// explicit date next to it tells which one is meant, the context states the weekday
+fn upcoming_day<'b>(
+    ctx: &Context,
+    day: i64,
+    weekday: i64,
+    input: &'b str,
+) -> Result<i64, SemanticError<'b>> {
+    if weekday <= day {
+        Ok(Duration::days(day - weekday).num_seconds())
+    } else {
+        // previous or next week day? we don't know!
+        Err(ambiguous_time_error(input).with_stated(*ctx))
+    }
+}
+
+/// Day of a month, e.g. "5 march": optional day number at Priority(0) and month at Priority(1)
+pub(crate) fn month_date<'b, Tz: TimeZone>(
+    res: &RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    // if day is omitted, assume it is 1st day of a month
+    let day = match res.token_by_priority(Priority(0)) {
+        Some(Token::Number(n)) => n,
+        _ => 1,
+    };
+
+    if day <= 0 {
+        return Err(invalid_time_error(input, "day", day));
+    }
+
+    let month = match res.token_by_priority(Priority(1)) {
+        Some(Token::Month(Month::January)) => 1,
+        Some(Token::Month(Month::February)) => 2,
+        Some(Token::Month(Month::March)) => 3,
+        Some(Token::Month(Month::April)) => 4,
+        Some(Token::Month(Month::May)) => 5,
+        Some(Token::Month(Month::June)) => 6,
+        Some(Token::Month(Month::July)) => 7,
+        Some(Token::Month(Month::August)) => 8,
+        Some(Token::Month(Month::September)) => 9,
+        Some(Token::Month(Month::October)) => 10,
+        Some(Token::Month(Month::November)) => 11,
+        Some(Token::Month(Month::December)) => 12,
+        _ => unreachable!(),
+    };
+
+    // 29 days in february for leap years
+    let days_in_month = if month == 2 && is_leap_year(tz_aware.year()) {
+        29
+    } else {
+        DAYS_IN_MONTH[month as usize - 1]
+    };
+
+    if day > days_in_month {
+        return Err(invalid_time_error(input, "day", day).with_nearest(days_in_month));
+    }
+
+    ctx.day = Some(day);
+    ctx.month = Some(month);
+
+    Ok(ctx)
+}
@@ -1,9 +1,13 @@
 use crate::rules::combinators;
 use crate::rules::common;
+use crate::rules::common_matchers;
+use crate::rules::consts;
 use crate::rules::errors;
 use crate::rules::rules;
 use crate::rules::tokens;
 
+pub mod hh_mm;
+pub(crate) mod interpreters;
 pub mod slash_dmy;
 
 pub(crate) const DAYS_IN_MONTH: &[i32; 12] = &[31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
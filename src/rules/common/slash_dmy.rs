@@ -71,7 +71,8 @@ fn make_time<'a, 'b, Tz: TimeZone>(
     };
 
     if day < 1 || day > days_in_month {
-        return Err(invalid_time_error(input, "day", day));
+        let nearest = day.clamp(1, days_in_month);
+        return Err(invalid_time_error(input, "day", day).with_nearest(nearest));
     }
 
     ctx.year = Some(year);
@@ -1,4 +1,4 @@
-use super::tokens::{IntWord, Ordinals, Token};
+use super::tokens::{IntWord, Ordinals, TimeOfDay, Token};
 
 pub(crate) fn match_num(token: Option<Token>) -> Option<i32> {
     token.and_then(|t| match t {
@@ -55,3 +55,15 @@ pub(crate) fn match_ordinal(token: Option<Token>) -> Option<i32> {
         _ => None,
     })
 }
+
+/// Hour a part of the day starts at, e.g. 18 for "evening"
+pub(crate) fn match_time_of_day(token: Option<Token>) -> Option<i32> {
+    token.and_then(|t| match t {
+        Token::TimeOfDay(TimeOfDay::Morning) => Some(8),
+        Token::TimeOfDay(TimeOfDay::Noon) => Some(12),
+        Token::TimeOfDay(TimeOfDay::Afternoon) => Some(15),
+        Token::TimeOfDay(TimeOfDay::Evening) => Some(18),
+        Token::TimeOfDay(TimeOfDay::Night) => Some(23),
+        _ => None,
+    })
+}
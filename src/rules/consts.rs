@@ -3,3 +3,6 @@ pub(crate) const MINUTE: i32 = 60 * SECOND;
 pub(crate) const HOUR: i32 = 60 * MINUTE;
 pub(crate) const DAY: i32 = 24 * HOUR;
 pub(crate) const WEEK: i32 = 7 * DAY;
+
+/// Longer business day intervals (about 40 years) are hardly meant literally
+pub(crate) const MAX_BUSINESS_DAYS: i32 = 10_000;
@@ -0,0 +1,125 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_time_of_day;
+use super::consts;
+use super::errors::SemanticError;
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, TimeOfDay, Token, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+define!(now: (Token::When(When::Now), Priority(0)), "jetzt", Dist(1));
+
+define!(today: (Token::When(When::Today), Priority(1)), "heute", Dist(1));
+define!(tomorrow: (Token::When(When::Tomorrow), Priority(1)), "morgen", Dist(1));
+define!(yesterday: (Token::When(When::Yesterday), Priority(1)), "gestern", Dist(1));
+define!(
+    day_after_tomorrow:
+    [(Token::When(When::DayAfterTomorrow), Priority(1)), "übermorgen", Dist(1)] |
+    [(Token::When(When::DayAfterTomorrow), Priority(1)), "uebermorgen", Dist(1)]
+);
+define!(
+    day_before_yesterday: (Token::When(When::DayBeforeYesterday), Priority(1)), "vorgestern", Dist(2)
+);
+
+combine!(when => today | tomorrow | yesterday | day_after_tomorrow | day_before_yesterday);
+
+// "heute morgen" is this morning, while "morgen früh" is tomorrow morning
+
+define!(
+    morning:
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "früh", Dist(1)] |
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "morgen", Dist(1)] |
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "vormittag", Dist(1)]
+);
+define!(noon: (Token::TimeOfDay(TimeOfDay::Noon), Priority(2)), "mittag", Dist(1));
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(2)), "nachmittag", Dist(2));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "abend", Dist(1));
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(2)), "nacht", Dist(1));
+
+combine!(time_of_day => morning | noon | afternoon | evening | night);
+
+define!(
+    at:
+    [(Token::Char, Priority(3)), "am", Dist(0)] |
+    [(Token::Char, Priority(3)), "zu", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // morgen früh, heute Abend, gestern am Nachmittag, etc.
+            tuple!(apply!(when, exact_match),
+                   preceded!(opt!(apply!(at, true)), apply!(time_of_day, exact_match))) |
+            // heute, morgen, übermorgen, etc.
+            tuple!(apply!(when, exact_match), stub) |
+            // jetzt
+            tuple!(apply!(now, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    _input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::When(When::Yesterday)) => ctx.set_duration(-consts::DAY),
+        Some(Token::When(When::Tomorrow)) => ctx.set_duration(consts::DAY),
+        Some(Token::When(When::DayAfterTomorrow)) => ctx.set_duration(2 * consts::DAY),
+        Some(Token::When(When::DayBeforeYesterday)) => ctx.set_duration(-2 * consts::DAY),
+        _ => (),
+    }
+
+    if let Some(hour) = match_time_of_day(res.token_by_priority(Priority(2))) {
+        ctx.hour = Some(hour);
+        ctx.minute = Some(0);
+        ctx.part_of_day = true;
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_casual_date() {
+        let result = interpret("ruf mich morgen früh an", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(9, 21)));
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 8);
+
+        let result = interpret("heute morgen", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 0);
+        assert_eq!(result.get_hours(), 8);
+
+        let result = interpret("heute abend", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 18);
+
+        let result = interpret("übermorgen", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * consts::DAY as i64);
+
+        let result = interpret("gestern am nachmittag", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -consts::DAY as i64);
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("morgen", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 0);
+    }
+}
@@ -0,0 +1,107 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::month_date as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Month, Priority, Token, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+// day numbers are usually followed by a dot, e.g. "5. März", the dot is skipped as a punctuation
+define_num!(day_num: (Token::Number, Priority(0)));
+
+define!(
+    january:
+    [(Token::Month(Month::January), Priority(1)), "januar", Dist(1)] |
+    [(Token::Month(Month::January), Priority(1)), "jänner", Dist(1)]
+);
+define!(february: (Token::Month(Month::February), Priority(1)), "februar", Dist(1));
+define!(
+    march:
+    [(Token::Month(Month::March), Priority(1)), "märz", Dist(1)] |
+    [(Token::Month(Month::March), Priority(1)), "maerz", Dist(1)]
+);
+define!(april: (Token::Month(Month::April), Priority(1)), "april", Dist(1));
+define!(may: (Token::Month(Month::May), Priority(1)), "mai", Dist(0));
+define!(june: (Token::Month(Month::June), Priority(1)), "juni", Dist(0));
+define!(july: (Token::Month(Month::July), Priority(1)), "juli", Dist(0));
+define!(august: (Token::Month(Month::August), Priority(1)), "august", Dist(1));
+define!(september: (Token::Month(Month::September), Priority(1)), "september", Dist(1));
+define!(october: (Token::Month(Month::October), Priority(1)), "oktober", Dist(1));
+define!(november: (Token::Month(Month::November), Priority(1)), "november", Dist(1));
+define!(december: (Token::Month(Month::December), Priority(1)), "dezember", Dist(1));
+
+combine!(month => january | february | march | april | may | june | july | august | september |
+                  october | november | december);
+
+define!(
+    month_abbr:
+    [(Token::Month(Month::January), Priority(1)), "jan", Dist(0)] |
+    [(Token::Month(Month::February), Priority(1)), "feb", Dist(0)] |
+    [(Token::Month(Month::March), Priority(1)), "mär", Dist(0)] |
+    [(Token::Month(Month::March), Priority(1)), "mrz", Dist(0)] |
+    [(Token::Month(Month::April), Priority(1)), "apr", Dist(0)] |
+    [(Token::Month(Month::June), Priority(1)), "jun", Dist(0)] |
+    [(Token::Month(Month::July), Priority(1)), "jul", Dist(0)] |
+    [(Token::Month(Month::August), Priority(1)), "aug", Dist(0)] |
+    [(Token::Month(Month::September), Priority(1)), "sep", Dist(0)] |
+    [(Token::Month(Month::September), Priority(1)), "sept", Dist(0)] |
+    [(Token::Month(Month::October), Priority(1)), "okt", Dist(0)] |
+    [(Token::Month(Month::November), Priority(1)), "nov", Dist(0)] |
+    [(Token::Month(Month::December), Priority(1)), "dez", Dist(0)]
+);
+
+define!(
+    on:
+    [(Token::When(When::In), Priority(2)), "am", Dist(0)] |
+    [(Token::When(When::In), Priority(2)), "im", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 5. März, am 1. Mai, 24. Dez
+            tuple!(preceded!(opt!(apply!(on, true)), day_num),
+                   alt!(apply!(month, exact_match) | apply!(month_abbr, true))) |
+            // März, im Januar
+            tuple!(preceded!(opt!(apply!(on, true)), apply!(month, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_exact_month() {
+        let result = interpret("geburtstag am 5. märz", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(11, 22)));
+        assert_eq!(result.get_day(), 5);
+        assert_eq!(result.get_month(), 3);
+
+        let result = interpret("24. dez", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 24);
+        assert_eq!(result.get_month(), 12);
+
+        let result = interpret("im oktober", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 1);
+        assert_eq!(result.get_month(), 10);
+
+        let result = interpret("30. februar", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("30. februar", "day", 30).extract_error()
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::past_time as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Adverbs, IntWord, Priority, TimeInterval, Token};
+use chrono::prelude::*;
+use nom::{
+    alt, apply, call, many_till, named_args, opt, preceded, terminated, tuple, types::CompleteStr,
+};
+
+define!(ago: (Token::Ago, Priority(0)), "vor", Dist(0));
+
+// numerals and intervals in the dative case, e.g. "in einer Woche", "vor drei Tagen"
+
+define!(
+    one:
+    [(Token::IntWord(IntWord::One), Priority(1)), "ein", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "eine", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "einer", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "einem", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "einen", Dist(0)]
+);
+define!(two: (Token::IntWord(IntWord::Two), Priority(1)), "zwei", Dist(0));
+define!(three: (Token::IntWord(IntWord::Three), Priority(1)), "drei", Dist(0));
+define!(four: (Token::IntWord(IntWord::Four), Priority(1)), "vier", Dist(0));
+define!(
+    five:
+    [(Token::IntWord(IntWord::Five), Priority(1)), "fünf", Dist(1)] |
+    [(Token::IntWord(IntWord::Five), Priority(1)), "fuenf", Dist(1)]
+);
+define!(six: (Token::IntWord(IntWord::Six), Priority(1)), "sechs", Dist(1));
+define!(seven: (Token::IntWord(IntWord::Seven), Priority(1)), "sieben", Dist(1));
+define!(eight: (Token::IntWord(IntWord::Eight), Priority(1)), "acht", Dist(0));
+define!(nine: (Token::IntWord(IntWord::Nine), Priority(1)), "neun", Dist(0));
+define!(ten: (Token::IntWord(IntWord::Ten), Priority(1)), "zehn", Dist(0));
+define!(eleven: (Token::IntWord(IntWord::Eleven), Priority(1)), "elf", Dist(0));
+define!(
+    twelve:
+    [(Token::IntWord(IntWord::Twelve), Priority(1)), "zwölf", Dist(1)] |
+    [(Token::IntWord(IntWord::Twelve), Priority(1)), "zwoelf", Dist(1)]
+);
+
+combine!(int_word => one | two | three | four | five | six | seven | eight | nine | ten
+                         | eleven | twelve);
+
+define_num!(number: (Token::Number, Priority(1)));
+
+define!(few: (Token::Adverbs(Adverbs::Few), Priority(1)), "paar", Dist(0));
+define!(some: (Token::Adverbs(Adverbs::Few), Priority(1)), "einigen", Dist(1));
+
+// e.g. "ein paar"
+named_args!(a_few<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    alt!(preceded!(opt!(apply!(one, true)), apply!(few, exact_match)) | apply!(some, exact_match))
+);
+
+define!(half: (Token::Adverbs(Adverbs::Half), Priority(1)), "halben", Dist(1));
+define!(hour: (Token::Char, Priority(2)), "stunde", Dist(1));
+
+// e.g. "einer halben Stunde"
+named_args!(half_hour<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(preceded!(opt!(apply!(one, true)), apply!(half, exact_match)),
+                apply!(hour, exact_match))
+);
+
+define!(
+    seconds:
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "sekunde", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "sekunden", Dist(1)]
+);
+define!(
+    minutes:
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "minute", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "minuten", Dist(1)]
+);
+define!(
+    hours:
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "stunde", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "stunden", Dist(1)]
+);
+define!(
+    days:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "tag", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "tage", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "tagen", Dist(0)]
+);
+define!(
+    weeks:
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "woche", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "wochen", Dist(1)]
+);
+define!(
+    months:
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "monat", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "monate", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "monaten", Dist(1)]
+);
+define!(
+    years:
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "jahr", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "jahre", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "jahren", Dist(1)]
+);
+
+combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // vor 2 Stunden
+            tuple!(apply!(ago, true), number, apply!(time_interval, exact_match)) |
+            // vor zwei Tagen, vor ein paar Minuten
+            tuple!(apply!(ago, true),
+                   alt!(apply!(a_few, exact_match) | apply!(int_word, exact_match)),
+                   apply!(time_interval, exact_match)) |
+            // vor einer halben Stunde
+            tuple!(apply!(ago, true), apply!(half_hour, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_past_time() {
+        let result = interpret("das war vor 3 stunden", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(8, 21)));
+        assert_eq!(result.get_duration_sec() as i32, -3 * consts::HOUR);
+
+        let result = interpret("vor fünf minuten", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -5 * consts::MINUTE);
+
+        let result = interpret("vor einer halben stunde", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -30 * consts::MINUTE);
+
+        let result = interpret("vor ein paar tagen", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -3 * consts::DAY);
+
+        let result = interpret("vor zwei jahren", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -730 * consts::DAY);
+    }
+}
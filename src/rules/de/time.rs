@@ -0,0 +1,148 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, TimeOfDay, Token, When};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define!(at: (Token::When(When::In), Priority(0)), "um", Dist(0));
+
+define_num!(hours: (Token::Number, Priority(1)));
+
+define_char!(colon: Priority(2), ':');
+
+define_num!(minutes: (Token::Number, Priority(3)));
+
+define!(hour_word: (Token::Char, Priority(4)), "uhr", Dist(0));
+
+// adverbs for parts of the day, e.g. "um 3 Uhr nachmittags", "um 10 abends"
+
+define!(
+    morning:
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "morgens", Dist(1)] |
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "früh", Dist(1)] |
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "vormittags", Dist(1)]
+);
+define!(noon: (Token::TimeOfDay(TimeOfDay::Noon), Priority(5)), "mittags", Dist(1));
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(5)), "nachmittags", Dist(2));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(5)), "abends", Dist(1));
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(5)), "nachts", Dist(1));
+
+combine!(day_part => morning | noon | afternoon | evening | night);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // hours:minutes, e.g. "um 14:30 Uhr", "um 9:15 abends"
+            tuple!(apply!(at, true), hours, colon, minutes,
+                   alt!(apply!(hour_word, exact_match) | stub),
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // e.g. "14:30 Uhr", "9:15 abends", plain "14:30" is handled by common rules
+            tuple!(stub, hours, colon, minutes, apply!(hour_word, exact_match),
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            tuple!(stub, hours, colon, minutes, stub, apply!(day_part, exact_match)) |
+            // e.g. "um 14 Uhr", "14 Uhr 30", "3 Uhr nachmittags"
+            tuple!(alt!(apply!(at, true) | stub), hours, stub,
+                   apply!(hour_word, exact_match), alt!(minutes | stub),
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // e.g. "um 10 abends"
+            tuple!(apply!(at, true), hours, stub, stub, stub, apply!(day_part, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 6);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let mut hrs = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    let minutes = match res.token_by_priority(Priority(3)) {
+        Some(Token::Number(n)) => n,
+        _ => 0,
+    };
+
+    if !(0..=23).contains(&hrs) {
+        return Err(invalid_time_error(input, "hours", hrs));
+    }
+    if !(0..=59).contains(&minutes) {
+        return Err(invalid_time_error(input, "minutes", minutes));
+    }
+
+    // 24-hour clock is used by default, parts of the day only matter for hours below 12
+    match res.token_by_priority(Priority(5)) {
+        Some(Token::TimeOfDay(TimeOfDay::Afternoon))
+        | Some(Token::TimeOfDay(TimeOfDay::Evening))
+            if hrs < 12 =>
+        {
+            hrs += 12
+        }
+        // "12 Uhr nachts" is midnight, "11 Uhr nachts" is 23:00 but "2 Uhr nachts" is 2:00
+        Some(Token::TimeOfDay(TimeOfDay::Night)) if hrs == 12 => hrs = 0,
+        Some(Token::TimeOfDay(TimeOfDay::Night)) if (9..12).contains(&hrs) => hrs += 12,
+        _ => (),
+    }
+
+    ctx.hour = Some(hrs);
+    ctx.minute = Some(minutes);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_time() {
+        let result = interpret("treffen um 14 uhr", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(8, 17)));
+        assert_eq!(result.get_hours(), 14);
+        assert_eq!(result.get_minutes(), 0);
+
+        let result = interpret("um 9:15 uhr", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 9);
+        assert_eq!(result.get_minutes(), 15);
+
+        let result = interpret("14 uhr 30", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 14);
+        assert_eq!(result.get_minutes(), 30);
+
+        let result = interpret("um 3 uhr nachmittags", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("um 11 nachts", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 23);
+
+        let result = interpret("um 2 uhr nachts", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 2);
+
+        let result = interpret("um 5 prozent", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+
+    #[test]
+    fn test_wrong_times() {
+        let result = interpret("25 uhr", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("25 uhr", "hours", 25).extract_error()
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::weekday as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Priority, Token, Weekday as Day, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+// two letter abbreviations like "do" or "so" are ordinary words, so they are not recognized
+
+define!(monday: (Token::Weekday(Day::Monday), Priority(0)), "montag", Dist(1));
+define!(tuesday: (Token::Weekday(Day::Tuesday), Priority(0)), "dienstag", Dist(1));
+define!(wednesday: (Token::Weekday(Day::Wednesday), Priority(0)), "mittwoch", Dist(1));
+define!(thursday: (Token::Weekday(Day::Thursday), Priority(0)), "donnerstag", Dist(2));
+define!(friday: (Token::Weekday(Day::Friday), Priority(0)), "freitag", Dist(1));
+define!(
+    saturday:
+    [(Token::Weekday(Day::Saturday), Priority(0)), "samstag", Dist(1)] |
+    [(Token::Weekday(Day::Saturday), Priority(0)), "sonnabend", Dist(1)]
+);
+define!(sunday: (Token::Weekday(Day::Sunday), Priority(0)), "sonntag", Dist(1));
+
+combine!(day_of_week => monday | tuesday | wednesday | thursday | friday | saturday | sunday);
+
+// adjectives in all genders and cases, e.g. "nächsten Dienstag", "am nächsten Dienstag"
+
+define!(
+    this:
+    [(Token::When(When::This), Priority(1)), "diesen", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "dieser", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "diesem", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "diese", Dist(0)]
+);
+
+define!(
+    next:
+    [(Token::When(When::Next), Priority(1)), "nächsten", Dist(1)] |
+    [(Token::When(When::Next), Priority(1)), "nächster", Dist(1)] |
+    [(Token::When(When::Next), Priority(1)), "nächste", Dist(1)] |
+    [(Token::When(When::Next), Priority(1)), "naechsten", Dist(1)] |
+    [(Token::When(When::Next), Priority(1)), "kommenden", Dist(1)] |
+    [(Token::When(When::Next), Priority(1)), "kommender", Dist(1)]
+);
+
+define!(
+    last:
+    [(Token::When(When::Last), Priority(1)), "letzten", Dist(1)] |
+    [(Token::When(When::Last), Priority(1)), "letzter", Dist(1)] |
+    [(Token::When(When::Last), Priority(1)), "letzte", Dist(0)] |
+    [(Token::When(When::Last), Priority(1)), "vergangenen", Dist(2)] |
+    [(Token::When(When::Last), Priority(1)), "vergangener", Dist(2)]
+);
+
+combine!(when => this | next | last);
+
+define!(
+    on:
+    [(Token::Char, Priority(2)), "am", Dist(0)] |
+    [(Token::Char, Priority(2)), "an", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // when and then any day of week, e.g. "nächsten Dienstag", "am letzten freitag"
+            tuple!(preceded!(opt!(apply!(on, true)), apply!(when, exact_match)),
+                   apply!(day_of_week, exact_match)) |
+            // day of week, e.g. "am Freitag"
+            tuple!(preceded!(opt!(apply!(on, true)), apply!(day_of_week, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::ambiguous_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_weekdays() {
+        let result = interpret("bis nächsten dienstag", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(4, 22)));
+        assert_eq!(result.get_duration_sec(), 7 * 86400);
+
+        let result = interpret("am letzten freitag", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -4 * 86400);
+
+        let result = interpret("naechsten samstag", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 4 * 86400);
+
+        let result = interpret("am mitwoch", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 86400);
+
+        let result = interpret("montag", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            ambiguous_time_error("montag").extract_error()
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_num;
+use super::consts;
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{
+    Articles, Boundary, IntWord, Prepositions, Priority, TimeInterval, Token, When,
+};
+use chrono::prelude::*;
+
+use nom::{
+    alt, apply, call, many_till, named_args, opt, pair, terminated, tuple, types::CompleteStr,
+};
+
+define!(
+    when:
+    [(Token::When(When::Within), Priority(0)), "within", Dist(1)] |
+    [(Token::When(When::In), Priority(0)), "in", Dist(0)]
+);
+
+define!(
+    next_last:
+    [(Token::When(When::Next), Priority(0)), "next", Dist(1)] |
+    [(Token::When(When::Next), Priority(0)), "following", Dist(2)] |
+    [(Token::When(When::Last), Priority(0)), "previous", Dist(2)] |
+    [(Token::When(When::Last), Priority(0)), "last", Dist(1)]
+);
+
+define!(
+    article:
+    [(Token::Articles(Articles::A), Priority(1)), "a", Dist(0)] |
+    [(Token::Articles(Articles::An), Priority(1)), "an", Dist(0)]
+);
+
+define!(one: (Token::IntWord(IntWord::One), Priority(1)), "one", Dist(0));
+define!(two: (Token::IntWord(IntWord::Two), Priority(1)), "two", Dist(0));
+define!(three: (Token::IntWord(IntWord::Three), Priority(1)), "three", Dist(1));
+define!(four: (Token::IntWord(IntWord::Four), Priority(1)), "four", Dist(1));
+define!(five: (Token::IntWord(IntWord::Five), Priority(1)), "five", Dist(1));
+define!(six: (Token::IntWord(IntWord::Six), Priority(1)), "six", Dist(0));
+define!(seven: (Token::IntWord(IntWord::Seven), Priority(1)), "seven", Dist(1));
+define!(eight: (Token::IntWord(IntWord::Eight), Priority(1)), "eight", Dist(1));
+define!(nine: (Token::IntWord(IntWord::Nine), Priority(1)), "nine", Dist(1));
+define!(ten: (Token::IntWord(IntWord::Ten), Priority(1)), "ten", Dist(0));
+
+combine!(int_word => one | two | three | four | five | six | seven | eight | nine | ten);
+
+define_num!(number: (Token::Number, Priority(1)));
+
+define!(
+    business:
+    [(Token::TimeInterval(TimeInterval::BusinessDay), Priority(2)), "business", Dist(2)] |
+    [(Token::TimeInterval(TimeInterval::BusinessDay), Priority(2)), "working", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::BusinessDay), Priority(2)), "work", Dist(0)]
+);
+
+define!(
+    day_noun:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "days", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "day", Dist(0)]
+);
+
+define!(
+    workday:
+    [(Token::TimeInterval(TimeInterval::BusinessDay), Priority(2)), "workdays", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::BusinessDay), Priority(2)), "workday", Dist(1)]
+);
+
+// e.g. "business days", "working day", "workdays"
+named_args!(business_day<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    alt!(terminated!(apply!(business, exact_match), apply!(day_noun, exact_match)) |
+         apply!(workday, exact_match))
+);
+
+define!(ago: (Token::Ago, Priority(3)), "ago", Dist(0));
+
+define!(
+    boundary:
+    [(Token::Boundary(Boundary::End), Priority(4)), "end", Dist(0)] |
+    [(Token::Boundary(Boundary::End), Priority(4)), "close", Dist(1)]
+);
+
+define!(of: (Token::Prepositions(Prepositions::Of), Priority(5)), "of", Dist(0));
+define!(the: (Token::Articles(Articles::The), Priority(5)), "the", Dist(0));
+
+// e.g. "end of", "close of the"
+named_args!(end_of<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(boundary, exact_match), pair!(apply!(of, true), opt!(apply!(the, true))))
+);
+
+define!(
+    eod:
+    [(Token::Boundary(Boundary::End), Priority(4)), "eod", Dist(0)] |
+    [(Token::Boundary(Boundary::End), Priority(4)), "cob", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // e.g. "in 3 business days", "within a working day"
+            tuple!(apply!(when, exact_match),
+                   alt!(number | apply!(int_word, exact_match) | apply!(article, true)),
+                   apply!(business_day, exact_match), stub, stub) |
+            // e.g. "2 workdays ago"
+            tuple!(alt!(number | apply!(int_word, exact_match) | apply!(article, true)),
+                   apply!(business_day, exact_match), apply!(ago, true), stub, stub) |
+            // e.g. "next business day", "the previous working day"
+            tuple!(apply!(next_last, exact_match), apply!(business_day, exact_match), stub,
+                   stub, stub) |
+            // e.g. "end of business day", "close of business"
+            tuple!(apply!(end_of, exact_match),
+                   alt!(apply!(business_day, exact_match) | apply!(business, exact_match)),
+                   stub, stub, stub) |
+            // e.g. "eod", "by cob"
+            tuple!(apply!(eod, true), stub, stub, stub, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 5);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    // end of the current business day, or of the next one if today is a day off
+    if let Some(Token::Boundary(Boundary::End)) = res.token_by_priority(Priority(4)) {
+        ctx.business_days = Some(0);
+        ctx.hour = Some(17);
+        ctx.minute = Some(0);
+        return Ok(ctx);
+    }
+
+    let num = match res.token_by_priority(Priority(1)) {
+        Some(Token::Articles(_)) | None => 1,
+        token => match_num(token).unwrap(),
+    };
+
+    if !(0..=consts::MAX_BUSINESS_DAYS).contains(&num) {
+        return Err(invalid_time_error(input, "number", num));
+    }
+
+    let backwards = match res.token_by_priority(Priority(0)) {
+        Some(Token::When(When::Last)) => true,
+        _ => res.token_by_priority(Priority(3)).is_some(),
+    };
+
+    ctx.business_days = Some(if backwards { -num } else { num });
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_business_days() {
+        let result = interpret("reply in 3 business days", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(6, 24)));
+        assert_eq!(result.context.business_days, Some(3));
+
+        let result = interpret("within five workng days", false, fixed_time()).unwrap();
+        assert_eq!(result.context.business_days, Some(5));
+
+        let result = interpret("in a workday", false, fixed_time()).unwrap();
+        assert_eq!(result.context.business_days, Some(1));
+
+        let result = interpret("it was sent 2 workdays ago", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(12, 26)));
+        assert_eq!(result.context.business_days, Some(-2));
+
+        let result = interpret("the next business day", false, fixed_time()).unwrap();
+        assert_eq!(result.context.business_days, Some(1));
+
+        let result = interpret("previous working day", false, fixed_time()).unwrap();
+        assert_eq!(result.context.business_days, Some(-1));
+
+        let result = interpret("in -2 business days", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("in -2 business days", "number", -2).extract_error()
+        );
+
+        let result = interpret("in 99999999 business days", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("in 99999999 business days", "number", 99999999).extract_error()
+        );
+    }
+
+    #[test]
+    fn test_end_of_business_day() {
+        for input in &[
+            "by the end of business day",
+            "end of the business day",
+            "close of business",
+            "send it by eod",
+        ] {
+            let result = interpret(input, false, fixed_time()).unwrap();
+            assert_eq!(result.context.business_days, Some(0));
+            assert_eq!(result.get_hours(), 17);
+            assert_eq!(result.get_minutes(), 0);
+        }
+    }
+}
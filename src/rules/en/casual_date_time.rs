@@ -1,4 +1,5 @@
 use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_time_of_day;
 use super::consts;
 use super::errors::SemanticError;
 use super::rules::{Context, RuleResult, TokenDesc};
@@ -46,9 +47,7 @@ named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
             // today, tomorrow, yesterday, etc.
             tuple!(apply!(when, exact_match), stub) |
             // now
-            tuple!(apply!(now, exact_match), stub) |
-            // night, morning, evening, etc
-            tuple!(apply!(time_of_day, exact_match), stub)
+            tuple!(apply!(now, exact_match), stub)
         )
     )
 );
@@ -80,37 +79,16 @@ fn make_time<'a, 'b, Tz: TimeZone>(
             Token::When(When::Tonight) => {
                 ctx.hour = Some(23);
                 ctx.minute = Some(0);
+                ctx.part_of_day = true;
             }
             _ => (),
         }
     }
 
-    let token = res.token_by_priority(Priority(2));
-
-    if token.is_some() {
-        match token.unwrap() {
-            Token::TimeOfDay(TimeOfDay::Morning) => {
-                ctx.hour = Some(8);
-                ctx.minute = Some(0);
-            }
-            Token::TimeOfDay(TimeOfDay::Noon) => {
-                ctx.hour = Some(12);
-                ctx.minute = Some(0);
-            }
-            Token::TimeOfDay(TimeOfDay::Afternoon) => {
-                ctx.hour = Some(15);
-                ctx.minute = Some(0);
-            }
-            Token::TimeOfDay(TimeOfDay::Evening) => {
-                ctx.hour = Some(18);
-                ctx.minute = Some(0);
-            }
-            Token::TimeOfDay(TimeOfDay::Night) => {
-                ctx.hour = Some(23);
-                ctx.minute = Some(0);
-            }
-            _ => (),
-        }
+    if let Some(hour) = match_time_of_day(res.token_by_priority(Priority(2))) {
+        ctx.hour = Some(hour);
+        ctx.minute = Some(0);
+        ctx.part_of_day = true;
     }
 
     Ok(ctx)
@@ -0,0 +1,117 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Articles, Prepositions, Priority, TimeInterval, Token};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define!(day_noun: (Token::TimeInterval(TimeInterval::Day), Priority(0)), "day", Dist(0));
+
+define_num!(day_num: (Token::Number, Priority(1)));
+define_suffixed_num!(day_ord: (Token::Number, Priority(1)), "st" | "nd" | "rd" | "th");
+
+define!(of: (Token::Prepositions(Prepositions::Of), Priority(2)), "of", Dist(0));
+define!(the: (Token::Articles(Articles::The), Priority(2)), "the", Dist(0));
+
+define_year!(year: (Token::Number, Priority(3)));
+define!(year_noun: (Token::TimeInterval(TimeInterval::Year), Priority(3)), "year", Dist(1));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // e.g. "day 100 of 2019"
+            tuple!(apply!(day_noun, exact_match), day_num, apply!(of, true), year, stub) |
+            // e.g. "day 100 of the year"
+            tuple!(apply!(day_noun, exact_match), day_num, apply!(of, true), apply!(the, true),
+                   apply!(year_noun, exact_match)) |
+            // e.g. "100th day of 2019"
+            tuple!(day_ord, apply!(day_noun, exact_match), apply!(of, true), year, stub) |
+            // e.g. "the 100th day of the year"
+            tuple!(day_ord, apply!(day_noun, exact_match), apply!(of, true), apply!(the, true),
+                   apply!(year_noun, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 5);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    // "of the year" means current year
+    let year = match res.token_by_priority(Priority(3)) {
+        Some(Token::Number(n)) => n,
+        _ => tz_aware.year(),
+    };
+
+    let day = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    // ordinal day must exist in the given year, i.e. 366th day is valid for leap years only
+    let date = if day >= 1 {
+        NaiveDate::from_yo_opt(year, day as u32)
+    } else {
+        None
+    };
+
+    match date {
+        Some(date) => {
+            ctx.year = Some(date.year());
+            ctx.month = Some(date.month() as i32);
+            ctx.day = Some(date.day() as i32);
+        }
+        None => return Err(invalid_time_error(input, "day", day)),
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_day_of_year() {
+        let result = interpret("it happened on day 100 of 2020", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(15, 30)));
+        assert_eq!(result.get_year(), 2020);
+        assert_eq!(result.get_month(), 4);
+        assert_eq!(result.get_day(), 9);
+
+        let result = interpret("the 256th day of the year", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(4, 25)));
+        assert_eq!(result.get_year(), 2019);
+        assert_eq!(result.get_month(), 9);
+        assert_eq!(result.get_day(), 13);
+
+        let result = interpret("day 32 of the yaer", false, fixed_time()).unwrap();
+        assert_eq!(result.get_month(), 2);
+        assert_eq!(result.get_day(), 1);
+
+        let result = interpret("366th day of 2020", false, fixed_time()).unwrap();
+        assert_eq!(result.get_month(), 12);
+        assert_eq!(result.get_day(), 31);
+
+        let result = interpret("366th day of 2019", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("366th day of 2019", "day", 366).extract_error()
+        );
+    }
+}
@@ -1,8 +1,8 @@
-use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::combinators::{recognize_int, stub, tokenize_count_symbols, wrap_error, Dist};
 use super::common_matchers::match_num;
 use super::consts;
-use super::errors::{invalid_time_error, SemanticError};
-use super::rules::{Context, RuleResult, TokenDesc};
+use super::errors::{invalid_time_error, SemanticError, UNKNOWN};
+use super::rules::{Context, MyResult, RuleResult, TokenDesc};
 use super::tokens::{
     Adverbs, Articles, IntWord, Prepositions, Priority, TimeInterval, Token, When,
 };
@@ -94,6 +94,15 @@ define!(
 
 combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
 
+/// Succeeds without consuming anything only if the next word is not a number, this prevents
+/// "in week 42" from being treated as "in a week"
+fn not_number(input: CompleteStr) -> MyResult {
+    if recognize_int(input).is_ok() {
+        return wrap_error(input, UNKNOWN);
+    }
+    stub(input)
+}
+
 named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
                              ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
 
@@ -123,7 +132,8 @@ named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
             tuple!(apply!(when, exact_match), apply!(adverb, exact_match),
                    apply!(time_interval, exact_match), stub, stub) |
             // e.g.: in a month, in a second, etc.
-            tuple!(apply!(when, exact_match), apply!(time_interval, exact_match), stub, stub, stub)
+            tuple!(apply!(when, exact_match), apply!(time_interval, exact_match), not_number, stub,
+                   stub)
         )
     )
 );
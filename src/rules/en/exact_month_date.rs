@@ -332,7 +332,7 @@ fn make_time<'a, 'b, Tz: TimeZone>(
     };
 
     if day > days_in_month {
-        return Err(invalid_time_error(input, "day", day));
+        return Err(invalid_time_error(input, "day", day).with_nearest(days_in_month));
     }
 
     ctx.month = Some(month);
@@ -392,10 +392,12 @@ mod tests {
         assert_eq!(result.get_month(), 2);
 
         let result = interpret("31st february", false, fixed_time());
+        let err = result.unwrap_err();
         assert_eq!(
-            result.unwrap_err().extract_error(),
+            err.extract_error(),
             invalid_time_error("31st february", "day", 31).extract_error()
         );
+        assert_eq!(err.get_suggestions(), ["28th february"]);
 
         let result = interpret("feb. 4", false, fixed_time()).unwrap();
         assert_eq!(result.get_day(), 4);
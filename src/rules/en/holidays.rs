@@ -0,0 +1,181 @@
+use super::combinators::{recognize_phrase, stub, tokenize_count_symbols, wrap_error, Dist};
+use super::errors::{SemanticError, UNKNOWN};
+use super::rules::{Context, HolidayRef, MyResult, RuleResult, TokenDesc};
+use super::session;
+use super::tokens::{PToken, Prepositions, Priority, TimeInterval, Token, When};
+use crate::holidays::YearPolicy;
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define!(this: (Token::When(When::This), Priority(0)), "this", Dist(1));
+define!(last: (Token::When(When::Last), Priority(0)), "last", Dist(1));
+define!(past: (Token::When(When::Past), Priority(0)), "past", Dist(1));
+define!(next: (Token::When(When::Next), Priority(0)), "next", Dist(1));
+
+combine!(when => this | last | past | next);
+
+define!(day_noun: (Token::TimeInterval(TimeInterval::Day), Priority(0)), "day", Dist(0));
+
+define!(
+    relation:
+    [(Token::Prepositions(Prepositions::After), Priority(1)), "after", Dist(1)] |
+    [(Token::Prepositions(Prepositions::Before), Priority(1)), "before", Dist(1)]
+);
+
+define_year!(year: (Token::Number, Priority(3)));
+
+/// Recognizes holiday names known to the current session, the longest matching name wins, so
+/// "christmas eve" is not mistaken for "christmas", names registered later win ties
+fn holiday_name(input: CompleteStr, exact_match: bool) -> MyResult {
+    let session = session::current();
+
+    // (number of words, distance, tail, holiday id)
+    let mut best: Option<(usize, Dist, CompleteStr, usize)> = None;
+
+    for (id, words) in session.holidays.iter().enumerate() {
+        if let Some((tail, dist)) = recognize_phrase(input, words, exact_match) {
+            let better = match best {
+                None => true,
+                Some((len, ref best_dist, _, _)) => {
+                    words.len() > len || (words.len() == len && dist <= *best_dist)
+                }
+            };
+            if better {
+                best = Some((words.len(), dist, tail, id));
+            }
+        }
+    }
+
+    match best {
+        Some((_, dist, tail, id)) => Ok((
+            tail,
+            TokenDesc::new(PToken::PToken(Token::Holiday(id), Priority(2)), dist),
+        )),
+        None => wrap_error(input, UNKNOWN),
+    }
+}
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // e.g. "the day after thanksgiving", "day before christmas 2019"
+            tuple!(apply!(day_noun, exact_match), apply!(relation, exact_match),
+                   apply!(holiday_name, exact_match), alt!(year | stub)) |
+            // e.g. "next christmas", "last easter"
+            tuple!(apply!(when, exact_match), stub, apply!(holiday_name, exact_match), stub) |
+            // e.g. "on christmas", "easter monday 2019"
+            tuple!(stub, stub, apply!(holiday_name, exact_match), alt!(year | stub))
+        )
+    )
+);
+
+make_interpreter!(positions = 4);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    _input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let id = match res.token_by_priority(Priority(2)) {
+        Some(Token::Holiday(id)) => id,
+        _ => unreachable!(),
+    };
+
+    let policy = match res.token_by_priority(Priority(0)) {
+        Some(Token::When(When::Next)) => Some(YearPolicy::Future),
+        Some(Token::When(When::Last)) | Some(Token::When(When::Past)) => Some(YearPolicy::Past),
+        Some(Token::When(When::This)) => Some(YearPolicy::Current),
+        _ => None,
+    };
+
+    let day_offset = match res.token_by_priority(Priority(1)) {
+        Some(Token::Prepositions(Prepositions::After)) => 1,
+        Some(Token::Prepositions(Prepositions::Before)) => -1,
+        _ => 0,
+    };
+
+    if let Some(Token::Number(n)) = res.token_by_priority(Priority(3)) {
+        ctx.year = Some(n);
+    }
+
+    ctx.holiday = Some(HolidayRef {
+        id,
+        policy,
+        day_offset,
+    });
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::holidays::{default_calendars, holiday_names, YearPolicy};
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    fn holiday_name(id: usize) -> String {
+        holiday_names(&default_calendars())[id].1.clone()
+    }
+
+    #[test]
+    fn test_holiday_names() {
+        let result = interpret("see you on christmas", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(11, 20)));
+        let holiday = result.context.holiday.unwrap();
+        assert_eq!(holiday_name(holiday.id), "christmas");
+        assert_eq!(holiday.policy, None);
+        assert_eq!(holiday.day_offset, 0);
+
+        let result = interpret("christmas eve 2020", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 18)));
+        let holiday = result.context.holiday.unwrap();
+        assert_eq!(holiday_name(holiday.id), "christmas eve");
+        assert_eq!(result.context.year, Some(2020));
+
+        let result = interpret("new year's eve party", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 14)));
+        assert_eq!(
+            holiday_name(result.context.holiday.unwrap().id),
+            "new year's eve"
+        );
+
+        let result = interpret("easter mnday", false, fixed_time()).unwrap();
+        assert_eq!(
+            holiday_name(result.context.holiday.unwrap().id),
+            "easter monday"
+        );
+
+        let result = interpret("easter mnday", true, fixed_time()).unwrap();
+        assert_eq!(holiday_name(result.context.holiday.unwrap().id), "easter");
+    }
+
+    #[test]
+    fn test_relative_holidays() {
+        let result = interpret("the day after thanksgivng", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(4, 25)));
+        let holiday = result.context.holiday.unwrap();
+        assert_eq!(holiday_name(holiday.id), "thanksgiving");
+        assert_eq!(holiday.day_offset, 1);
+
+        let result = interpret("next halloween", false, fixed_time()).unwrap();
+        let holiday = result.context.holiday.unwrap();
+        assert_eq!(holiday_name(holiday.id), "halloween");
+        assert_eq!(holiday.policy, Some(YearPolicy::Future));
+
+        let result = interpret("last memorial day", false, fixed_time()).unwrap();
+        assert_eq!(
+            result.context.holiday.unwrap().policy,
+            Some(YearPolicy::Past)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, Span, TokenDesc};
+use super::tokens::{Prepositions, Priority, Token, When};
+use chrono::prelude::*;
+use num_traits::FromPrimitive;
+
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define!(
+    week_noun:
+    [(Token::Week, Priority(0)), "week", Dist(1)] |
+    [(Token::Week, Priority(0)), "wk", Dist(0)]
+);
+
+define_num!(week_num: (Token::Number, Priority(1)));
+define_prefixed_num!(week_code: (Token::Number, Priority(1)), "w" | "wk" | "cw" | "kw");
+
+define_char!(dash: Priority(2), '-');
+
+define_num!(weekday_num: (Token::Number, Priority(3)));
+
+define!(of: (Token::Prepositions(Prepositions::Of), Priority(4)), "of", Dist(0));
+
+define_year!(year: (Token::Number, Priority(5)));
+
+define!(this: (Token::When(When::This), Priority(6)), "this", Dist(1));
+define!(last: (Token::When(When::Last), Priority(6)), "last", Dist(1));
+define!(next: (Token::When(When::Next), Priority(6)), "next", Dist(1));
+
+combine!(when => this | last | next);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // week code with day of week and year, e.g. "w42-3 2019"
+            tuple!(week_code, dash, weekday_num, year) |
+            // week code with day of week, e.g. "w42-3"
+            tuple!(week_code, dash, weekday_num, stub) |
+            // week code with year, e.g. "w42 2019", "cw17 2019"
+            tuple!(week_code, year, stub, stub) |
+            // week code, e.g. "w42", "cw17"
+            tuple!(week_code, stub, stub, stub) |
+            // e.g. "next week 3", "last week 42"
+            tuple!(apply!(when, exact_match), apply!(week_noun, exact_match), week_num, stub) |
+            // e.g. "week 42 of 2019"
+            tuple!(apply!(week_noun, exact_match), week_num, apply!(of, exact_match), year) |
+            // e.g. "week 42 2019"
+            tuple!(apply!(week_noun, exact_match), week_num, year, stub) |
+            // e.g. "week 42", "in week 42"
+            tuple!(apply!(week_noun, exact_match), week_num, stub, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 4);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let week = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    // "next week 3" is the closest week 3 after the current week, "last week 3" is the closest
+    // one before it
+    let current = tz_aware.iso_week();
+    let current_week = current.week() as i32;
+    let year = match (
+        res.token_by_priority(Priority(5)),
+        res.token_by_priority(Priority(6)),
+    ) {
+        (Some(Token::Number(n)), _) => Some(n),
+        (_, Some(Token::When(When::Next))) if week > current_week => Some(current.year()),
+        (_, Some(Token::When(When::Next))) => Some(current.year() + 1),
+        (_, Some(Token::When(When::Last))) if week < current_week => Some(current.year()),
+        (_, Some(Token::When(When::Last))) => Some(current.year() - 1),
+        (_, Some(Token::When(When::This))) => Some(current.year()),
+        _ => None,
+    };
+    ctx.year = year;
+    let year = year.unwrap_or_else(|| tz_aware.year());
+
+    // not every year has 53 weeks, so let chrono decide whether the week exists
+    if week < 1 || NaiveDate::from_isoywd_opt(year, week as u32, Weekday::Mon).is_none() {
+        return Err(invalid_time_error(input, "week", week));
+    }
+
+    ctx.week = Some(week);
+
+    if let Some(Token::Number(n)) = res.token_by_priority(Priority(3)) {
+        // ISO weekdays are counted from 1 (Monday) to 7 (Sunday)
+        match Weekday::from_i32(n - 1) {
+            Some(weekday) if n >= 1 => ctx.weekday = Some(weekday),
+            _ => return Err(invalid_time_error(input, "weekday", n)),
+        }
+    } else {
+        ctx.span = Some(Span::Days(7));
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::{MatchBounds, Span};
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_week_number() {
+        let result = interpret("let's plan it for week 42", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(18, 25)));
+        assert_eq!(result.context.week, Some(42));
+        assert_eq!(result.context.year, None);
+        assert_eq!(result.context.span, Some(Span::Days(7)));
+
+        let result = interpret("in week 3", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(3, 9)));
+        assert_eq!(result.context.week, Some(3));
+
+        let result = interpret("week 42 of 2020", false, fixed_time()).unwrap();
+        assert_eq!(result.context.week, Some(42));
+        assert_eq!(result.context.year, Some(2020));
+
+        let result = interpret("wek 7 2020", false, fixed_time()).unwrap();
+        assert_eq!(result.context.week, Some(7));
+        assert_eq!(result.context.year, Some(2020));
+    }
+
+    #[test]
+    fn test_relative_weeks() {
+        // 2019-01-01 is in the week 1 of 2019
+        let result = interpret("next week 3", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 11)));
+        assert_eq!(result.context.week, Some(3));
+        assert_eq!(result.context.year, Some(2019));
+
+        let result = interpret("next week 1", false, fixed_time()).unwrap();
+        assert_eq!(result.context.year, Some(2020));
+
+        let result = interpret("last week 3", false, fixed_time()).unwrap();
+        assert_eq!(result.context.year, Some(2018));
+
+        let result = interpret("this week 3", false, fixed_time()).unwrap();
+        assert_eq!(result.context.year, Some(2019));
+
+        // 2015 has 53 weeks, 2019 doesn't
+        let result = interpret("last week 53", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("last week 53", "week", 53).extract_error()
+        );
+    }
+
+    #[test]
+    fn test_week_code() {
+        let result = interpret("cw17", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 4)));
+        assert_eq!(result.context.week, Some(17));
+
+        let result = interpret("due w42 2020.", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(4, 13)));
+        assert_eq!(result.context.week, Some(42));
+        assert_eq!(result.context.year, Some(2020));
+
+        let result = interpret("w42-3", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 5)));
+        assert_eq!(result.context.week, Some(42));
+        assert_eq!(result.context.weekday, Some(Weekday::Wed));
+        assert_eq!(result.context.span, None);
+    }
+
+    #[test]
+    fn test_wrong_weeks() {
+        let result = interpret("week 53", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("week 53", "week", 53).extract_error()
+        );
+
+        let result = interpret("w53 2020", false, fixed_time()).unwrap();
+        assert_eq!(result.context.week, Some(53));
+
+        let result = interpret("w12-8", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("w12-8", "weekday", 8).extract_error()
+        );
+    }
+}
@@ -1,37 +1,62 @@
+mod business_days;
 mod casual_date_time;
+mod day_of_year;
 mod deadline;
 mod exact_month_date;
+mod holidays;
+mod iso_week;
+mod offset;
 mod past_time;
+mod quarter;
 mod time;
+mod time_of_day;
 mod weekdays;
 
-use crate::rules::combinators;
+use crate::rule::Rule;
+use crate::rules::combinators::{self, BuiltinRule};
 use crate::rules::common;
 use crate::rules::common_matchers;
 use crate::rules::consts;
 use crate::rules::errors;
 use crate::rules::rules;
+use crate::rules::session;
 use crate::rules::tokens;
 use chrono::{offset::TimeZone, DateTime};
 
-pub fn parse<'a, Tz: TimeZone + 'a>(
+/// Built-in english rules
+pub(crate) fn rules<'a, Tz: TimeZone + 'a>() -> Vec<Box<dyn Rule<Tz> + 'a>> {
+    let rules: Vec<(&'static str, rules::FnRule<Tz>)> = vec![
+        ("en::weekdays", weekdays::interpret::<Tz>),
+        ("en::time", time::interpret::<Tz>),
+        ("en::past_time", past_time::interpret::<Tz>),
+        ("en::exact_month_date", exact_month_date::interpret::<Tz>),
+        ("en::deadline", deadline::interpret::<Tz>),
+        ("en::casual_date_time", casual_date_time::interpret::<Tz>),
+        ("en::time_of_day", time_of_day::interpret::<Tz>),
+        ("common::slash_dmy", common::slash_dmy::interpret::<Tz>),
+        ("en::iso_week", iso_week::interpret::<Tz>),
+        ("en::day_of_year", day_of_year::interpret::<Tz>),
+        ("en::quarter", quarter::interpret::<Tz>),
+        ("en::holidays", holidays::interpret::<Tz>),
+        ("en::business_days", business_days::interpret::<Tz>),
+        ("en::offset", offset::interpret::<Tz>),
+    ];
+
+    rules
+        .into_iter()
+        .map(|(name, interpret)| {
+            Box::new(BuiltinRule { name, interpret }) as Box<dyn Rule<Tz> + 'a>
+        })
+        .collect()
+}
+
+pub fn parse<Tz: TimeZone>(
     tz_aware: DateTime<Tz>,
-    input: &'a str,
+    input: &str,
     exact_match: bool,
 ) -> Vec<Result<rules::MatchResult, errors::DateTimeError>> {
     let input_lowered = input.to_lowercase();
-    combinators::apply_generic(
-        tz_aware,
-        &input_lowered,
-        &[
-            weekdays::interpret::<Tz>,
-            time::interpret::<Tz>,
-            past_time::interpret::<Tz>,
-            exact_month_date::interpret::<Tz>,
-            deadline::interpret::<Tz>,
-            casual_date_time::interpret::<Tz>,
-            common::slash_dmy::interpret::<Tz>,
-        ],
-        exact_match,
-    )
+    let rules = rules();
+    let rules: Vec<_> = rules.iter().map(|rule| (rule.as_ref(), 0)).collect();
+    combinators::apply_generic(tz_aware, &input_lowered, &rules, exact_match)
 }
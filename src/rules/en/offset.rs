@@ -0,0 +1,214 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_num;
+use super::consts;
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, Offset, RuleResult, TokenDesc};
+use super::tokens::{Articles, IntWord, Prepositions, Priority, TimeInterval, Token, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, terminated, tuple, types::CompleteStr};
+
+define!(in_: (Token::When(When::In), Priority(4)), "in", Dist(0));
+
+define!(
+    article:
+    [(Token::Articles(Articles::A), Priority(0)), "a", Dist(0)] |
+    [(Token::Articles(Articles::An), Priority(0)), "an", Dist(0)]
+);
+
+define!(one: (Token::IntWord(IntWord::One), Priority(0)), "one", Dist(0));
+define!(two: (Token::IntWord(IntWord::Two), Priority(0)), "two", Dist(0));
+define!(three: (Token::IntWord(IntWord::Three), Priority(0)), "three", Dist(1));
+define!(four: (Token::IntWord(IntWord::Four), Priority(0)), "four", Dist(1));
+define!(five: (Token::IntWord(IntWord::Five), Priority(0)), "five", Dist(1));
+define!(six: (Token::IntWord(IntWord::Six), Priority(0)), "six", Dist(0));
+define!(seven: (Token::IntWord(IntWord::Seven), Priority(0)), "seven", Dist(1));
+define!(eight: (Token::IntWord(IntWord::Eight), Priority(0)), "eight", Dist(1));
+define!(nine: (Token::IntWord(IntWord::Nine), Priority(0)), "nine", Dist(1));
+define!(ten: (Token::IntWord(IntWord::Ten), Priority(0)), "ten", Dist(0));
+define!(eleven: (Token::IntWord(IntWord::Eleven), Priority(0)), "eleven", Dist(1));
+define!(twelve: (Token::IntWord(IntWord::Twelve), Priority(0)), "twelve", Dist(1));
+
+combine!(int_word => one | two | three | four | five | six | seven | eight | nine | ten
+                         | eleven | twelve);
+
+define_num!(number: (Token::Number, Priority(0)));
+
+define!(
+    minutes:
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(1)), "minutes", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(1)), "minute", Dist(1)]
+);
+
+define!(
+    hours:
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(1)), "hours", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(1)), "hour", Dist(1)]
+);
+
+define!(
+    days:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(1)), "days", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(1)), "day", Dist(0)]
+);
+
+define!(
+    weeks:
+    [(Token::TimeInterval(TimeInterval::Week), Priority(1)), "weeks", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(1)), "week", Dist(1)]
+);
+
+define!(
+    months:
+    [(Token::TimeInterval(TimeInterval::Month), Priority(1)), "months", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(1)), "month", Dist(1)]
+);
+
+define!(
+    years:
+    [(Token::TimeInterval(TimeInterval::Year), Priority(1)), "years", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(1)), "year", Dist(1)]
+);
+
+combine!(time_interval => minutes | hours | days | weeks | months | years);
+
+define!(
+    relation:
+    [(Token::Prepositions(Prepositions::After), Priority(2)), "after", Dist(1)] |
+    [(Token::Prepositions(Prepositions::After), Priority(2)), "from", Dist(0)] |
+    [(Token::Prepositions(Prepositions::Before), Priority(2)), "before", Dist(1)]
+);
+
+define!(prior: (Token::Prepositions(Prepositions::Before), Priority(2)), "prior", Dist(1));
+define!(to: (Token::Prepositions(Prepositions::Before), Priority(3)), "to", Dist(0));
+
+define!(the: (Token::Articles(Articles::The), Priority(3)), "the", Dist(0));
+
+// e.g. "after", "before the", "prior to"
+named_args!(relation_to<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(
+        alt!(terminated!(apply!(prior, exact_match), apply!(to, true)) |
+             apply!(relation, exact_match)),
+        opt!(apply!(the, true))
+    )
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // e.g. "3 days after", "two weeks before", "in a month from"
+            tuple!(alt!(apply!(in_, true) | stub),
+                   alt!(number | apply!(int_word, exact_match) | apply!(article, true)),
+                   apply!(time_interval, exact_match), apply!(relation_to, exact_match)) |
+            // e.g. "day after", "week before the"
+            tuple!(stub, stub, apply!(time_interval, exact_match),
+                   apply!(relation_to, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 4);
+
+/// Offset on its own is not a date, combinators attach it to the date expression which
+/// follows it, e.g. "tomorrow" in "the day after tomorrow"
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let num = match res.token_by_priority(Priority(0)) {
+        Some(Token::Articles(_)) | None => 1,
+        token => match_num(token).unwrap(),
+    };
+
+    if num < 0 {
+        return Err(invalid_time_error(input, "number", num));
+    }
+
+    let num = match res.token_by_priority(Priority(2)) {
+        Some(Token::Prepositions(Prepositions::Before)) => -num,
+        _ => num,
+    };
+
+    let mut offset = Offset::default();
+
+    let overflow = || invalid_time_error(input, "number", num);
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::TimeInterval(TimeInterval::Minute)) => {
+            let seconds = num.checked_mul(consts::MINUTE).ok_or_else(overflow)?;
+            offset.duration = time::Duration::seconds(i64::from(seconds));
+        }
+        Some(Token::TimeInterval(TimeInterval::Hour)) => {
+            let seconds = num.checked_mul(consts::HOUR).ok_or_else(overflow)?;
+            offset.duration = time::Duration::seconds(i64::from(seconds));
+        }
+        Some(Token::TimeInterval(TimeInterval::Day)) => {
+            offset.duration = time::Duration::days(i64::from(num));
+        }
+        Some(Token::TimeInterval(TimeInterval::Week)) => {
+            offset.duration = time::Duration::weeks(i64::from(num));
+        }
+        Some(Token::TimeInterval(TimeInterval::Month)) => offset.months = num,
+        Some(Token::TimeInterval(TimeInterval::Year)) => {
+            offset.months = num.checked_mul(12).ok_or_else(overflow)?;
+        }
+        _ => unreachable!(),
+    }
+
+    ctx.offset = Some(offset);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::{MatchBounds, Offset};
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_offsets() {
+        let result = interpret("3 days after christmas", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 12)));
+        assert_eq!(
+            result.context.offset,
+            Some(Offset {
+                duration: time::Duration::days(3),
+                months: 0
+            })
+        );
+
+        let result = interpret("the day before yesterday", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(4, 14)));
+        assert_eq!(
+            result.context.offset.unwrap().duration,
+            time::Duration::days(-1)
+        );
+
+        let result = interpret("two weeks prior to the launch", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 22)));
+        assert_eq!(
+            result.context.offset.unwrap().duration,
+            time::Duration::weeks(-2)
+        );
+
+        let result = interpret("a year from now", false, fixed_time()).unwrap();
+        assert_eq!(result.context.offset.unwrap().months, 12);
+
+        let result = interpret("99999999 minutes after christmas", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("99999999 minutes after", "number", 99999999).extract_error()
+        );
+    }
+}
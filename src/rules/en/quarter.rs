@@ -0,0 +1,246 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_ordinal;
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, Period, RuleResult, Span, TokenDesc};
+use super::tokens::{
+    Articles, Boundary, Ordinals, Prepositions, Priority, TimeInterval, Token, When,
+};
+use chrono::prelude::*;
+
+use nom::{
+    alt, apply, call, many_till, named, named_args, opt, pair, preceded, terminated, tuple,
+    types::CompleteStr,
+};
+
+define!(
+    boundary:
+    [(Token::Boundary(Boundary::End), Priority(0)), "end", Dist(0)] |
+    [(Token::Boundary(Boundary::Start), Priority(0)), "start", Dist(1)] |
+    [(Token::Boundary(Boundary::Start), Priority(0)), "beginning", Dist(2)]
+);
+
+define!(of: (Token::Prepositions(Prepositions::Of), Priority(6)), "of", Dist(0));
+define!(the: (Token::Articles(Articles::The), Priority(6)), "the", Dist(0));
+
+// e.g. "end of", "beginning of the"
+named_args!(boundary_of<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(boundary, exact_match), pair!(apply!(of, true), opt!(apply!(the, true))))
+);
+
+define_prefixed_num!(quarter_code: (Token::Number, Priority(1)), "q");
+define_prefixed_num!(half_code: (Token::Number, Priority(2)), "h");
+
+named!(period_code<CompleteStr, TokenDesc>, alt!(quarter_code | half_code));
+
+define!(
+    first:
+    [(Token::Ordinals(Ordinals::First), Priority(1)), "first", Dist(1)] |
+    [(Token::Ordinals(Ordinals::First), Priority(1)), "1st", Dist(0)]
+);
+define!(
+    second:
+    [(Token::Ordinals(Ordinals::Second), Priority(1)), "second", Dist(1)] |
+    [(Token::Ordinals(Ordinals::Second), Priority(1)), "2nd", Dist(0)]
+);
+define!(
+    third:
+    [(Token::Ordinals(Ordinals::Third), Priority(1)), "third", Dist(1)] |
+    [(Token::Ordinals(Ordinals::Third), Priority(1)), "3rd", Dist(0)]
+);
+define!(
+    fourth:
+    [(Token::Ordinals(Ordinals::Fourth), Priority(1)), "fourth", Dist(1)] |
+    [(Token::Ordinals(Ordinals::Fourth), Priority(1)), "4th", Dist(0)]
+);
+
+combine!(ordinal => first | second | third | fourth);
+
+define!(this: (Token::When(When::This), Priority(3)), "this", Dist(1));
+define!(last: (Token::When(When::Last), Priority(3)), "last", Dist(1));
+define!(past: (Token::When(When::Past), Priority(3)), "past", Dist(1));
+define!(next: (Token::When(When::Next), Priority(3)), "next", Dist(1));
+
+combine!(when => this | last | past | next);
+
+define!(
+    quarter:
+    [(Token::TimeInterval(TimeInterval::Quarter), Priority(5)), "quarter", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Quarter), Priority(5)), "qtr", Dist(0)]
+);
+define!(half: (Token::TimeInterval(TimeInterval::HalfYear), Priority(5)), "half", Dist(0));
+
+combine!(period_noun => quarter | half);
+
+define_prefixed_num!(fiscal_year: (Token::Number, Priority(4)), "fy");
+define_year!(year: (Token::Number, Priority(4)));
+
+// e.g. "2019", "fy19", "of fy2019"
+named_args!(of_year<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    preceded!(opt!(apply!(of, exact_match)), alt!(fiscal_year | year))
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // e.g. "q3", "h2 2019", "end of q4 fy19"
+            tuple!(alt!(apply!(boundary_of, exact_match) | stub), period_code, stub,
+                   alt!(apply!(of_year, true) | stub)) |
+            // e.g. "third quarter", "second half of 2019", "end of the first quarter"
+            tuple!(alt!(apply!(boundary_of, exact_match) | stub), apply!(ordinal, exact_match),
+                   apply!(period_noun, exact_match), alt!(apply!(of_year, true) | stub)) |
+            // e.g. "next quarter", "end of last quarter"
+            tuple!(alt!(apply!(boundary_of, exact_match) | stub), apply!(when, exact_match),
+                   apply!(period_noun, exact_match), stub) |
+            // e.g. "end of the quarter"
+            tuple!(apply!(boundary_of, exact_match), apply!(period_noun, exact_match), stub, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 4);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let is_half = match res.token_by_priority(Priority(5)) {
+        Some(Token::TimeInterval(TimeInterval::HalfYear)) => true,
+        _ => res.token_by_priority(Priority(2)).is_some(),
+    };
+
+    let index = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => Some(n),
+        token => match_ordinal(token),
+    };
+
+    let index = match res.token_by_priority(Priority(2)) {
+        Some(Token::Number(n)) => Some(n),
+        _ => index,
+    };
+
+    ctx.period = Some(match index {
+        Some(n) if is_half && !(1..=2).contains(&n) => {
+            return Err(invalid_time_error(input, "half", n));
+        }
+        Some(n) if !is_half && !(1..=4).contains(&n) => {
+            return Err(invalid_time_error(input, "quarter", n));
+        }
+        Some(n) if is_half => Period::Half(n),
+        Some(n) => Period::Quarter(n),
+        None => {
+            let shift = match res.token_by_priority(Priority(3)) {
+                Some(Token::When(When::Next)) => 1,
+                Some(Token::When(When::Last)) | Some(Token::When(When::Past)) => -1,
+                _ => 0,
+            };
+            if is_half {
+                Period::HalfShift(shift)
+            } else {
+                Period::QuarterShift(shift)
+            }
+        }
+    });
+
+    // fiscal years are often written with two digits, e.g. "fy19"
+    if let Some(Token::Number(n)) = res.token_by_priority(Priority(4)) {
+        if n >= 10000 {
+            return Err(invalid_time_error(input, "year", n));
+        }
+        ctx.year = Some(if n < 100 { 2000 + n } else { n });
+    }
+
+    if let Some(Token::Boundary(Boundary::End)) = res.token_by_priority(Priority(0)) {
+        ctx.period_end = true;
+        ctx.span = Some(Span::Days(1));
+    } else {
+        ctx.span = Some(Span::Months(if is_half { 6 } else { 3 }));
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::{MatchBounds, Period, Span};
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_quarter_codes() {
+        let result = interpret("revenue for q3 was great", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(12, 14)));
+        assert_eq!(result.context.period, Some(Period::Quarter(3)));
+        assert_eq!(result.context.year, None);
+        assert_eq!(result.context.span, Some(Span::Months(3)));
+
+        let result = interpret("q1 2020", false, fixed_time()).unwrap();
+        assert_eq!(result.context.period, Some(Period::Quarter(1)));
+        assert_eq!(result.context.year, Some(2020));
+
+        let result = interpret("targets for q4 fy19.", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(12, 20)));
+        assert_eq!(result.context.period, Some(Period::Quarter(4)));
+        assert_eq!(result.context.year, Some(2019));
+
+        let result = interpret("h2", false, fixed_time()).unwrap();
+        assert_eq!(result.context.period, Some(Period::Half(2)));
+        assert_eq!(result.context.span, Some(Span::Months(6)));
+
+        let result = interpret("q5", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("q5", "quarter", 5).extract_error()
+        );
+
+        let result = interpret("q4 fy99999999", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("q4 fy99999999", "year", 99999999).extract_error()
+        );
+    }
+
+    #[test]
+    fn test_quarter_words() {
+        let result = interpret("see you next quarter", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(8, 20)));
+        assert_eq!(result.context.period, Some(Period::QuarterShift(1)));
+
+        let result = interpret("last qurter", false, fixed_time()).unwrap();
+        assert_eq!(result.context.period, Some(Period::QuarterShift(-1)));
+
+        let result = interpret("the second half of 2020", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(4, 23)));
+        assert_eq!(result.context.period, Some(Period::Half(2)));
+        assert_eq!(result.context.year, Some(2020));
+
+        let result = interpret("third quarter", false, fixed_time()).unwrap();
+        assert_eq!(result.context.period, Some(Period::Quarter(3)));
+    }
+
+    #[test]
+    fn test_end_of_quarter() {
+        let result = interpret("by the end of q2", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(7, 16)));
+        assert_eq!(result.context.period, Some(Period::Quarter(2)));
+        assert!(result.context.period_end);
+        assert_eq!(result.context.span, Some(Span::Days(1)));
+
+        let result = interpret("end of the quarter", false, fixed_time()).unwrap();
+        assert_eq!(result.context.period, Some(Period::QuarterShift(0)));
+        assert!(result.context.period_end);
+
+        let result = interpret("beginning of next quarter", false, fixed_time()).unwrap();
+        assert_eq!(result.context.period, Some(Period::QuarterShift(1)));
+        assert!(!result.context.period_end);
+    }
+}
@@ -0,0 +1,72 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_time_of_day;
+use super::errors::SemanticError;
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, TimeOfDay, Token};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+// bare parts of the day live in their own rule, words like "morning" are often used in chats
+// without referring to any particular time, so this rule may be turned off separately
+
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(0)), "night", Dist(1));
+define!(morning: (Token::TimeOfDay(TimeOfDay::Morning), Priority(0)), "morning", Dist(2));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(0)), "evening", Dist(2));
+define!(noon: (Token::TimeOfDay(TimeOfDay::Noon), Priority(0)), "noon", Dist(1));
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(0)), "afternoon", Dist(2));
+
+combine!(time_of_day => night | morning | evening | noon | afternoon);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // night, morning, evening, etc
+            tuple!(apply!(time_of_day, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    _input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    if let Some(hour) = match_time_of_day(res.token_by_priority(Priority(0))) {
+        ctx.hour = Some(hour);
+        ctx.minute = Some(0);
+        ctx.part_of_day = true;
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        let result = interpret("see you in the evning", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(15, 21)));
+        assert_eq!(result.get_hours(), 18);
+        assert_eq!(result.get_minutes(), 0);
+
+        let result = interpret("good morning", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 8);
+
+        let result = interpret("at noon", true, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 12);
+    }
+}
@@ -3,10 +3,21 @@ use super::errors::{ambiguous_time_error, SemanticError};
 use super::rules::{Context, RuleResult, TokenDesc};
 use super::tokens::{Priority, Token, Weekday as Day, When};
 use chrono::prelude::*;
+use num_traits::FromPrimitive;
 use time::Duration;
 
 use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
 
+const WEEKDAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
 // days of week have biggest priority
 
 define!(
@@ -117,6 +128,9 @@ fn make_time<'a, 'b, Tz: TimeZone>(
         _ => (),
     });
 
+    // stated weekday, parser checks it against the date it is merged with
+    ctx.weekday = Weekday::from_i64(day);
+
     let mut token = res.token_by_priority(Priority(1));
 
     if token.is_none() {
@@ -147,8 +161,11 @@ fn make_time<'a, 'b, Tz: TimeZone>(
                     ctx.set_duration(Duration::days(day - weekday).num_seconds());
                 } else {
                     // what did user mean? previous week day or this week day or next
-                    // week day? we don't know!
-                    return Err(ambiguous_time_error(input));
+                    // week day? we don't know! suggest both spelled properly
+                    let name = WEEKDAY_NAMES[day as usize];
+                    return Err(ambiguous_time_error(input)
+                        .with_suggestions(vec![format!("next {}", name), format!("last {}", name)])
+                        .with_stated(ctx));
                 }
             }
             _ => (),
@@ -215,10 +232,19 @@ mod tests {
     #[test]
     fn test_this() {
         let result = interpret("drop me a line at this monday", false, fixed_time());
+        let err = result.unwrap_err();
         assert_eq!(
-            result.unwrap_err().extract_error(),
+            err.extract_error(),
             ambiguous_time_error("this monday").extract_error()
         );
+        assert_eq!(err.get_suggestions(), ["next monday", "last monday"]);
+
+        // suggestions are spelled properly
+        let result = interpret("this mondy", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().get_suggestions(),
+            ["next monday", "last monday"]
+        );
 
         let result = interpret("this friday", false, fixed_time()).unwrap();
         assert_eq!(result.bounds, Some(MatchBounds::new(0, 11)));
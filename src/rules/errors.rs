@@ -1,7 +1,9 @@
-use super::rules::MatchBounds;
+use super::rules::{Context, MatchBounds};
+use crate::rule::words;
 use failure::Fail;
 use nom::types::CompleteStr;
 use serde::Serialize;
+use std::fmt;
 
 pub(crate) const UNKNOWN: u32 = 1; // couldn't recognize token
 
@@ -17,6 +19,129 @@ pub enum DateTimeError {
     },
     #[fail(display = "can't parse, rules intersection detected in {}", text)]
     IntersectionError { text: String },
+    #[fail(display = "can't parse, conflicting {} in {}", what, text)]
+    ConflictError { text: String, what: String },
+}
+
+impl DateTimeError {
+    /// Stable machine readable kind of the error, unlike messages codes never change
+    pub fn code(&self) -> &'static str {
+        match self {
+            DateTimeError::AmbiguousTime { .. } => "ambiguous_time",
+            DateTimeError::InvalidTime { .. } => "invalid_time",
+            DateTimeError::IntersectionError { .. } => "intersection",
+            DateTimeError::ConflictError { .. } => "conflict",
+        }
+    }
+}
+
+/// Error along with the part of the input it was found in, lets user interfaces point at the
+/// offending text, see "Parser::parse_matches"
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Diagnostic {
+    pub error: DateTimeError,
+    // byte offsets in the input, the end is exclusive
+    pub start_idx: usize,
+    pub end_idx: usize,
+    // the offending part of the match, e.g. "30th" in "30th of february"
+    pub token: String,
+    // valid expressions close to the erroneous one, e.g. "28th of february"
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Diagnostic of the error found in input[start_idx..end_idx], the offending token is the
+    /// word which holds an invalid value or the whole match
+    pub(crate) fn new(error: DateTimeError, input: &str, start_idx: usize, end_idx: usize) -> Self {
+        let text = input.get(start_idx..end_idx).unwrap_or("");
+        let token = match &error {
+            DateTimeError::InvalidTime { value, .. } => {
+                find_number(text, *value).and_then(|(start, end)| {
+                    words(text)
+                        .into_iter()
+                        .find(|word| word.start_idx <= start && word.end_idx >= end)
+                        .map(|word| word.text.to_owned())
+                })
+            }
+            _ => None,
+        };
+        Diagnostic {
+            token: token.unwrap_or_else(|| text.to_owned()),
+            error,
+            start_idx,
+            end_idx,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Diagnostic of an error which lost its position, e.g. reported by a language function,
+    /// the erroneous text is looked up in the input
+    pub(crate) fn locate(error: DateTimeError, input: &str) -> Self {
+        let text = match &error {
+            DateTimeError::AmbiguousTime { msg } => msg,
+            DateTimeError::InvalidTime { text, .. }
+            | DateTimeError::IntersectionError { text }
+            | DateTimeError::ConflictError { text, .. } => text,
+        };
+        let (start_idx, end_idx) = match input.find(text.as_str()) {
+            Some(start_idx) => (start_idx, start_idx + text.len()),
+            None => (0, 0),
+        };
+        Diagnostic::new(error, input, start_idx, end_idx)
+    }
+
+    pub(crate) fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    /// See "DateTimeError::code"
+    pub fn code(&self) -> &'static str {
+        self.error.code()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if !self.suggestions.is_empty() {
+            write!(f, ", did you mean {}?", self.suggestions.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+
+fn ordinal_suffix(n: i32) -> &'static str {
+    match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// Finds the number in the text, numbers may be parts of words, e.g. "30" in "30th", returns
+/// its byte range
+fn find_number(text: &str, value: i32) -> Option<(usize, usize)> {
+    let value = value.abs().to_string();
+    let mut start = None;
+    for (idx, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (c.is_ascii_digit(), start) {
+            (true, None) => start = Some(idx),
+            (false, Some(from)) => {
+                if text[from..idx] == value {
+                    return Some((from, idx));
+                }
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    None
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -27,9 +152,74 @@ pub struct SemanticError<'a> {
 
     // user visible error info
     error: DateTimeError,
+    suggestions: Vec<String>,
+
+    #[serde(skip)]
+    stated: Option<Box<Context>>,
 }
 
 impl<'a> SemanticError<'a> {
+    pub fn new(error: DateTimeError, bounds: MatchBounds) -> Self {
+        SemanticError {
+            bounds,
+            tail: "",
+            error,
+            suggestions: Vec::new(),
+            stated: None,
+        }
+    }
+
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    /// What the erroneous match states anyway, e.g. the weekday of "tuesday" said on friday is
+    /// ambiguous on its own, but parser merges it with an explicit date next to it, e.g.
+    /// "tuesday, march 5"
+    pub fn with_stated(mut self, stated: Context) -> Self {
+        self.stated = Some(Box::new(stated));
+        self
+    }
+
+    /// Suggests to replace the invalid value with the nearest valid one, e.g. "28th of february"
+    /// for "30th of february"
+    pub fn with_nearest(mut self, nearest: i32) -> Self {
+        if let DateTimeError::InvalidTime { text, value, .. } = &self.error {
+            if let Some((start, end)) = find_number(text, *value) {
+                // english ordinal suffixes depend on the number, e.g. "31st" -> "28th"
+                let (suffix, rest) = match text.get(end..end + 2) {
+                    Some("st") | Some("nd") | Some("rd") | Some("th") => {
+                        (ordinal_suffix(nearest), &text[end + 2..])
+                    }
+                    _ => ("", &text[end..]),
+                };
+                let suggestion = format!("{}{}{}{}", &text[..start], nearest, suffix, rest);
+                self.suggestions.push(suggestion);
+            }
+        }
+        self
+    }
+
+    pub fn get_suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+
+    pub fn get_stated(&self) -> Option<Context> {
+        self.stated.as_deref().copied()
+    }
+
+    /// Unboxes the error keeping its position in the input
+    pub fn to_diagnostic(&self, input: &str) -> Diagnostic {
+        Diagnostic::new(
+            self.error.clone(),
+            input,
+            self.bounds.start_idx,
+            self.bounds.end_idx,
+        )
+        .with_suggestions(self.suggestions.clone())
+    }
+
     pub fn extract_error(&self) -> DateTimeError {
         self.error.clone()
     }
@@ -59,6 +249,8 @@ pub fn ambiguous_time_error(msg: &str) -> SemanticError {
     SemanticError {
         bounds: MatchBounds::new(0, 0),
         tail: "",
+        suggestions: Vec::new(),
+        stated: None,
         error: DateTimeError::AmbiguousTime {
             msg: msg.to_owned(),
         },
@@ -69,6 +261,8 @@ pub fn invalid_time_error<'a>(msg: &'a str, what: &'a str, value: i32) -> Semant
     SemanticError {
         bounds: MatchBounds::new(0, 0),
         tail: "",
+        suggestions: Vec::new(),
+        stated: None,
         error: DateTimeError::InvalidTime {
             text: msg.to_owned(),
             what: what.to_owned(),
@@ -81,6 +275,8 @@ pub fn intersection_error(text: &str) -> SemanticError {
     SemanticError {
         bounds: MatchBounds::new(0, 0),
         tail: "",
+        suggestions: Vec::new(),
+        stated: None,
         error: DateTimeError::IntersectionError {
             text: text.to_owned(),
         },
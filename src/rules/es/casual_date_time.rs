@@ -0,0 +1,145 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_time_of_day;
+use super::consts;
+use super::errors::SemanticError;
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, Pronouns, TimeOfDay, Token, When};
+use chrono::prelude::*;
+
+use nom::{
+    alt, apply, call, many_till, named_args, opt, preceded, terminated, tuple, types::CompleteStr,
+};
+
+define!(now: (Token::When(When::Now), Priority(0)), "ahora", Dist(1));
+
+define!(today: (Token::When(When::Today), Priority(1)), "hoy", Dist(0));
+define!(tomorrow: (Token::When(When::Tomorrow), Priority(1)), "mañana", Dist(1));
+define!(yesterday: (Token::When(When::Yesterday), Priority(1)), "ayer", Dist(0));
+define!(
+    day_before_yesterday:
+    [(Token::When(When::DayBeforeYesterday), Priority(1)), "anteayer", Dist(1)] |
+    [(Token::When(When::DayBeforeYesterday), Priority(1)), "antier", Dist(0)]
+);
+
+define!(after: (Token::When(When::DayAfterTomorrow), Priority(1)), "pasado", Dist(1));
+define!(tomorrow_word: (Token::Char, Priority(4)), "mañana", Dist(1));
+
+// e.g. "pasado mañana"
+named_args!(day_after_tomorrow<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(after, exact_match), apply!(tomorrow_word, exact_match))
+);
+
+combine!(when => day_after_tomorrow | today | tomorrow | yesterday | day_before_yesterday);
+
+define!(this: (Token::Pronouns(Pronouns::This), Priority(1)), "esta", Dist(0));
+
+// "mañana" is both tomorrow and morning, e.g. "mañana por la mañana"
+
+define!(morning: (Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "mañana", Dist(1));
+define!(noon: (Token::TimeOfDay(TimeOfDay::Noon), Priority(2)), "mediodía", Dist(1));
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(2)), "tarde", Dist(1));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "noche", Dist(1));
+
+combine!(time_of_day => morning | noon | afternoon | evening);
+
+// prepositions before parts of the day: "por la tarde", "en la noche", "al mediodía"
+
+define!(
+    prep:
+    [(Token::Char, Priority(3)), "por", Dist(0)] |
+    [(Token::Char, Priority(3)), "en", Dist(0)] |
+    [(Token::Char, Priority(3)), "a", Dist(0)] |
+    [(Token::Char, Priority(3)), "al", Dist(0)]
+);
+define!(
+    the:
+    [(Token::Char, Priority(3)), "la", Dist(0)] |
+    [(Token::Char, Priority(3)), "el", Dist(0)]
+);
+
+named_args!(part_of_day<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    preceded!(opt!(tuple!(apply!(prep, true), opt!(apply!(the, true)))),
+              apply!(time_of_day, exact_match))
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // mañana por la mañana, hoy en la noche, ayer al mediodía, etc.
+            tuple!(apply!(when, exact_match), apply!(part_of_day, exact_match)) |
+            // esta tarde, esta noche
+            tuple!(apply!(this, true), apply!(time_of_day, exact_match)) |
+            // hoy, mañana, pasado mañana, etc.
+            tuple!(apply!(when, exact_match), stub) |
+            // ahora
+            tuple!(apply!(now, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    _input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::When(When::Yesterday)) => ctx.set_duration(-consts::DAY),
+        Some(Token::When(When::Tomorrow)) => ctx.set_duration(consts::DAY),
+        Some(Token::When(When::DayAfterTomorrow)) => ctx.set_duration(2 * consts::DAY),
+        Some(Token::When(When::DayBeforeYesterday)) => ctx.set_duration(-2 * consts::DAY),
+        _ => (),
+    }
+
+    if let Some(hour) = match_time_of_day(res.token_by_priority(Priority(2))) {
+        ctx.hour = Some(hour);
+        ctx.minute = Some(0);
+        ctx.part_of_day = true;
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_casual_date() {
+        let result = interpret("llámame mañana por la tarde", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(9, 29)));
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("mañana por la mañana", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 8);
+
+        let result = interpret("pasado mañana", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * consts::DAY as i64);
+
+        let result = interpret("fue anteayer", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -2 * consts::DAY as i64);
+
+        let result = interpret("ayer al mediodia", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -consts::DAY as i64);
+        assert_eq!(result.get_hours(), 12);
+
+        let result = interpret("esta noche", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 18);
+
+        let result = interpret("ahora mismo", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 5)));
+    }
+}
@@ -0,0 +1,157 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::deadline as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Adverbs, IntWord, Priority, TimeInterval, Token, When};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, terminated, tuple, types::CompleteStr};
+
+define!(in_: (Token::When(When::In), Priority(0)), "en", Dist(0));
+define!(inside: (Token::When(When::Within), Priority(0)), "dentro", Dist(1));
+define!(of: (Token::Char, Priority(3)), "de", Dist(0));
+
+// e.g. "dentro de"
+named_args!(within<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(inside, exact_match), apply!(of, true))
+);
+
+define!(
+    one:
+    [(Token::IntWord(IntWord::One), Priority(1)), "un", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "uno", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "una", Dist(0)]
+);
+define!(two: (Token::IntWord(IntWord::Two), Priority(1)), "dos", Dist(0));
+define!(three: (Token::IntWord(IntWord::Three), Priority(1)), "tres", Dist(0));
+define!(four: (Token::IntWord(IntWord::Four), Priority(1)), "cuatro", Dist(1));
+define!(five: (Token::IntWord(IntWord::Five), Priority(1)), "cinco", Dist(1));
+define!(six: (Token::IntWord(IntWord::Six), Priority(1)), "seis", Dist(0));
+define!(seven: (Token::IntWord(IntWord::Seven), Priority(1)), "siete", Dist(1));
+define!(eight: (Token::IntWord(IntWord::Eight), Priority(1)), "ocho", Dist(0));
+define!(nine: (Token::IntWord(IntWord::Nine), Priority(1)), "nueve", Dist(1));
+define!(ten: (Token::IntWord(IntWord::Ten), Priority(1)), "diez", Dist(0));
+define!(eleven: (Token::IntWord(IntWord::Eleven), Priority(1)), "once", Dist(0));
+define!(twelve: (Token::IntWord(IntWord::Twelve), Priority(1)), "doce", Dist(0));
+
+combine!(int_word => one | two | three | four | five | six | seven | eight | nine | ten
+                         | eleven | twelve);
+
+define_num!(number: (Token::Number, Priority(1)));
+
+define!(
+    few:
+    [(Token::Adverbs(Adverbs::Few), Priority(1)), "unos", Dist(0)] |
+    [(Token::Adverbs(Adverbs::Few), Priority(1)), "unas", Dist(0)] |
+    [(Token::Adverbs(Adverbs::Few), Priority(1)), "algunos", Dist(1)] |
+    [(Token::Adverbs(Adverbs::Few), Priority(1)), "algunas", Dist(1)]
+);
+
+define!(half: (Token::Adverbs(Adverbs::Half), Priority(1)), "media", Dist(0));
+define!(hour: (Token::Char, Priority(2)), "hora", Dist(0));
+
+// e.g. "media hora"
+named_args!(half_hour<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(half, exact_match), apply!(hour, exact_match))
+);
+
+define!(
+    seconds:
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "segundo", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "segundos", Dist(1)]
+);
+define!(
+    minutes:
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "minuto", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "minutos", Dist(1)]
+);
+define!(
+    hours:
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "hora", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "horas", Dist(1)]
+);
+define!(
+    days:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "día", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "días", Dist(1)] |
+    // spelling without the accent is common in informal text
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "dia", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "dias", Dist(0)]
+);
+define!(
+    weeks:
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "semana", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "semanas", Dist(1)]
+);
+define!(
+    months:
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "mes", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "meses", Dist(1)]
+);
+define!(
+    years:
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "año", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "años", Dist(1)]
+);
+
+combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // en 5 minutos, dentro de 2 semanas
+            tuple!(alt!(apply!(in_, true) | apply!(within, exact_match)), number,
+                   apply!(time_interval, exact_match)) |
+            // en tres días, dentro de unos minutos
+            tuple!(alt!(apply!(in_, true) | apply!(within, exact_match)),
+                   alt!(apply!(int_word, exact_match) | apply!(few, exact_match)),
+                   apply!(time_interval, exact_match)) |
+            // en media hora
+            tuple!(alt!(apply!(in_, true) | apply!(within, exact_match)),
+                   apply!(half_hour, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_deadline() {
+        let result = interpret("listo en tres días", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(6, 19)));
+        assert_eq!(result.get_duration_sec(), 3 * consts::DAY as i64);
+
+        let result = interpret("en 5 minutos", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 5 * consts::MINUTE as i64);
+
+        let result = interpret("dentro de dos horas", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * consts::HOUR as i64);
+
+        let result = interpret("en media hora", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 30 * consts::MINUTE as i64);
+
+        let result = interpret("en unos dias", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 3 * consts::DAY as i64);
+
+        let result = interpret("en un mes", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 31 * consts::DAY as i64);
+
+        let result = interpret("en -3 minutos", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("en -3 minutos", "number", -3).extract_error()
+        );
+    }
+}
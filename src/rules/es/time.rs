@@ -0,0 +1,174 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, TimeOfDay, Token, When};
+use chrono::prelude::*;
+use nom::{
+    alt, apply, call, many_till, named_args, opt, preceded, terminated, tuple, types::CompleteStr,
+};
+
+define!(
+    at_:
+    [(Token::When(When::In), Priority(0)), "a", Dist(0)] |
+    [(Token::When(When::In), Priority(0)), "al", Dist(0)]
+);
+define!(
+    the:
+    [(Token::Char, Priority(6)), "las", Dist(0)] |
+    [(Token::Char, Priority(6)), "la", Dist(0)]
+);
+
+// e.g. "a las", "a la"
+named_args!(at<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(at_, true), opt!(apply!(the, true)))
+);
+
+// bare hours need the article, "a 5" alone is more likely a distance or an amount
+named_args!(at_the<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(at_, true), apply!(the, true))
+);
+
+define_num!(hours: (Token::Number, Priority(1)));
+
+define_char!(colon: Priority(2), ':');
+
+define_num!(minutes: (Token::Number, Priority(3)));
+
+define!(
+    hour_word:
+    [(Token::Char, Priority(4)), "hora", Dist(0)] |
+    [(Token::Char, Priority(4)), "horas", Dist(1)]
+);
+
+define!(
+    noon_midnight:
+    [(Token::Number(12), Priority(1)), "mediodía", Dist(1)] |
+    [(Token::Number(0), Priority(1)), "medianoche", Dist(2)]
+);
+
+// parts of the day, e.g. "a las 3 de la tarde", "a las 8 de la mañana"
+
+define!(of: (Token::Char, Priority(6)), "de", Dist(0));
+
+define!(morning: (Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "mañana", Dist(1));
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(5)), "tarde", Dist(1));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(5)), "noche", Dist(1));
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(5)), "madrugada", Dist(2));
+
+combine!(time_of_day => morning | afternoon | evening | night);
+
+named_args!(day_part<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    preceded!(tuple!(apply!(of, true), apply!(the, true)), apply!(time_of_day, exact_match))
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // hours:minutes, e.g. "a las 14:30", "a las 9:15 de la noche", plain "14:30" is
+            // handled by common rules
+            tuple!(apply!(at, true), hours, colon, minutes, stub,
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            tuple!(stub, hours, colon, minutes, stub, apply!(day_part, exact_match)) |
+            // e.g. "a las 5", "a las 15 horas", "a la 1 de la tarde"
+            tuple!(apply!(at_the, true), hours, stub, stub,
+                   alt!(apply!(hour_word, exact_match) | stub),
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // e.g. "3 de la tarde"
+            tuple!(stub, hours, stub, stub, stub, apply!(day_part, exact_match)) |
+            // e.g. "al mediodía", "a medianoche"
+            tuple!(apply!(at, true), apply!(noon_midnight, exact_match), stub, stub, stub, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 6);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let mut hrs = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    let minutes = match res.token_by_priority(Priority(3)) {
+        Some(Token::Number(n)) => n,
+        _ => 0,
+    };
+
+    if !(0..=23).contains(&hrs) {
+        return Err(invalid_time_error(input, "hours", hrs));
+    }
+    if !(0..=59).contains(&minutes) {
+        return Err(invalid_time_error(input, "minutes", minutes));
+    }
+
+    // 24-hour clock is used by default, parts of the day only matter for hours below 12
+    match res.token_by_priority(Priority(5)) {
+        Some(Token::TimeOfDay(TimeOfDay::Afternoon)) if hrs < 12 => hrs += 12,
+        // "12 de la noche" is midnight, but "2 de la noche" is 2:00
+        Some(Token::TimeOfDay(TimeOfDay::Evening)) if hrs == 12 => hrs = 0,
+        Some(Token::TimeOfDay(TimeOfDay::Evening)) if (6..12).contains(&hrs) => hrs += 12,
+        _ => (),
+    }
+
+    ctx.hour = Some(hrs);
+    ctx.minute = Some(minutes);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_time() {
+        let result = interpret("reunión a las 14:30", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(9, 20)));
+        assert_eq!(result.get_hours(), 14);
+        assert_eq!(result.get_minutes(), 30);
+
+        let result = interpret("a las 3 de la tarde", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("a la 1", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 1);
+
+        let result = interpret("a las 15 horas", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("10 de la noche", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 22);
+
+        let result = interpret("a las 2 de la madrugada", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 2);
+
+        let result = interpret("al mediodia", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 12);
+
+        let result = interpret("a 5 kilómetros", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+
+    #[test]
+    fn test_wrong_times() {
+        let result = interpret("a las 25", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("a las 25", "hours", 25).extract_error()
+        );
+    }
+}
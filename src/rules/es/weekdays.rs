@@ -0,0 +1,95 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::weekday as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Priority, Token, Weekday as Day, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+define!(monday: (Token::Weekday(Day::Monday), Priority(0)), "lunes", Dist(1));
+define!(tuesday: (Token::Weekday(Day::Tuesday), Priority(0)), "martes", Dist(1));
+define!(wednesday: (Token::Weekday(Day::Wednesday), Priority(0)), "miércoles", Dist(2));
+define!(thursday: (Token::Weekday(Day::Thursday), Priority(0)), "jueves", Dist(1));
+define!(friday: (Token::Weekday(Day::Friday), Priority(0)), "viernes", Dist(1));
+define!(saturday: (Token::Weekday(Day::Saturday), Priority(0)), "sábado", Dist(1));
+define!(sunday: (Token::Weekday(Day::Sunday), Priority(0)), "domingo", Dist(1));
+
+combine!(day_of_week => monday | tuesday | wednesday | thursday | friday | saturday | sunday);
+
+define!(
+    this:
+    [(Token::When(When::This), Priority(1)), "este", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "esta", Dist(0)]
+);
+
+// "próximo" and "pasado" may go before or after the day, e.g. "el próximo martes",
+// "el martes pasado"
+
+define!(next: (Token::When(When::Next), Priority(1)), "próximo", Dist(1));
+define!(last: (Token::When(When::Last), Priority(1)), "pasado", Dist(1));
+
+combine!(when => next | last);
+
+define!(that: (Token::When(When::Next), Priority(1)), "que", Dist(0));
+define!(comes: (Token::When(When::Next), Priority(1)), "viene", Dist(1));
+
+// e.g. "que viene"
+named_args!(coming<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    preceded!(apply!(that, true), apply!(comes, exact_match))
+);
+
+define!(the: (Token::Char, Priority(2)), "el", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // when and then any day of week, e.g. "el próximo martes", "este viernes"
+            tuple!(preceded!(opt!(apply!(the, true)),
+                             alt!(apply!(when, exact_match) | apply!(this, true))),
+                   apply!(day_of_week, exact_match)) |
+            // day of week and then when, e.g. "el martes pasado", "el lunes que viene"
+            tuple!(preceded!(opt!(apply!(the, true)), apply!(day_of_week, exact_match)),
+                   alt!(apply!(when, exact_match) | apply!(coming, exact_match))) |
+            // day of week, e.g. "el viernes"
+            tuple!(preceded!(opt!(apply!(the, true)), apply!(day_of_week, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::ambiguous_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_weekdays() {
+        let result = interpret("hasta el próximo martes", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(6, 24)));
+        assert_eq!(result.get_duration_sec(), 7 * 86400);
+
+        let result = interpret("el viernes pasado", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -4 * 86400);
+
+        let result = interpret("el sabado que viene", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 4 * 86400);
+
+        let result = interpret("este miercoles", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 86400);
+
+        let result = interpret("el lunes", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            ambiguous_time_error("el lunes").extract_error()
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_time_of_day;
+use super::consts;
+use super::errors::SemanticError;
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, Pronouns, TimeOfDay, Token, When};
+use chrono::prelude::*;
+
+use nom::{
+    alt, apply, call, many_till, named_args, opt, preceded, terminated, tuple, types::CompleteStr,
+};
+
+define!(now: (Token::When(When::Now), Priority(0)), "maintenant", Dist(2));
+
+// apostrophes and dashes split words, so "aujourd'hui" or "après-demain" are parsed by parts
+
+define_char!(dash: Priority(4), '-');
+
+define!(today_: (Token::When(When::Today), Priority(1)), "aujourd", Dist(1));
+define!(hui: (Token::Char, Priority(4)), "hui", Dist(0));
+
+// e.g. "aujourd'hui"
+named_args!(today<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(today_, exact_match), apply!(hui, true))
+);
+
+define!(tomorrow: (Token::When(When::Tomorrow), Priority(1)), "demain", Dist(1));
+define!(yesterday: (Token::When(When::Yesterday), Priority(1)), "hier", Dist(0));
+
+define!(after: (Token::When(When::DayAfterTomorrow), Priority(1)), "après", Dist(1));
+define!(tomorrow_word: (Token::Char, Priority(4)), "demain", Dist(1));
+
+// e.g. "après-demain"
+named_args!(day_after_tomorrow<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(after, exact_match),
+                preceded!(opt!(dash), apply!(tomorrow_word, exact_match)))
+);
+
+define!(before: (Token::When(When::DayBeforeYesterday), Priority(1)), "avant", Dist(1));
+define!(yesterday_word: (Token::Char, Priority(4)), "hier", Dist(0));
+
+// e.g. "avant-hier"
+named_args!(day_before_yesterday<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(before, exact_match), preceded!(opt!(dash), apply!(yesterday_word, true)))
+);
+
+combine!(when => day_after_tomorrow | day_before_yesterday | today | tomorrow | yesterday);
+
+define!(
+    this:
+    [(Token::Pronouns(Pronouns::This), Priority(1)), "ce", Dist(0)] |
+    [(Token::Pronouns(Pronouns::This), Priority(1)), "cet", Dist(0)] |
+    [(Token::Pronouns(Pronouns::This), Priority(1)), "cette", Dist(0)]
+);
+
+define!(morning: (Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "matin", Dist(1));
+define!(noon: (Token::TimeOfDay(TimeOfDay::Noon), Priority(2)), "midi", Dist(0));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "soir", Dist(0));
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(2)), "nuit", Dist(0));
+
+define!(after_: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(2)), "après", Dist(1));
+define!(noon_word: (Token::Char, Priority(4)), "midi", Dist(0));
+
+// e.g. "après-midi"
+named_args!(afternoon<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(after_, exact_match), preceded!(opt!(dash), apply!(noon_word, true)))
+);
+
+combine!(time_of_day => morning | noon | afternoon | evening | night);
+
+define!(at: (Token::Char, Priority(3)), "à", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // demain matin, hier soir, demain à midi, etc.
+            tuple!(apply!(when, exact_match),
+                   preceded!(opt!(apply!(at, true)), apply!(time_of_day, exact_match))) |
+            // ce soir, cet après-midi
+            tuple!(apply!(this, true), apply!(time_of_day, exact_match)) |
+            // aujourd'hui, demain, après-demain, etc.
+            tuple!(apply!(when, exact_match), stub) |
+            // maintenant
+            tuple!(apply!(now, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    _input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::When(When::Yesterday)) => ctx.set_duration(-consts::DAY),
+        Some(Token::When(When::Tomorrow)) => ctx.set_duration(consts::DAY),
+        Some(Token::When(When::DayAfterTomorrow)) => ctx.set_duration(2 * consts::DAY),
+        Some(Token::When(When::DayBeforeYesterday)) => ctx.set_duration(-2 * consts::DAY),
+        _ => (),
+    }
+
+    if let Some(hour) = match_time_of_day(res.token_by_priority(Priority(2))) {
+        ctx.hour = Some(hour);
+        ctx.minute = Some(0);
+        ctx.part_of_day = true;
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_casual_date() {
+        let result = interpret("appelle-moi demain soir", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(12, 23)));
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 18);
+
+        let result = interpret("aujourd'hui", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 11)));
+        assert_eq!(result.get_duration_sec(), 0);
+
+        let result = interpret("après-demain", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * consts::DAY as i64);
+
+        let result = interpret("avant-hier", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -2 * consts::DAY as i64);
+
+        let result = interpret("demain après-midi", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("cette nuit", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 23);
+
+        let result = interpret("hier à midi", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -consts::DAY as i64);
+        assert_eq!(result.get_hours(), 12);
+    }
+}
@@ -0,0 +1,100 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::month_date as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Month, Priority, Token, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+define_num!(day_num: (Token::Number, Priority(0)));
+
+// the first day of a month is written as an ordinal, e.g. "le 1er mai"
+define_suffixed_num!(day_ord: (Token::Number, Priority(0)), "er");
+
+define!(the: (Token::Char, Priority(2)), "le", Dist(0));
+
+define!(january: (Token::Month(Month::January), Priority(1)), "janvier", Dist(1));
+define!(february: (Token::Month(Month::February), Priority(1)), "février", Dist(1));
+define!(march: (Token::Month(Month::March), Priority(1)), "mars", Dist(0));
+define!(april: (Token::Month(Month::April), Priority(1)), "avril", Dist(1));
+define!(may: (Token::Month(Month::May), Priority(1)), "mai", Dist(0));
+define!(june: (Token::Month(Month::June), Priority(1)), "juin", Dist(0));
+define!(july: (Token::Month(Month::July), Priority(1)), "juillet", Dist(1));
+define!(august: (Token::Month(Month::August), Priority(1)), "août", Dist(1));
+define!(september: (Token::Month(Month::September), Priority(1)), "septembre", Dist(1));
+define!(october: (Token::Month(Month::October), Priority(1)), "octobre", Dist(1));
+define!(november: (Token::Month(Month::November), Priority(1)), "novembre", Dist(1));
+define!(december: (Token::Month(Month::December), Priority(1)), "décembre", Dist(1));
+
+combine!(month => january | february | march | april | may | june | july | august | september |
+                  october | november | december);
+
+define!(
+    month_abbr:
+    [(Token::Month(Month::January), Priority(1)), "janv", Dist(0)] |
+    [(Token::Month(Month::February), Priority(1)), "févr", Dist(0)] |
+    [(Token::Month(Month::February), Priority(1)), "fevr", Dist(0)] |
+    [(Token::Month(Month::April), Priority(1)), "avr", Dist(0)] |
+    [(Token::Month(Month::July), Priority(1)), "juil", Dist(0)] |
+    [(Token::Month(Month::September), Priority(1)), "sept", Dist(0)] |
+    [(Token::Month(Month::October), Priority(1)), "oct", Dist(0)] |
+    [(Token::Month(Month::November), Priority(1)), "nov", Dist(0)] |
+    [(Token::Month(Month::December), Priority(1)), "déc", Dist(0)] |
+    [(Token::Month(Month::December), Priority(1)), "dec", Dist(0)]
+);
+
+define!(in_: (Token::When(When::In), Priority(2)), "en", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 5 mars, le 1er mai, 25 déc
+            tuple!(preceded!(opt!(apply!(the, true)), alt!(day_num | day_ord)),
+                   alt!(apply!(month, exact_match) | apply!(month_abbr, true))) |
+            // mars, en janvier
+            tuple!(preceded!(opt!(apply!(in_, true)), apply!(month, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_exact_month() {
+        let result = interpret("on se voit le 14 juillet", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(11, 24)));
+        assert_eq!(result.get_day(), 14);
+        assert_eq!(result.get_month(), 7);
+
+        let result = interpret("le 1er mai", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 1);
+        assert_eq!(result.get_month(), 5);
+
+        let result = interpret("3 aout", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 3);
+        assert_eq!(result.get_month(), 8);
+
+        let result = interpret("en décembre", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 1);
+        assert_eq!(result.get_month(), 12);
+
+        let result = interpret("31 avril", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("31 avril", "day", 31).extract_error()
+        );
+    }
+}
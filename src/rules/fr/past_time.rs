@@ -0,0 +1,140 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::past_time as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Adverbs, IntWord, Priority, TimeInterval, Token};
+use chrono::prelude::*;
+use nom::{
+    alt, apply, call, many_till, named_args, opt, preceded, terminated, tuple, types::CompleteStr,
+};
+
+define!(it: (Token::Ago, Priority(0)), "il", Dist(0));
+define!(
+    there_is:
+    [(Token::Char, Priority(3)), "y", Dist(0)] |
+    [(Token::Char, Priority(3)), "a", Dist(0)]
+);
+
+// e.g. "il y a"
+named_args!(ago<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(it, true), tuple!(apply!(there_is, true), apply!(there_is, true)))
+);
+
+define!(
+    one:
+    [(Token::IntWord(IntWord::One), Priority(1)), "un", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "une", Dist(0)]
+);
+define!(two: (Token::IntWord(IntWord::Two), Priority(1)), "deux", Dist(0));
+define!(three: (Token::IntWord(IntWord::Three), Priority(1)), "trois", Dist(1));
+define!(four: (Token::IntWord(IntWord::Four), Priority(1)), "quatre", Dist(1));
+define!(five: (Token::IntWord(IntWord::Five), Priority(1)), "cinq", Dist(0));
+define!(six: (Token::IntWord(IntWord::Six), Priority(1)), "six", Dist(0));
+define!(seven: (Token::IntWord(IntWord::Seven), Priority(1)), "sept", Dist(0));
+define!(eight: (Token::IntWord(IntWord::Eight), Priority(1)), "huit", Dist(0));
+define!(nine: (Token::IntWord(IntWord::Nine), Priority(1)), "neuf", Dist(0));
+define!(ten: (Token::IntWord(IntWord::Ten), Priority(1)), "dix", Dist(0));
+define!(eleven: (Token::IntWord(IntWord::Eleven), Priority(1)), "onze", Dist(0));
+define!(twelve: (Token::IntWord(IntWord::Twelve), Priority(1)), "douze", Dist(1));
+
+combine!(int_word => one | two | three | four | five | six | seven | eight | nine | ten
+                         | eleven | twelve);
+
+define_num!(number: (Token::Number, Priority(1)));
+
+define!(few: (Token::Adverbs(Adverbs::Few), Priority(1)), "quelques", Dist(2));
+
+define!(half: (Token::Adverbs(Adverbs::Half), Priority(1)), "demi", Dist(0));
+define_char!(dash: Priority(3), '-');
+define!(hour: (Token::Char, Priority(2)), "heure", Dist(1));
+
+// e.g. "une demi-heure"
+named_args!(half_hour<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(preceded!(opt!(apply!(one, true)), apply!(half, true)),
+                preceded!(opt!(dash), apply!(hour, exact_match)))
+);
+
+define!(
+    seconds:
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "seconde", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "secondes", Dist(1)]
+);
+define!(
+    minutes:
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "minute", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "minutes", Dist(1)]
+);
+define!(
+    hours:
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "heure", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "heures", Dist(1)]
+);
+define!(
+    days:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "jour", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "jours", Dist(1)]
+);
+define!(
+    weeks:
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "semaine", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "semaines", Dist(1)]
+);
+define!(months: (Token::TimeInterval(TimeInterval::Month), Priority(2)), "mois", Dist(0));
+define!(
+    years:
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "an", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "ans", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "année", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "années", Dist(1)]
+);
+
+combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // il y a 2 heures
+            tuple!(apply!(ago, true), number, apply!(time_interval, exact_match)) |
+            // il y a deux jours, il y a quelques minutes
+            tuple!(apply!(ago, true),
+                   alt!(apply!(int_word, exact_match) | apply!(few, exact_match)),
+                   apply!(time_interval, exact_match)) |
+            // il y a une demi-heure
+            tuple!(apply!(ago, true), apply!(half_hour, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_past_time() {
+        let result = interpret("c'était il y a deux jours", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(9, 26)));
+        assert_eq!(result.get_duration_sec() as i32, -2 * consts::DAY);
+
+        let result = interpret("il y a 3 heures", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -3 * consts::HOUR);
+
+        let result = interpret("il y a une demi-heure", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -30 * consts::MINUTE);
+
+        let result = interpret("il y a quelques minutes", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -3 * consts::MINUTE);
+
+        let result = interpret("il y a un an", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -365 * consts::DAY);
+    }
+}
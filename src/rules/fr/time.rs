@@ -0,0 +1,183 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, TimeOfDay, Token, When};
+use chrono::prelude::*;
+use nom::{
+    alt, apply, call, many_till, named_args, opt, preceded, terminated, tuple, types::CompleteStr,
+};
+
+define!(at: (Token::When(When::In), Priority(0)), "à", Dist(0));
+
+define_num!(hours: (Token::Number, Priority(1)));
+
+define_char!(colon: Priority(2), ':');
+
+define_num!(minutes: (Token::Number, Priority(3)));
+
+// hours glued to "h" and optional minutes, e.g. "14h", "14h30"
+
+define_hours_h!(hours_h: (Token::Number, Priority(1)));
+define_glued_num!(glued_minutes: (Token::Number, Priority(3)));
+
+define!(
+    hour_word:
+    [(Token::Char, Priority(4)), "heure", Dist(1)] |
+    [(Token::Char, Priority(4)), "heures", Dist(1)]
+);
+
+define!(
+    noon_midnight:
+    [(Token::Number(12), Priority(1)), "midi", Dist(0)] |
+    [(Token::Number(0), Priority(1)), "minuit", Dist(1)]
+);
+
+// parts of the day, e.g. "à 8 heures du matin", "à 3h de l'après-midi"
+
+define!(
+    of:
+    [(Token::Char, Priority(6)), "du", Dist(0)] |
+    [(Token::Char, Priority(6)), "de", Dist(0)]
+);
+define!(
+    the:
+    [(Token::Char, Priority(6)), "l", Dist(0)] |
+    [(Token::Char, Priority(6)), "la", Dist(0)]
+);
+
+define!(morning: (Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "matin", Dist(1));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(5)), "soir", Dist(0));
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(5)), "nuit", Dist(0));
+
+define!(after: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(5)), "après", Dist(1));
+define_char!(dash: Priority(6), '-');
+define!(noon_word: (Token::Char, Priority(6)), "midi", Dist(0));
+
+// e.g. "après-midi"
+named_args!(afternoon<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(after, exact_match), preceded!(opt!(dash), apply!(noon_word, true)))
+);
+
+combine!(time_of_day => morning | afternoon | evening | night);
+
+named_args!(day_part<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    preceded!(tuple!(apply!(of, true), opt!(apply!(the, true))), apply!(time_of_day, exact_match))
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // hours:minutes, e.g. "à 14:30", "à 9:15 du soir", plain "14:30" is handled by
+            // common rules
+            tuple!(apply!(at, true), hours, colon, minutes, stub,
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // hours glued to "h", e.g. "à 14h30", "14h"
+            tuple!(alt!(apply!(at, true) | stub), hours_h, stub, alt!(glued_minutes | stub), stub,
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // e.g. "à 14 heures", "à 8 heures 30 du matin"
+            tuple!(apply!(at, true), hours, stub, apply!(hour_word, exact_match),
+                   alt!(minutes | stub), alt!(apply!(day_part, exact_match) | stub)) |
+            // without "à" the part of the day is required, because "3 heures" alone is more
+            // likely a duration, e.g. "3 heures de l'après-midi"
+            tuple!(stub, hours, stub, apply!(hour_word, exact_match), stub,
+                   apply!(day_part, exact_match)) |
+            // e.g. "à midi", "à minuit"
+            tuple!(apply!(at, true), apply!(noon_midnight, exact_match), stub, stub, stub, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 6);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let mut hrs = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    let minutes = match res.token_by_priority(Priority(3)) {
+        Some(Token::Number(n)) => n,
+        _ => 0,
+    };
+
+    if !(0..=23).contains(&hrs) {
+        return Err(invalid_time_error(input, "hours", hrs));
+    }
+    if !(0..=59).contains(&minutes) {
+        return Err(invalid_time_error(input, "minutes", minutes));
+    }
+
+    // 24-hour clock is used by default, parts of the day only matter for hours below 12
+    match res.token_by_priority(Priority(5)) {
+        Some(Token::TimeOfDay(TimeOfDay::Afternoon))
+        | Some(Token::TimeOfDay(TimeOfDay::Evening))
+            if hrs < 12 =>
+        {
+            hrs += 12
+        }
+        Some(Token::TimeOfDay(TimeOfDay::Night)) if hrs == 12 => hrs = 0,
+        Some(Token::TimeOfDay(TimeOfDay::Night)) if (9..12).contains(&hrs) => hrs += 12,
+        _ => (),
+    }
+
+    ctx.hour = Some(hrs);
+    ctx.minute = Some(minutes);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_time() {
+        let result = interpret("rendez-vous à 14h", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(12, 18)));
+        assert_eq!(result.get_hours(), 14);
+        assert_eq!(result.get_minutes(), 0);
+
+        let result = interpret("à 9h45", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 9);
+        assert_eq!(result.get_minutes(), 45);
+
+        let result = interpret("à 8 heures 30 du matin", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 8);
+        assert_eq!(result.get_minutes(), 30);
+
+        let result = interpret("3 heures de l'après-midi", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 15);
+
+        let result = interpret("à 10h du soir", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 22);
+
+        let result = interpret("à minuit", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 0);
+
+        let result = interpret("pendant 3 heures", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+
+    #[test]
+    fn test_wrong_times() {
+        let result = interpret("à 14h75", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("à 14h75", "minutes", 75).extract_error()
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::weekday as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Priority, Token, Weekday as Day, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+define!(monday: (Token::Weekday(Day::Monday), Priority(0)), "lundi", Dist(1));
+define!(tuesday: (Token::Weekday(Day::Tuesday), Priority(0)), "mardi", Dist(1));
+define!(wednesday: (Token::Weekday(Day::Wednesday), Priority(0)), "mercredi", Dist(2));
+define!(thursday: (Token::Weekday(Day::Thursday), Priority(0)), "jeudi", Dist(1));
+define!(friday: (Token::Weekday(Day::Friday), Priority(0)), "vendredi", Dist(2));
+define!(saturday: (Token::Weekday(Day::Saturday), Priority(0)), "samedi", Dist(1));
+define!(sunday: (Token::Weekday(Day::Sunday), Priority(0)), "dimanche", Dist(2));
+
+combine!(day_of_week => monday | tuesday | wednesday | thursday | friday | saturday | sunday);
+
+define!(this: (Token::When(When::This), Priority(1)), "ce", Dist(0));
+
+// adjectives follow the day, e.g. "mardi prochain", "vendredi dernier"
+
+define!(
+    next:
+    [(Token::When(When::Next), Priority(1)), "prochain", Dist(1)] |
+    [(Token::When(When::Next), Priority(1)), "suivant", Dist(1)]
+);
+
+define!(
+    last:
+    [(Token::When(When::Last), Priority(1)), "dernier", Dist(1)] |
+    [(Token::When(When::Last), Priority(1)), "passé", Dist(1)]
+);
+
+combine!(when => next | last);
+
+define!(the: (Token::Char, Priority(2)), "le", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // day of week and then when, e.g. "mardi prochain", "le vendredi dernier"
+            tuple!(preceded!(opt!(apply!(the, true)), apply!(day_of_week, exact_match)),
+                   apply!(when, exact_match)) |
+            // e.g. "ce mardi"
+            tuple!(apply!(this, true), apply!(day_of_week, exact_match)) |
+            // day of week, e.g. "vendredi"
+            tuple!(preceded!(opt!(apply!(the, true)), apply!(day_of_week, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::ambiguous_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_weekdays() {
+        let result = interpret("à mardi prochain", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(3, 17)));
+        assert_eq!(result.get_duration_sec(), 7 * 86400);
+
+        let result = interpret("le vendredi dernier", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -4 * 86400);
+
+        let result = interpret("ce samedi", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 4 * 86400);
+
+        let result = interpret("mercredi", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 86400);
+
+        let result = interpret("lundi", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            ambiguous_time_error("lundi").extract_error()
+        );
+    }
+}
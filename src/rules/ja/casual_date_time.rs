@@ -0,0 +1,143 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_time_of_day;
+use super::consts;
+use super::errors::SemanticError;
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, TimeOfDay, Token, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+define!(now: (Token::When(When::Now), Priority(0)), "今すぐ", Dist(0));
+
+define!(
+    today:
+    [(Token::When(When::Today), Priority(1)), "今日", Dist(0)] |
+    [(Token::When(When::Today), Priority(1)), "きょう", Dist(0)]
+);
+define!(
+    tomorrow:
+    [(Token::When(When::Tomorrow), Priority(1)), "明日", Dist(0)] |
+    [(Token::When(When::Tomorrow), Priority(1)), "あした", Dist(0)]
+);
+define!(
+    yesterday:
+    [(Token::When(When::Yesterday), Priority(1)), "昨日", Dist(0)] |
+    [(Token::When(When::Yesterday), Priority(1)), "きのう", Dist(0)]
+);
+define!(
+    day_after_tomorrow:
+    [(Token::When(When::DayAfterTomorrow), Priority(1)), "明後日", Dist(0)] |
+    [(Token::When(When::DayAfterTomorrow), Priority(1)), "あさって", Dist(0)]
+);
+define!(
+    day_before_yesterday:
+    [(Token::When(When::DayBeforeYesterday), Priority(1)), "一昨日", Dist(0)] |
+    [(Token::When(When::DayBeforeYesterday), Priority(1)), "おととい", Dist(0)]
+);
+
+combine!(when => today | tomorrow | yesterday | day_after_tomorrow | day_before_yesterday);
+
+define!(
+    morning:
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "朝", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "午前", Dist(0)]
+);
+define!(noon: (Token::TimeOfDay(TimeOfDay::Noon), Priority(2)), "昼", Dist(0));
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(2)), "午後", Dist(0));
+define!(
+    evening:
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "夕方", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "夜", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "晩", Dist(0)]
+);
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(2)), "深夜", Dist(0));
+
+combine!(time_of_day => morning | noon | afternoon | evening | night);
+
+// "this morning" and "tonight" are single words, they refer to today
+define!(
+    this_time_of_day:
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "今朝", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "今夜", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "今晩", Dist(0)]
+);
+
+define!(of: (Token::Char, Priority(3)), "の", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 明日の朝, 昨日の夜, 明日午後, etc.
+            tuple!(apply!(when, exact_match),
+                   preceded!(opt!(apply!(of, true)), apply!(time_of_day, exact_match))) |
+            // 今日, 明日, 明後日, etc.
+            tuple!(apply!(when, exact_match), stub) |
+            // 今朝, 今夜
+            tuple!(apply!(this_time_of_day, exact_match), stub) |
+            // 今すぐ
+            tuple!(apply!(now, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    _input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::When(When::Yesterday)) => ctx.set_duration(-consts::DAY),
+        Some(Token::When(When::Tomorrow)) => ctx.set_duration(consts::DAY),
+        Some(Token::When(When::DayAfterTomorrow)) => ctx.set_duration(2 * consts::DAY),
+        Some(Token::When(When::DayBeforeYesterday)) => ctx.set_duration(-2 * consts::DAY),
+        _ => (),
+    }
+
+    if let Some(hour) = match_time_of_day(res.token_by_priority(Priority(2))) {
+        ctx.hour = Some(hour);
+        ctx.minute = Some(0);
+        ctx.part_of_day = true;
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_casual_date() {
+        let result = interpret("明日の朝に会いましょう", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 12)));
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 8);
+
+        let result = interpret("明後日", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * consts::DAY as i64);
+
+        let result = interpret("おととい", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -2 * consts::DAY as i64);
+
+        let result = interpret("昨日の夜", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -consts::DAY as i64);
+        assert_eq!(result.get_hours(), 18);
+
+        let result = interpret("今夜", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 0);
+        assert_eq!(result.get_hours(), 18);
+    }
+}
@@ -0,0 +1,100 @@
+use super::combinators::{tokenize_count_symbols, Dist};
+use super::common::interpreters::deadline as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Adverbs, Priority, TimeInterval, Token, When};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define_cjk_num!(number: (Token::Number, Priority(1)));
+
+define!(few: (Token::Adverbs(Adverbs::Few), Priority(1)), "数", Dist(0));
+
+define!(seconds: (Token::TimeInterval(TimeInterval::Second), Priority(2)), "秒", Dist(0));
+define!(
+    minutes:
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "分間", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "分", Dist(0)]
+);
+define!(hours: (Token::TimeInterval(TimeInterval::Hour), Priority(2)), "時間", Dist(0));
+define!(
+    days:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "日間", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "日", Dist(0)]
+);
+define!(weeks: (Token::TimeInterval(TimeInterval::Week), Priority(2)), "週間", Dist(0));
+define!(
+    months:
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "か月", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "ヶ月", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "ケ月", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "カ月", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "ヵ月", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "箇月", Dist(0)]
+);
+define!(
+    years:
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "年間", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "年", Dist(0)]
+);
+
+combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
+
+define!(after: (Token::When(When::In), Priority(0)), "後", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 3日後, 2時間後, 数分後
+            tuple!(alt!(number | apply!(few, exact_match)), apply!(time_interval, exact_match),
+                   apply!(after, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_deadline() {
+        let result = interpret("3日後に出発", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 7)));
+        assert_eq!(result.get_duration_sec(), 3 * consts::DAY as i64);
+
+        let result = interpret("2時間後", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * consts::HOUR as i64);
+
+        let result = interpret("十五分後", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 15 * consts::MINUTE as i64);
+
+        let result = interpret("数日後", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 3 * consts::DAY as i64);
+
+        let result = interpret("1週間後", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), consts::WEEK as i64);
+
+        let result = interpret("一か月後", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 31 * consts::DAY as i64);
+
+        let result = interpret("3日間", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+
+        let result = interpret("1000000時間後", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("1000000時間後", "number", 1000000).extract_error()
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::{is_leap_year, DAYS_IN_MONTH};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, Span, TokenDesc};
+use super::tokens::{Era, Priority, Token};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define_cjk_num!(day: (Token::Number, Priority(0)));
+define_cjk_num!(month: (Token::Number, Priority(1)));
+define_cjk_num!(year: (Token::Number, Priority(2)));
+
+// the first year of an era is "元年"
+define!(first_year: (Token::Number(1), Priority(2)), "元", Dist(0));
+
+define!(year_word: (Token::Char, Priority(3)), "年", Dist(0));
+define!(month_word: (Token::Char, Priority(4)), "月", Dist(0));
+define!(day_word: (Token::Char, Priority(5)), "日", Dist(0));
+
+define!(
+    era:
+    [(Token::Era(Era::Meiji), Priority(6)), "明治", Dist(0)] |
+    [(Token::Era(Era::Taisho), Priority(6)), "大正", Dist(0)] |
+    [(Token::Era(Era::Showa), Priority(6)), "昭和", Dist(0)] |
+    [(Token::Era(Era::Heisei), Priority(6)), "平成", Dist(0)] |
+    [(Token::Era(Era::Reiwa), Priority(6)), "令和", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc,
+                               TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // e.g. "令和5年4月1日", "平成元年"
+            tuple!(apply!(era, exact_match), alt!(year | apply!(first_year, exact_match)),
+                   apply!(year_word, exact_match), month, apply!(month_word, exact_match),
+                   day, apply!(day_word, exact_match)) |
+            tuple!(apply!(era, exact_match), alt!(year | apply!(first_year, exact_match)),
+                   apply!(year_word, exact_match), month, apply!(month_word, exact_match),
+                   stub, stub) |
+            tuple!(apply!(era, exact_match), alt!(year | apply!(first_year, exact_match)),
+                   apply!(year_word, exact_match), stub, stub, stub, stub) |
+            // e.g. "2019年3月5日", "2019年3月"
+            tuple!(stub, year, apply!(year_word, exact_match), month, apply!(month_word, exact_match),
+                   day, apply!(day_word, exact_match)) |
+            tuple!(stub, year, apply!(year_word, exact_match), month, apply!(month_word, exact_match),
+                   stub, stub) |
+            // e.g. "3月5日", "三月"
+            tuple!(stub, stub, stub, month, apply!(month_word, exact_match), day,
+                   apply!(day_word, exact_match)) |
+            tuple!(stub, stub, stub, month, apply!(month_word, exact_match), stub, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 7);
+
+/// Gregorian year the first year of an era corresponds to
+fn era_start(era: Era) -> i32 {
+    match era {
+        Era::Meiji => 1868,
+        Era::Taisho => 1912,
+        Era::Showa => 1926,
+        Era::Heisei => 1989,
+        Era::Reiwa => 2019,
+    }
+}
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let year = match res.token_by_priority(Priority(2)) {
+        Some(Token::Number(n)) => Some(n),
+        _ => None,
+    };
+
+    let year = match res.token_by_priority(Priority(6)) {
+        Some(Token::Era(era)) => match year {
+            Some(n) if n > 0 => Some(era_start(era) + n - 1),
+            Some(n) => return Err(invalid_time_error(input, "year", n)),
+            None => unreachable!(),
+        },
+        _ => year,
+    };
+
+    // era year alone, e.g. "令和5年", is the whole year
+    let month = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => {
+            ctx.year = year;
+            ctx.month = Some(1);
+            ctx.day = Some(1);
+            ctx.span = Some(Span::Months(12));
+            return Ok(ctx);
+        }
+    };
+
+    // if day is omitted, assume it is 1st day of a month
+    let day = match res.token_by_priority(Priority(0)) {
+        Some(Token::Number(n)) => n,
+        _ => 1,
+    };
+
+    if !(1..=12).contains(&month) {
+        return Err(invalid_time_error(input, "month", month));
+    }
+
+    // 29 days in february for leap years
+    let days_in_month = if month == 2 && is_leap_year(year.unwrap_or_else(|| tz_aware.year())) {
+        29
+    } else {
+        DAYS_IN_MONTH[month as usize - 1]
+    };
+
+    if day <= 0 || day > days_in_month {
+        let nearest = day.clamp(1, days_in_month);
+        return Err(invalid_time_error(input, "day", day).with_nearest(nearest));
+    }
+
+    ctx.year = year;
+    ctx.day = Some(day);
+    ctx.month = Some(month);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_exact_month_date() {
+        let result = interpret("締め切りは3月5日です", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(15, 23)));
+        assert_eq!(result.get_month(), 3);
+        assert_eq!(result.get_day(), 5);
+
+        let result = interpret("2020年2月29日", false, fixed_time()).unwrap();
+        assert_eq!(result.get_year(), 2020);
+        assert_eq!(result.get_month(), 2);
+        assert_eq!(result.get_day(), 29);
+
+        let result = interpret("令和5年4月1日", false, fixed_time()).unwrap();
+        assert_eq!(result.get_year(), 2023);
+        assert_eq!(result.get_month(), 4);
+        assert_eq!(result.get_day(), 1);
+
+        let result = interpret("平成元年", false, fixed_time()).unwrap();
+        assert_eq!(result.get_year(), 1989);
+        assert_eq!(result.get_month(), 1);
+
+        let result = interpret("十二月", false, fixed_time()).unwrap();
+        assert_eq!(result.get_month(), 12);
+        assert_eq!(result.get_day(), 1);
+
+        let result = interpret("3ヶ月", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+
+    #[test]
+    fn test_wrong_dates() {
+        let result = interpret("2月30日", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("2月30日", "day", 30).extract_error()
+        );
+
+        let result = interpret("令和0年", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("令和0年", "year", 0).extract_error()
+        );
+    }
+}
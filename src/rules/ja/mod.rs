@@ -0,0 +1,46 @@
+mod casual_date_time;
+mod deadline;
+mod exact_month_date;
+mod past_time;
+mod time;
+mod weekdays;
+
+use crate::rule::Rule;
+use crate::rules::combinators::{self, BuiltinRule};
+use crate::rules::common;
+use crate::rules::common_matchers;
+use crate::rules::consts;
+use crate::rules::errors;
+use crate::rules::rules;
+use crate::rules::tokens;
+use chrono::{offset::TimeZone, DateTime};
+
+/// Built-in japanese rules
+pub(crate) fn rules<'a, Tz: TimeZone + 'a>() -> Vec<Box<dyn Rule<Tz> + 'a>> {
+    let rules: Vec<(&'static str, rules::FnRule<Tz>)> = vec![
+        ("ja::weekdays", weekdays::interpret::<Tz>),
+        ("ja::time", time::interpret::<Tz>),
+        ("ja::past_time", past_time::interpret::<Tz>),
+        ("ja::exact_month_date", exact_month_date::interpret::<Tz>),
+        ("ja::deadline", deadline::interpret::<Tz>),
+        ("ja::casual_date_time", casual_date_time::interpret::<Tz>),
+    ];
+
+    rules
+        .into_iter()
+        .map(|(name, interpret)| {
+            Box::new(BuiltinRule { name, interpret }) as Box<dyn Rule<Tz> + 'a>
+        })
+        .collect()
+}
+
+pub fn parse<Tz: TimeZone>(
+    tz_aware: DateTime<Tz>,
+    input: &str,
+    exact_match: bool,
+) -> Vec<Result<rules::MatchResult, errors::DateTimeError>> {
+    let input_lowered = input.to_lowercase();
+    let rules = rules();
+    let rules: Vec<_> = rules.iter().map(|rule| (rule.as_ref(), 0)).collect();
+    combinators::apply_generic(tz_aware, &input_lowered, &rules, exact_match)
+}
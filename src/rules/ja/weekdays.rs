@@ -0,0 +1,104 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::week_day as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Priority, Token, Weekday as Day, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, terminated, tuple, types::CompleteStr};
+
+define!(
+    monday:
+    [(Token::Weekday(Day::Monday), Priority(0)), "月曜日", Dist(0)] |
+    [(Token::Weekday(Day::Monday), Priority(0)), "月曜", Dist(0)]
+);
+define!(
+    tuesday:
+    [(Token::Weekday(Day::Tuesday), Priority(0)), "火曜日", Dist(0)] |
+    [(Token::Weekday(Day::Tuesday), Priority(0)), "火曜", Dist(0)]
+);
+define!(
+    wednesday:
+    [(Token::Weekday(Day::Wednesday), Priority(0)), "水曜日", Dist(0)] |
+    [(Token::Weekday(Day::Wednesday), Priority(0)), "水曜", Dist(0)]
+);
+define!(
+    thursday:
+    [(Token::Weekday(Day::Thursday), Priority(0)), "木曜日", Dist(0)] |
+    [(Token::Weekday(Day::Thursday), Priority(0)), "木曜", Dist(0)]
+);
+define!(
+    friday:
+    [(Token::Weekday(Day::Friday), Priority(0)), "金曜日", Dist(0)] |
+    [(Token::Weekday(Day::Friday), Priority(0)), "金曜", Dist(0)]
+);
+define!(
+    saturday:
+    [(Token::Weekday(Day::Saturday), Priority(0)), "土曜日", Dist(0)] |
+    [(Token::Weekday(Day::Saturday), Priority(0)), "土曜", Dist(0)]
+);
+define!(
+    sunday:
+    [(Token::Weekday(Day::Sunday), Priority(0)), "日曜日", Dist(0)] |
+    [(Token::Weekday(Day::Sunday), Priority(0)), "日曜", Dist(0)]
+);
+
+combine!(day_of_week => monday | tuesday | wednesday | thursday | friday | saturday | sunday);
+
+define!(
+    when:
+    [(Token::When(When::Next), Priority(1)), "来週", Dist(0)] |
+    [(Token::When(When::Last), Priority(1)), "先週", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "今週", Dist(0)]
+);
+
+define!(of: (Token::Char, Priority(2)), "の", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 来週の金曜日, 先週月曜
+            tuple!(terminated!(apply!(when, exact_match), opt!(apply!(of, true))),
+                   apply!(day_of_week, exact_match)) |
+            // 金曜日
+            tuple!(apply!(day_of_week, exact_match), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::ambiguous_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_weekdays() {
+        let result = interpret("来週の金曜日に", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 18)));
+        assert_eq!(result.get_duration_sec(), 10 * 86400);
+
+        let result = interpret("先週月曜", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -8 * 86400);
+
+        let result = interpret("今週の日曜日", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 5 * 86400);
+
+        let result = interpret("木曜日", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * 86400);
+
+        let result = interpret("月曜日", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            ambiguous_time_error("月曜日").extract_error()
+        );
+    }
+}
@@ -0,0 +1,394 @@
+use super::combinators::{next_word, skip_word, trim};
+use super::session;
+use super::vocabulary::{self, Vocabulary, MAX_EDITS};
+use crate::languages::Language;
+use nom::types::CompleteStr;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Word of the input which rules recognized only as a misspelled pattern, e.g. "fridy" as
+/// "friday", indices are byte offsets in the input
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Correction {
+    pub start_idx: usize,
+    pub end_idx: usize,
+    pub pattern: String,
+    pub cost: f64,
+}
+
+/// Word of the input along with the tokens it may be recognized as
+struct Node {
+    // byte length of the word
+    len: usize,
+    // normalized text of the word
+    text: String,
+    // patterns of the vocabularies close to the word along with their typos costs, keyed by
+    // the language of the vocabulary, None is the parser's own vocabulary
+    tokens: HashMap<Option<Language>, HashMap<String, f64>>,
+}
+
+/// Words of the input and the candidate tokens of every word, i.e. patterns of the rules close
+/// to it along with their typos costs. The lattice is built once per parse before rules are
+/// applied: rules skip words, read normalized words and look tokens up in it instead of
+/// tokenizing the input and comparing words with patterns themselves.
+pub(crate) struct Lattice {
+    // address right after the last byte of the input, rules are applied to its suffixes
+    end: usize,
+    // byte length of the input
+    len: usize,
+    // words keyed by the length of the rest of the input the word starts
+    nodes: HashMap<usize, Node>,
+    // length of the rest of the input a word starts, keyed by the length of the rest after it
+    starts: HashMap<usize, usize>,
+    // length of the rest of the input after the next word and separators around it, keyed by
+    // the length of the rest before them, see "tokenize_count_symbols"
+    skips: HashMap<usize, usize>,
+    // typos costs of normalized words compared with patterns which are not tokens, e.g.
+    // patterns of several words
+    costs: RefCell<HashMap<String, HashMap<String, f64>>>,
+    // language of the rule being applied, its tokens are used
+    language: Cell<Option<Language>>,
+    // words of the parser's own patterns, e.g. holiday names
+    vocabulary: Arc<Vocabulary>,
+    // the cheapest pattern every word was recognized as, keyed by the word bounds
+    recognized: RefCell<HashMap<(usize, usize), (String, f64)>>,
+}
+
+impl Lattice {
+    /// Tokenizes the input, words are looked up in the vocabularies of built-in rules of the
+    /// languages and in the parser's own one
+    pub fn new(input: &str, languages: &[Language]) -> Self {
+        let session = session::current();
+        let mut nodes = HashMap::new();
+        let mut starts = HashMap::new();
+        let mut skips = HashMap::new();
+        let mut rest = CompleteStr(input);
+
+        let vocabularies: Vec<(Option<Language>, &Vocabulary)> = languages
+            .iter()
+            .map(|&language| (Some(language), vocabulary::builtin(language)))
+            .chain(Some((None, &*session.vocabulary)))
+            .collect();
+
+        while let Some((tail, word)) = next_word(rest) {
+            if let Ok((after, _)) = skip_word(rest) {
+                skips.insert(rest.len(), after.len());
+            }
+            if !word.is_empty() {
+                let start = tail.len() + word.len();
+                if let Ok((after, _)) = skip_word(CompleteStr(&input[input.len() - start..])) {
+                    skips.insert(start, after.len());
+                }
+
+                let text = normalize(*word);
+                let tokens = vocabularies
+                    .iter()
+                    .map(|(language, vocabulary)| {
+                        let close = vocabulary
+                            .candidates(&text)
+                            .into_iter()
+                            .map(|pattern| {
+                                (pattern.to_owned(), session.typo_model.cost(&text, pattern))
+                            })
+                            .collect();
+                        (*language, close)
+                    })
+                    .collect();
+                nodes.insert(
+                    start,
+                    Node {
+                        len: word.len(),
+                        text,
+                        tokens,
+                    },
+                );
+                starts.insert(tail.len(), start);
+            }
+            rest = tail;
+        }
+
+        Lattice {
+            end: input.as_ptr() as usize + input.len(),
+            len: input.len(),
+            nodes,
+            starts,
+            skips,
+            costs: RefCell::new(HashMap::new()),
+            language: Cell::new(None),
+            vocabulary: session.vocabulary.clone(),
+            recognized: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn is_suffix(&self, input: CompleteStr) -> bool {
+        input.as_ptr() as usize + input.len() == self.end
+    }
+
+    /// Returns the normalized first word of the input and the rest of the input, None if
+    /// the input is not a part of the tokenized one
+    pub fn word<'a>(&self, input: CompleteStr<'a>) -> Option<(CompleteStr<'a>, &str)> {
+        if !self.is_suffix(input) {
+            return None;
+        }
+        let (rest, _) = trim(input).ok()?;
+        let node = self.nodes.get(&rest.len())?;
+        Some((CompleteStr(&rest[node.len..]), &node.text))
+    }
+
+    /// Skips the first word of the input along with separators around it, None if the input is
+    /// not a part of the tokenized one
+    pub fn skip<'a>(&self, input: CompleteStr<'a>) -> Option<(CompleteStr<'a>, usize)> {
+        if !self.is_suffix(input) {
+            return None;
+        }
+        let rest = *self.skips.get(&input.len())?;
+        let count = input.len() - rest;
+        Some((CompleteStr(&input[count..]), count))
+    }
+
+    /// Sets the language of the rule which is applied next
+    pub fn set_language(&self, language: Option<Language>) {
+        self.language.set(language);
+    }
+
+    /// Returns typos cost of the word which ends where the tail starts compared with the
+    /// pattern, None if the word is not close to the pattern. Patterns of the vocabularies of
+    /// the current language and of the parser are looked up among the tokens of the word when
+    /// they may be within max_edits of it, other patterns are compared with the word the first
+    /// time a rule tries them.
+    pub fn cost<F: FnOnce() -> f64>(
+        &self,
+        tail: CompleteStr,
+        word: &str,
+        pattern: &str,
+        max_edits: Option<usize>,
+        f: F,
+    ) -> Option<f64> {
+        let node = self
+            .starts
+            .get(&tail.len())
+            .filter(|_| self.is_suffix(tail))
+            .and_then(|start| self.nodes.get(start))
+            .filter(|node| node.text == word);
+        if let (Some(node), Some(max_edits)) = (node, max_edits) {
+            if max_edits <= MAX_EDITS {
+                let language = self.language.get();
+                let vocabularies = [
+                    (language, language.map(vocabulary::builtin)),
+                    (None, Some(&*self.vocabulary)),
+                ];
+                let mut indexed = false;
+                for (key, vocabulary) in vocabularies.iter() {
+                    let tokens = match (vocabulary, node.tokens.get(key)) {
+                        (Some(vocabulary), Some(tokens)) if vocabulary.contains(pattern) => tokens,
+                        _ => continue,
+                    };
+                    if let Some(cost) = tokens.get(pattern) {
+                        return Some(*cost);
+                    }
+                    indexed = true;
+                }
+                // the word is not among the words the index finds close to the pattern
+                if indexed {
+                    return None;
+                }
+            }
+        }
+
+        if let Some(cost) = self
+            .costs
+            .borrow()
+            .get(word)
+            .and_then(|costs| costs.get(pattern))
+        {
+            return Some(*cost);
+        }
+
+        let cost = f();
+        self.costs
+            .borrow_mut()
+            .entry(word.to_owned())
+            .or_default()
+            .insert(pattern.to_owned(), cost);
+        Some(cost)
+    }
+
+    /// Remembers that the word which ends where the tail starts was recognized as the pattern
+    /// with the given typos cost
+    pub fn recognize(&self, tail: CompleteStr, pattern: &str, cost: f64) {
+        if !self.is_suffix(tail) {
+            return;
+        }
+        if let Some(start) = self.starts.get(&tail.len()) {
+            let bounds = (self.len - start, self.len - tail.len());
+            let mut recognized = self.recognized.borrow_mut();
+            let best = recognized
+                .entry(bounds)
+                .or_insert_with(|| (pattern.to_owned(), cost));
+            if cost < best.1 {
+                *best = (pattern.to_owned(), cost);
+            }
+        }
+    }
+
+    /// Words which were recognized only with typos, ordered by their positions
+    pub fn corrections(&self) -> Vec<Correction> {
+        let mut corrections: Vec<Correction> = self
+            .recognized
+            .borrow()
+            .iter()
+            .filter(|(_, (_, cost))| *cost > 0.0)
+            .map(|(&(start_idx, end_idx), (pattern, cost))| Correction {
+                start_idx,
+                end_idx,
+                pattern: pattern.clone(),
+                cost: *cost,
+            })
+            .collect();
+        corrections.sort_by_key(|correction| correction.start_idx);
+        corrections
+    }
+}
+
+/// Dots are dropped, so "a.m." is the same word as "am"
+pub(crate) fn normalize(word: &str) -> String {
+    word.replace(".", "")
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<Lattice>>> = const { RefCell::new(None) };
+}
+
+/// Runs the function with the lattice of the input installed, previous lattice is restored
+/// afterwards even if the function panics
+pub(crate) fn scoped<R, F: FnOnce(&Lattice) -> R>(lattice: Lattice, f: F) -> R {
+    struct Restore(Option<Rc<Lattice>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let lattice = Rc::new(lattice);
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(lattice.clone()))));
+    f(&lattice)
+}
+
+/// Returns currently installed lattice, there is none if rules are applied outside of
+/// "apply_generic", e.g. in tests
+pub(crate) fn current() -> Option<Rc<Lattice>> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Correction, Lattice};
+    use crate::languages::Language;
+    use crate::rules::session::{self, Session};
+    use crate::rules::vocabulary::Vocabulary;
+    use nom::types::CompleteStr;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_lattice() {
+        let input = "meet me at 10 a.m. tomorrow";
+        let lattice = Lattice::new(input, &[Language::English]);
+
+        let (tail, word) = lattice.word(CompleteStr(&input[13..])).unwrap();
+        assert_eq!((*tail, word), (" tomorrow", "am"));
+        let (tail, word) = lattice.word(tail).unwrap();
+        assert_eq!((*tail, word), ("", "tomorrow"));
+
+        let (tail, count) = lattice.skip(CompleteStr(input)).unwrap();
+        assert_eq!((*tail, count), ("me at 10 a.m. tomorrow", 5));
+        let (tail, count) = lattice.skip(CompleteStr(&input[10..])).unwrap();
+        assert_eq!((*tail, count), ("a.m. tomorrow", 4));
+
+        // strings other than the input are not looked up
+        let other = input.to_owned();
+        assert!(lattice.word(CompleteStr(&other[13..])).is_none());
+        assert!(lattice.skip(CompleteStr(&other[13..])).is_none());
+
+        // patterns which are not tokens are compared with the word once
+        let tail = CompleteStr(&input[27..]);
+        let mut calls = 0;
+        for _ in 0..3 {
+            let cost = lattice.cost(tail, "tomorrow", "tomorow", None, || {
+                calls += 1;
+                1.0
+            });
+            assert_eq!(cost, Some(1.0));
+        }
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_tokens() {
+        let input = "see you on fridy";
+        let lattice = Lattice::new(input, &[Language::English]);
+        lattice.set_language(Some(Language::English));
+        let tail = CompleteStr(&input[16..]);
+        let token = |pattern, max_edits| lattice.cost(tail, "fridy", pattern, max_edits, || 9.0);
+
+        assert_eq!(token("friday", Some(1)), Some(1.0));
+        assert_eq!(token("tomorrow", Some(2)), None);
+        // patterns which aren't indexed, too many typos for the index
+        assert_eq!(token("frida", Some(1)), Some(9.0));
+        assert_eq!(token("friday", Some(4)), Some(9.0));
+        assert_eq!(token("friday", None), Some(9.0));
+        // other words which end there, e.g. the empty one at the end of the input
+        assert_eq!(lattice.cost(tail, "", "friday", Some(1), || 9.0), Some(9.0));
+        // words of other languages
+        lattice.set_language(Some(Language::Russian));
+        assert_eq!(token("пятница", Some(1)), Some(9.0));
+    }
+
+    #[test]
+    fn test_parser_vocabulary() {
+        let mut vocabulary = Vocabulary::default();
+        vocabulary.add("sprint");
+        let session = Session {
+            vocabulary: Arc::new(vocabulary),
+            ..Session::new(&[])
+        };
+
+        let input = "after sprnt";
+        let lattice = session::scoped(session, || Lattice::new(input, &[]));
+        lattice.set_language(None);
+        let tail = CompleteStr(&input[11..]);
+        let token =
+            |lattice: &Lattice, pattern| lattice.cost(tail, "sprnt", pattern, Some(1), || 9.0);
+        assert_eq!(token(&lattice, "sprint"), Some(1.0));
+        assert_eq!(token(&lattice, "review"), Some(9.0));
+
+        // parsers don't share their own words
+        let other = Lattice::new(input, &[]);
+        assert_eq!(token(&other, "sprint"), Some(9.0));
+    }
+
+    #[test]
+    fn test_corrections() {
+        let input = "next fridy";
+        let lattice = Lattice::new(input, &[]);
+        lattice.recognize(CompleteStr(&input[4..]), "next", 0.0);
+        lattice.recognize(CompleteStr(&input[10..]), "fri", 2.0);
+        lattice.recognize(CompleteStr(&input[10..]), "friday", 1.0);
+        assert_eq!(
+            lattice.corrections(),
+            vec![Correction {
+                start_idx: 5,
+                end_idx: 10,
+                pattern: "friday".to_owned(),
+                cost: 1.0
+            }]
+        );
+
+        // a word which matches a pattern exactly is not a typo
+        lattice.recognize(CompleteStr(&input[10..]), "fridy", 0.0);
+        assert!(lattice.corrections().is_empty());
+    }
+}
@@ -1,11 +1,23 @@
 #[macro_use]
 pub(crate) mod combinators;
 
+pub(crate) mod chart;
 pub(crate) mod common_matchers;
 pub(crate) mod consts;
 pub(crate) mod errors;
+pub(crate) mod lattice;
 pub(crate) mod rules;
+pub(crate) mod session;
 pub(crate) mod tokens;
+pub(crate) mod trace;
+pub(crate) mod vocabulary;
 
+pub(crate) mod br;
 pub(crate) mod common;
+pub(crate) mod de;
 pub(crate) mod en;
+pub(crate) mod es;
+pub(crate) mod fr;
+pub(crate) mod ja;
+pub(crate) mod ru;
+pub(crate) mod zh;
@@ -0,0 +1,143 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common_matchers::match_time_of_day;
+use super::consts;
+use super::errors::SemanticError;
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, Pronouns, TimeOfDay, Token, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define!(now: (Token::When(When::Now), Priority(0)), "сейчас", Dist(1));
+
+// adjectives are in the instrumental case, e.g. "прошлой ночью", "этим утром"
+
+define!(last: (Token::When(When::Last), Priority(1)), "прошлой", Dist(1));
+define!(
+    this:
+    [(Token::Pronouns(Pronouns::This), Priority(1)), "этим", Dist(0)] |
+    [(Token::Pronouns(Pronouns::This), Priority(1)), "этой", Dist(0)]
+);
+
+combine!(adj => last | this);
+
+define!(today: (Token::When(When::Today), Priority(1)), "сегодня", Dist(1));
+define!(tomorrow: (Token::When(When::Tomorrow), Priority(1)), "завтра", Dist(1));
+define!(yesterday: (Token::When(When::Yesterday), Priority(1)), "вчера", Dist(1));
+define!(
+    day_after_tomorrow: (Token::When(When::DayAfterTomorrow), Priority(1)), "послезавтра", Dist(2)
+);
+define!(
+    day_before_yesterday: (Token::When(When::DayBeforeYesterday), Priority(1)), "позавчера", Dist(2)
+);
+
+combine!(when => today | tomorrow | yesterday | day_after_tomorrow | day_before_yesterday);
+
+define!(
+    night:
+    [(Token::TimeOfDay(TimeOfDay::Night), Priority(2)), "ночью", Dist(1)] |
+    [(Token::TimeOfDay(TimeOfDay::Night), Priority(2)), "ночь", Dist(0)]
+);
+define!(
+    morning:
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "утром", Dist(1)] |
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(2)), "утро", Dist(0)]
+);
+define!(
+    evening:
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "вечером", Dist(1)] |
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(2)), "вечер", Dist(0)]
+);
+define!(noon: (Token::TimeOfDay(TimeOfDay::Noon), Priority(2)), "полдень", Dist(1));
+define!(
+    afternoon:
+    [(Token::TimeOfDay(TimeOfDay::Afternoon), Priority(2)), "днём", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Afternoon), Priority(2)), "днем", Dist(0)]
+);
+
+combine!(time_of_day => night | morning | evening | noon | afternoon);
+
+define!(at: (Token::When(When::In), Priority(3)), "в", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // прошлой ночью, этим утром, etc.
+            tuple!(apply!(adj, exact_match), apply!(time_of_day, exact_match), stub) |
+            // завтра вечером, сегодня утром, завтра в полдень, etc.
+            tuple!(apply!(when, exact_match), alt!(apply!(at, true) | stub),
+                   apply!(time_of_day, exact_match)) |
+            // сегодня, завтра, послезавтра, etc.
+            tuple!(apply!(when, exact_match), stub, stub) |
+            // сейчас
+            tuple!(apply!(now, exact_match), stub, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    _input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    match res.token_by_priority(Priority(1)) {
+        Some(Token::When(When::Last)) | Some(Token::When(When::Yesterday)) => {
+            ctx.set_duration(-consts::DAY);
+        }
+        Some(Token::When(When::Tomorrow)) => ctx.set_duration(consts::DAY),
+        Some(Token::When(When::DayAfterTomorrow)) => ctx.set_duration(2 * consts::DAY),
+        Some(Token::When(When::DayBeforeYesterday)) => ctx.set_duration(-2 * consts::DAY),
+        _ => (),
+    }
+
+    if let Some(hour) = match_time_of_day(res.token_by_priority(Priority(2))) {
+        ctx.hour = Some(hour);
+        ctx.minute = Some(0);
+        ctx.part_of_day = true;
+    }
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_casual_date() {
+        let result = interpret("позвони мне завтра вечером", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(22, 49)));
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 18);
+
+        let result = interpret("послезавтра", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 2 * consts::DAY as i64);
+
+        let result = interpret("это было позавчра", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -2 * consts::DAY as i64);
+
+        let result = interpret("сегодня в полдень", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 0);
+        assert_eq!(result.get_hours(), 12);
+
+        let result = interpret("прошлой ночью", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -consts::DAY as i64);
+        assert_eq!(result.get_hours(), 23);
+
+        let result = interpret("прямо сейчас", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(11, 23)));
+        assert_eq!(result.get_duration_sec(), 0);
+    }
+}
@@ -0,0 +1,162 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::deadline as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Adverbs, IntWord, Priority, TimeInterval, Token, When};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, terminated, tuple, types::CompleteStr};
+
+define!(in_: (Token::When(When::In), Priority(0)), "через", Dist(1));
+define!(during: (Token::When(When::Within), Priority(0)), "в", Dist(0));
+define!(course: (Token::When(When::Within), Priority(0)), "течение", Dist(1));
+
+// e.g. "в течение"
+named_args!(within<'a>(exact_match: bool)<CompleteStr<'a>, TokenDesc>,
+    terminated!(apply!(during, true), apply!(course, exact_match))
+);
+
+define!(
+    one:
+    [(Token::IntWord(IntWord::One), Priority(1)), "один", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "одну", Dist(0)]
+);
+define!(
+    two:
+    [(Token::IntWord(IntWord::Two), Priority(1)), "два", Dist(0)] |
+    [(Token::IntWord(IntWord::Two), Priority(1)), "две", Dist(0)]
+);
+define!(three: (Token::IntWord(IntWord::Three), Priority(1)), "три", Dist(0));
+define!(four: (Token::IntWord(IntWord::Four), Priority(1)), "четыре", Dist(1));
+define!(five: (Token::IntWord(IntWord::Five), Priority(1)), "пять", Dist(0));
+define!(six: (Token::IntWord(IntWord::Six), Priority(1)), "шесть", Dist(1));
+define!(seven: (Token::IntWord(IntWord::Seven), Priority(1)), "семь", Dist(0));
+define!(eight: (Token::IntWord(IntWord::Eight), Priority(1)), "восемь", Dist(1));
+define!(nine: (Token::IntWord(IntWord::Nine), Priority(1)), "девять", Dist(1));
+define!(ten: (Token::IntWord(IntWord::Ten), Priority(1)), "десять", Dist(1));
+define!(eleven: (Token::IntWord(IntWord::Eleven), Priority(1)), "одиннадцать", Dist(2));
+define!(twelve: (Token::IntWord(IntWord::Twelve), Priority(1)), "двенадцать", Dist(2));
+
+combine!(int_word => one | two | three | four | five | six | seven | eight | nine | ten
+                         | eleven | twelve);
+
+define_num!(number: (Token::Number, Priority(1)));
+
+define!(few: (Token::Adverbs(Adverbs::Few), Priority(1)), "несколько", Dist(2));
+define!(half_hour: (Token::Adverbs(Adverbs::Half), Priority(1)), "полчаса", Dist(1));
+
+// "через" takes the accusative case: "через минуту", "через 2 минуты", "через 5 минут",
+// "в течение" takes the genitive case: "в течение минуты", "в течение недели"
+
+define!(
+    seconds:
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "секунду", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "секунды", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "секунд", Dist(1)]
+);
+define!(
+    minutes:
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "минуту", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "минуты", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "минут", Dist(1)]
+);
+define!(
+    hours:
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "час", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "часа", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "часов", Dist(1)]
+);
+define!(
+    days:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "день", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "дня", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "дней", Dist(0)]
+);
+define!(
+    weeks:
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "неделю", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "недели", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "недель", Dist(1)]
+);
+define!(
+    months:
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "месяц", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "месяца", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "месяцев", Dist(1)]
+);
+define!(
+    years:
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "год", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "года", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "лет", Dist(0)]
+);
+
+combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // через 5 минут, в течение 10 дней
+            tuple!(alt!(apply!(in_, exact_match) | apply!(within, exact_match)), number,
+                   apply!(time_interval, exact_match)) |
+            // через пять минут, через несколько дней
+            tuple!(alt!(apply!(in_, exact_match) | apply!(within, exact_match)),
+                   alt!(apply!(int_word, exact_match) | apply!(few, exact_match)),
+                   apply!(time_interval, exact_match)) |
+            // через полчаса
+            tuple!(apply!(in_, exact_match), apply!(half_hour, exact_match), stub) |
+            // через неделю, в течение часа
+            tuple!(alt!(apply!(in_, exact_match) | apply!(within, exact_match)), stub,
+                   apply!(time_interval, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_deadline() {
+        let result = interpret("перезвоню через 5 минут", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(19, 42)));
+        assert_eq!(result.get_duration_sec(), 5 * consts::MINUTE as i64);
+
+        let result = interpret("черз три дня", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 3 * consts::DAY as i64);
+
+        let result = interpret("через полчаса", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 30 * consts::MINUTE as i64);
+
+        let result = interpret("через неделю", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), consts::WEEK as i64);
+
+        let result = interpret("в течение 10 дней", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 10 * consts::DAY as i64);
+
+        let result = interpret("через 2 месяца", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 59 * consts::DAY as i64);
+
+        let result = interpret("через -3 минуты", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("через -3 минуты", "number", -3).extract_error()
+        );
+
+        let result = interpret("через 1000000 часов", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("через 1000000 часов", "number", 1000000).extract_error()
+        );
+    }
+}
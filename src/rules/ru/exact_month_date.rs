@@ -0,0 +1,198 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::{is_leap_year, DAYS_IN_MONTH};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Month, Priority, Token, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+define_num!(day_num: (Token::Number, Priority(0)));
+
+// months in nominative, genitive and prepositional cases, e.g. "январь", "5 января", "в январе"
+
+define!(
+    january:
+    [(Token::Month(Month::January), Priority(1)), "январь", Dist(1)] |
+    [(Token::Month(Month::January), Priority(1)), "января", Dist(1)] |
+    [(Token::Month(Month::January), Priority(1)), "январе", Dist(1)] |
+    [(Token::Month(Month::January), Priority(1)), "янв", Dist(0)]
+);
+define!(
+    february:
+    [(Token::Month(Month::February), Priority(1)), "февраль", Dist(1)] |
+    [(Token::Month(Month::February), Priority(1)), "февраля", Dist(1)] |
+    [(Token::Month(Month::February), Priority(1)), "феврале", Dist(1)] |
+    [(Token::Month(Month::February), Priority(1)), "фев", Dist(0)]
+);
+define!(
+    march:
+    [(Token::Month(Month::March), Priority(1)), "март", Dist(0)] |
+    [(Token::Month(Month::March), Priority(1)), "марта", Dist(1)] |
+    [(Token::Month(Month::March), Priority(1)), "марте", Dist(1)] |
+    [(Token::Month(Month::March), Priority(1)), "мар", Dist(0)]
+);
+define!(
+    april:
+    [(Token::Month(Month::April), Priority(1)), "апрель", Dist(1)] |
+    [(Token::Month(Month::April), Priority(1)), "апреля", Dist(1)] |
+    [(Token::Month(Month::April), Priority(1)), "апреле", Dist(1)] |
+    [(Token::Month(Month::April), Priority(1)), "апр", Dist(0)]
+);
+define!(
+    may:
+    [(Token::Month(Month::May), Priority(1)), "май", Dist(0)] |
+    [(Token::Month(Month::May), Priority(1)), "мая", Dist(0)] |
+    [(Token::Month(Month::May), Priority(1)), "мае", Dist(0)]
+);
+define!(
+    june:
+    [(Token::Month(Month::June), Priority(1)), "июнь", Dist(0)] |
+    [(Token::Month(Month::June), Priority(1)), "июня", Dist(0)] |
+    [(Token::Month(Month::June), Priority(1)), "июне", Dist(0)]
+);
+define!(
+    july:
+    [(Token::Month(Month::July), Priority(1)), "июль", Dist(0)] |
+    [(Token::Month(Month::July), Priority(1)), "июля", Dist(0)] |
+    [(Token::Month(Month::July), Priority(1)), "июле", Dist(0)]
+);
+define!(
+    august:
+    [(Token::Month(Month::August), Priority(1)), "август", Dist(1)] |
+    [(Token::Month(Month::August), Priority(1)), "августа", Dist(1)] |
+    [(Token::Month(Month::August), Priority(1)), "августе", Dist(1)] |
+    [(Token::Month(Month::August), Priority(1)), "авг", Dist(0)]
+);
+define!(
+    september:
+    [(Token::Month(Month::September), Priority(1)), "сентябрь", Dist(1)] |
+    [(Token::Month(Month::September), Priority(1)), "сентября", Dist(1)] |
+    [(Token::Month(Month::September), Priority(1)), "сентябре", Dist(1)] |
+    [(Token::Month(Month::September), Priority(1)), "сент", Dist(0)]
+);
+define!(
+    october:
+    [(Token::Month(Month::October), Priority(1)), "октябрь", Dist(1)] |
+    [(Token::Month(Month::October), Priority(1)), "октября", Dist(1)] |
+    [(Token::Month(Month::October), Priority(1)), "октябре", Dist(1)] |
+    [(Token::Month(Month::October), Priority(1)), "окт", Dist(0)]
+);
+define!(
+    november:
+    [(Token::Month(Month::November), Priority(1)), "ноябрь", Dist(1)] |
+    [(Token::Month(Month::November), Priority(1)), "ноября", Dist(1)] |
+    [(Token::Month(Month::November), Priority(1)), "ноябре", Dist(1)] |
+    [(Token::Month(Month::November), Priority(1)), "нояб", Dist(0)]
+);
+define!(
+    december:
+    [(Token::Month(Month::December), Priority(1)), "декабрь", Dist(1)] |
+    [(Token::Month(Month::December), Priority(1)), "декабря", Dist(1)] |
+    [(Token::Month(Month::December), Priority(1)), "декабре", Dist(1)] |
+    [(Token::Month(Month::December), Priority(1)), "дек", Dist(0)]
+);
+
+combine!(month => january | february | march | april | may | june | july | august | september |
+                  october | november | december);
+
+define!(at: (Token::When(When::In), Priority(2)), "в", Dist(0));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 4 марта
+            tuple!(day_num, apply!(month, exact_match)) |
+            // март, в январе
+            tuple!(preceded!(opt!(apply!(at, true)), apply!(month, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    // if day is omitted, assume it is 1st day of a month
+    let day = match res.token_by_priority(Priority(0)) {
+        Some(Token::Number(n)) => n,
+        _ => 1,
+    };
+
+    if day <= 0 {
+        return Err(invalid_time_error(input, "day", day));
+    }
+
+    let month = match res.token_by_priority(Priority(1)) {
+        Some(Token::Month(Month::January)) => 1,
+        Some(Token::Month(Month::February)) => 2,
+        Some(Token::Month(Month::March)) => 3,
+        Some(Token::Month(Month::April)) => 4,
+        Some(Token::Month(Month::May)) => 5,
+        Some(Token::Month(Month::June)) => 6,
+        Some(Token::Month(Month::July)) => 7,
+        Some(Token::Month(Month::August)) => 8,
+        Some(Token::Month(Month::September)) => 9,
+        Some(Token::Month(Month::October)) => 10,
+        Some(Token::Month(Month::November)) => 11,
+        Some(Token::Month(Month::December)) => 12,
+        _ => unreachable!(),
+    };
+
+    // 29 days in february for leap years
+    let days_in_month = if month == 2 && is_leap_year(tz_aware.year()) {
+        29
+    } else {
+        DAYS_IN_MONTH[month as usize - 1]
+    };
+
+    if day > days_in_month {
+        return Err(invalid_time_error(input, "day", day).with_nearest(days_in_month));
+    }
+
+    ctx.day = Some(day);
+    ctx.month = Some(month);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_exact_month() {
+        let result = interpret("приезжай 8 марта", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(17, 29)));
+        assert_eq!(result.get_day(), 8);
+        assert_eq!(result.get_month(), 3);
+
+        let result = interpret("1 сентебря", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 1);
+        assert_eq!(result.get_month(), 9);
+
+        let result = interpret("в декабре", false, fixed_time()).unwrap();
+        assert_eq!(result.get_day(), 1);
+        assert_eq!(result.get_month(), 12);
+
+        let result = interpret("30 февраля", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("30 февраля", "day", 30).extract_error()
+        );
+    }
+}
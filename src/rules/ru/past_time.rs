@@ -0,0 +1,151 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::past_time as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Adverbs, IntWord, Priority, TimeInterval, Token};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+// numerals in nominative and accusative cases, e.g. "одна минута", "одну минуту", "две недели"
+
+define!(
+    one:
+    [(Token::IntWord(IntWord::One), Priority(1)), "один", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "одну", Dist(0)] |
+    [(Token::IntWord(IntWord::One), Priority(1)), "одна", Dist(0)]
+);
+define!(
+    two:
+    [(Token::IntWord(IntWord::Two), Priority(1)), "два", Dist(0)] |
+    [(Token::IntWord(IntWord::Two), Priority(1)), "две", Dist(0)]
+);
+define!(three: (Token::IntWord(IntWord::Three), Priority(1)), "три", Dist(0));
+define!(four: (Token::IntWord(IntWord::Four), Priority(1)), "четыре", Dist(1));
+define!(five: (Token::IntWord(IntWord::Five), Priority(1)), "пять", Dist(0));
+define!(six: (Token::IntWord(IntWord::Six), Priority(1)), "шесть", Dist(1));
+define!(seven: (Token::IntWord(IntWord::Seven), Priority(1)), "семь", Dist(0));
+define!(eight: (Token::IntWord(IntWord::Eight), Priority(1)), "восемь", Dist(1));
+define!(nine: (Token::IntWord(IntWord::Nine), Priority(1)), "девять", Dist(1));
+define!(ten: (Token::IntWord(IntWord::Ten), Priority(1)), "десять", Dist(1));
+define!(eleven: (Token::IntWord(IntWord::Eleven), Priority(1)), "одиннадцать", Dist(2));
+define!(twelve: (Token::IntWord(IntWord::Twelve), Priority(1)), "двенадцать", Dist(2));
+
+combine!(int_word => one | two | three | four | five | six | seven | eight | nine | ten
+                         | eleven | twelve);
+
+define_num!(number: (Token::Number, Priority(1)));
+
+define!(few: (Token::Adverbs(Adverbs::Few), Priority(1)), "несколько", Dist(2));
+define!(half_hour: (Token::Adverbs(Adverbs::Half), Priority(1)), "полчаса", Dist(1));
+
+// interval nouns agree with numerals: "1 минуту", "2 минуты", "5 минут"
+
+define!(
+    seconds:
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "секунду", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "секунды", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "секунд", Dist(1)]
+);
+define!(
+    minutes:
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "минуту", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "минуты", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Minute), Priority(2)), "минут", Dist(1)]
+);
+define!(
+    hours:
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "час", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "часа", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "часов", Dist(1)]
+);
+define!(
+    days:
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "день", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "дня", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Day), Priority(2)), "дней", Dist(0)]
+);
+define!(
+    weeks:
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "неделю", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "недели", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "недель", Dist(1)]
+);
+define!(
+    months:
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "месяц", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "месяца", Dist(1)] |
+    [(Token::TimeInterval(TimeInterval::Month), Priority(2)), "месяцев", Dist(1)]
+);
+define!(
+    years:
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "год", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "года", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Year), Priority(2)), "лет", Dist(0)]
+);
+
+combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
+
+define!(ago: (Token::Ago, Priority(0)), "назад", Dist(1));
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 2 часа назад, 5 минут назад
+            tuple!(number, apply!(time_interval, exact_match), apply!(ago, exact_match)) |
+            // два дня назад, несколько минут назад
+            tuple!(alt!(apply!(int_word, exact_match) | apply!(few, exact_match)),
+                   apply!(time_interval, exact_match), apply!(ago, exact_match)) |
+            // полчаса назад
+            tuple!(apply!(half_hour, exact_match), stub, apply!(ago, exact_match)) |
+            // час назад, неделю назад
+            tuple!(stub, apply!(time_interval, exact_match), apply!(ago, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_past_time() {
+        let result = interpret("это было 3 часа назад", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(16, 37)));
+        assert_eq!(result.get_duration_sec() as i32, -3 * consts::HOUR);
+
+        let result = interpret("пять минут назад", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -5 * consts::MINUTE);
+
+        let result = interpret("две недели назад", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -2 * consts::WEEK);
+
+        let result = interpret("полчаса назад", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -30 * consts::MINUTE);
+
+        let result = interpret("несколько дней нозад", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -3 * consts::DAY);
+
+        let result = interpret("месяц назад", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -31 * consts::DAY);
+
+        let result = interpret("10 лет назад", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec() as i32, -3652 * consts::DAY);
+
+        let result = interpret("1000000 часов назад", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("1000000 часов назад", "number", 1000000).extract_error()
+        );
+    }
+}
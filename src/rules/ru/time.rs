@@ -0,0 +1,157 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, TimeOfDay, Token, When};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define!(at: (Token::When(When::In), Priority(0)), "в", Dist(0));
+
+define_num!(hours: (Token::Number, Priority(1)));
+
+define_char!(colon: Priority(2), ':');
+
+define_num!(minutes: (Token::Number, Priority(3)));
+
+define!(
+    hour_word:
+    [(Token::Char, Priority(4)), "час", Dist(0)] |
+    [(Token::Char, Priority(4)), "часа", Dist(0)] |
+    [(Token::Char, Priority(4)), "часов", Dist(1)]
+);
+
+// parts of the day in the genitive case, e.g. "в 5 утра", "в 3 часа дня"
+
+define!(morning: (Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "утра", Dist(0));
+define!(evening: (Token::TimeOfDay(TimeOfDay::Evening), Priority(5)), "вечера", Dist(1));
+
+combine!(morning_evening => morning | evening);
+
+define!(afternoon: (Token::TimeOfDay(TimeOfDay::Afternoon), Priority(5)), "дня", Dist(0));
+define!(night: (Token::TimeOfDay(TimeOfDay::Night), Priority(5)), "ночи", Dist(0));
+
+combine!(day_part => morning | evening | afternoon | night);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // hours:minutes and part of the day, e.g. "в 10:30 вечера", "17:45"
+            tuple!(alt!(apply!(at, true) | stub), hours, colon, minutes, stub,
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // e.g. "в 3 часа дня", "в 12 часов"
+            tuple!(apply!(at, true), hours, stub, stub, apply!(hour_word, exact_match),
+                   alt!(apply!(day_part, exact_match) | stub)) |
+            // e.g. "5 часов утра", without "в" the part of the day is required, because
+            // "3 часа" alone is more likely a duration
+            tuple!(stub, hours, stub, stub, apply!(hour_word, exact_match),
+                   apply!(day_part, exact_match)) |
+            // e.g. "в 2 ночи", "в 3 дня"
+            tuple!(apply!(at, true), hours, stub, stub, stub, apply!(day_part, exact_match)) |
+            // "3 дня" and "2 ночи" without "в" are also amounts of days and nights, so only
+            // mornings and evenings are accepted here, e.g. "5 утра", "7 вечера"
+            tuple!(stub, hours, stub, stub, stub, apply!(morning_evening, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 6);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let mut hrs = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    let minutes = match res.token_by_priority(Priority(3)) {
+        Some(Token::Number(n)) => n,
+        _ => 0,
+    };
+
+    if !(0..=23).contains(&hrs) {
+        return Err(invalid_time_error(input, "hours", hrs));
+    }
+    if !(0..=59).contains(&minutes) {
+        return Err(invalid_time_error(input, "minutes", minutes));
+    }
+
+    match res.token_by_priority(Priority(5)) {
+        Some(Token::TimeOfDay(TimeOfDay::Afternoon))
+        | Some(Token::TimeOfDay(TimeOfDay::Evening))
+            if hrs < 12 =>
+        {
+            hrs += 12
+        }
+        // "12 ночи" is midnight, "11 ночи" is 23:00 but "2 ночи" is 2:00
+        Some(Token::TimeOfDay(TimeOfDay::Night)) if hrs == 12 => hrs = 0,
+        Some(Token::TimeOfDay(TimeOfDay::Night)) if hrs >= 9 => hrs += 12,
+        Some(Token::TimeOfDay(TimeOfDay::Morning)) if hrs == 12 => hrs = 0,
+        _ => (),
+    }
+
+    ctx.hour = Some(hrs);
+    ctx.minute = Some(minutes);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_time() {
+        let result = interpret("встреча в 15:30", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(15, 23)));
+        assert_eq!(result.get_hours(), 15);
+        assert_eq!(result.get_minutes(), 30);
+
+        let result = interpret("в 10:15 вечера", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 22);
+        assert_eq!(result.get_minutes(), 15);
+
+        let result = interpret("в 3 часа дня", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 15);
+        assert_eq!(result.get_minutes(), 0);
+
+        let result = interpret("7 утра", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 7);
+
+        let result = interpret("в 11 ночи", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 23);
+
+        let result = interpret("в 2 ночи", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 2);
+
+        let result = interpret("в 12 часов", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 12);
+
+        let result = interpret("на 3 дня", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+
+        let result = interpret("это займет 3 часа", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+
+    #[test]
+    fn test_wrong_times() {
+        let result = interpret("24:10", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("24:10", "hours", 24).extract_error()
+        );
+    }
+}
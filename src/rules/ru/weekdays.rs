@@ -0,0 +1,141 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::weekday as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Priority, Token, Weekday as Day, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, opt, preceded, tuple, types::CompleteStr};
+
+// days of week in nominative and accusative cases, e.g. "среда" and "в среду"
+
+define!(
+    monday:
+    [(Token::Weekday(Day::Monday), Priority(0)), "понедельник", Dist(2)] |
+    [(Token::Weekday(Day::Monday), Priority(0)), "пн", Dist(0)]
+);
+
+define!(
+    tuesday:
+    [(Token::Weekday(Day::Tuesday), Priority(0)), "вторник", Dist(1)] |
+    [(Token::Weekday(Day::Tuesday), Priority(0)), "вт", Dist(0)]
+);
+
+define!(
+    wednesday:
+    [(Token::Weekday(Day::Wednesday), Priority(0)), "среда", Dist(1)] |
+    [(Token::Weekday(Day::Wednesday), Priority(0)), "среду", Dist(1)] |
+    [(Token::Weekday(Day::Wednesday), Priority(0)), "ср", Dist(0)]
+);
+
+define!(
+    thursday:
+    [(Token::Weekday(Day::Thursday), Priority(0)), "четверг", Dist(1)] |
+    [(Token::Weekday(Day::Thursday), Priority(0)), "чт", Dist(0)]
+);
+
+define!(
+    friday:
+    [(Token::Weekday(Day::Friday), Priority(0)), "пятница", Dist(1)] |
+    [(Token::Weekday(Day::Friday), Priority(0)), "пятницу", Dist(1)] |
+    [(Token::Weekday(Day::Friday), Priority(0)), "пт", Dist(0)]
+);
+
+define!(
+    saturday:
+    [(Token::Weekday(Day::Saturday), Priority(0)), "суббота", Dist(1)] |
+    [(Token::Weekday(Day::Saturday), Priority(0)), "субботу", Dist(1)] |
+    [(Token::Weekday(Day::Saturday), Priority(0)), "сб", Dist(0)]
+);
+
+define!(
+    sunday:
+    [(Token::Weekday(Day::Sunday), Priority(0)), "воскресенье", Dist(2)] |
+    [(Token::Weekday(Day::Sunday), Priority(0)), "вс", Dist(0)]
+);
+
+combine!(day_of_week => monday | tuesday | wednesday | thursday | friday | saturday | sunday);
+
+// adjectives agree with the gender of the day: "следующий вторник", "следующую среду",
+// "следующее воскресенье"
+
+define!(
+    this:
+    [(Token::When(When::This), Priority(1)), "этот", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "эту", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "это", Dist(0)]
+);
+
+define!(
+    last:
+    [(Token::When(When::Last), Priority(1)), "прошлый", Dist(1)] |
+    [(Token::When(When::Last), Priority(1)), "прошлую", Dist(1)] |
+    [(Token::When(When::Last), Priority(1)), "прошлое", Dist(1)]
+);
+
+define!(
+    next:
+    [(Token::When(When::Next), Priority(1)), "следующий", Dist(2)] |
+    [(Token::When(When::Next), Priority(1)), "следующую", Dist(2)] |
+    [(Token::When(When::Next), Priority(1)), "следующее", Dist(2)]
+);
+
+combine!(when => this | last | next);
+
+define!(
+    prep:
+    [(Token::When(When::In), Priority(2)), "в", Dist(0)] |
+    [(Token::When(When::In), Priority(2)), "во", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // when and then any day of week, e.g. "в следующую пятницу"
+            tuple!(preceded!(opt!(apply!(prep, true)), apply!(when, exact_match)),
+                   apply!(day_of_week, exact_match)) |
+            // day of week, e.g. "во вторник"
+            tuple!(preceded!(opt!(apply!(prep, true)), apply!(day_of_week, exact_match)), stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 2);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::ambiguous_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_weekdays() {
+        let result = interpret("увидимся в следующую пятницу", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(17, 53)));
+        assert_eq!(result.get_duration_sec(), 3 * 86400);
+
+        let result = interpret("в прошлый понедельник", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -86400);
+
+        let result = interpret("следующий вторнк", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 7 * 86400);
+
+        let result = interpret("в среду", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 86400);
+
+        let result = interpret("это воскресенье", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 5 * 86400);
+
+        let result = interpret("в пн", true, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            ambiguous_time_error("в пн").extract_error()
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::prelude::DateTime;
+use chrono::prelude::{DateTime, Weekday};
 use nom::{types::CompleteStr, IResult};
 use serde::Serialize;
 use std::convert::From;
@@ -6,6 +6,8 @@ use std::convert::From;
 use super::combinators::Dist;
 use super::errors::SemanticError;
 use super::tokens::{PToken, Priority, Token};
+use crate::holidays::YearPolicy;
+use crate::languages::Language;
 
 pub type MyResult<'a> = IResult<CompleteStr<'a>, TokenDesc>;
 
@@ -13,11 +15,29 @@ pub type MyResult<'a> = IResult<CompleteStr<'a>, TokenDesc>;
 pub struct TokenDesc {
     pub token: PToken,
     pub dist: Dist,
+    // weight of the typos according to the typo model, equals to dist by default
+    pub cost: f64,
+    // number of letters in the matched word, 0 for numbers and symbols
+    pub len: usize,
 }
 
 impl TokenDesc {
     pub(crate) fn new(token: PToken, dist: Dist) -> Self {
-        Self { token, dist }
+        let cost = dist.0 as f64;
+        Self {
+            token,
+            dist,
+            cost,
+            len: 0,
+        }
+    }
+    pub(crate) fn with_cost(mut self, cost: f64) -> Self {
+        self.cost = cost;
+        self
+    }
+    pub(crate) fn with_len(mut self, len: usize) -> Self {
+        self.len = len;
+        self
     }
     pub(crate) fn clone_content(&self) -> PToken {
         self.token.clone()
@@ -36,7 +56,59 @@ impl MatchBounds {
     }
 }
 
+/// Length of an interval which starts at the resolved date/time
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Span {
+    Days(i64),
+    Months(i32),
+}
+
+/// Part of a fiscal year, it is resolved by parser because it depends on the fiscal year start
 #[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Period {
+    // n-th quarter of a year, from 1 to 4
+    Quarter(i32),
+    // n-th half of a year, 1 or 2
+    Half(i32),
+    // quarter relative to the current one, e.g. -1 for "last quarter"
+    QuarterShift(i32),
+    // half relative to the current one, e.g. 1 for "next half"
+    HalfShift(i32),
+}
+
+/// Holiday recognized by name, it is resolved by parser because holiday dates come from
+/// parser's calendars
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct HolidayRef {
+    // position of the holiday name in the session holidays list
+    pub id: usize,
+    // overrides parser's policy, e.g. for "next christmas"
+    pub policy: Option<YearPolicy>,
+    // days to add to the holiday date, e.g. 1 for "the day after thanksgiving"
+    pub day_offset: i64,
+}
+
+/// Shift applied to an already resolved date, e.g. "3 days" in "3 days after christmas"
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Offset {
+    pub duration: time::Duration,
+    pub months: i32,
+}
+
+impl Default for Offset {
+    fn default() -> Self {
+        Offset {
+            duration: time::Duration::zero(),
+            months: 0,
+        }
+    }
+}
+
+/// Components of a date/time expression, rules set the ones they recognize and parser resolves
+/// them relative to the current time. New components may be added, so contexts are built from
+/// "Context::default()".
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[non_exhaustive]
 pub struct Context {
     // relative value
     pub duration: time::Duration,
@@ -47,6 +119,31 @@ pub struct Context {
     pub day: Option<i32>,
     pub hour: Option<i32>,
     pub minute: Option<i32>,
+    // set when hour and minute come from a part of the day, e.g. "evening", rather than from
+    // an exact time, an exact time next to it refines them
+    pub part_of_day: bool,
+
+    // ISO week number and optional day of that week, week is resolved by parser because
+    // it depends on the week start setting
+    pub week: Option<i32>,
+    pub weekday: Option<Weekday>,
+
+    // quarter or half of a year, if set the year above is a fiscal year,
+    // period_end is set for expressions like "end of q2"
+    pub period: Option<Period>,
+    pub period_end: bool,
+
+    pub holiday: Option<HolidayRef>,
+
+    // business days to move by, resolved by parser because weekends and days off are
+    // configurable, zero means the nearest business day which is not in the past
+    pub business_days: Option<i32>,
+
+    // applied after everything else, so it shifts the date expression it is attached to
+    pub offset: Option<Offset>,
+
+    // set when the match denotes an interval rather than a moment, e.g. "week 42"
+    pub span: Option<Span>,
 }
 
 impl Context {
@@ -58,7 +155,7 @@ impl Context {
     }
 
     pub fn update(&mut self, other: &Self) {
-        self.duration = self.duration + other.duration;
+        self.duration = saturating_add(self.duration, other.duration);
         if other.year.is_some() {
             self.year = other.year;
         }
@@ -68,15 +165,130 @@ impl Context {
         if other.day.is_some() {
             self.day = other.day;
         }
-        if other.hour.is_some() {
-            self.hour = other.hour;
+        // a part of the day doesn't override an exact time, e.g. "at 7pm in the evening"
+        if !(other.part_of_day && !self.part_of_day && self.hour.is_some()) {
+            if other.hour.is_some() {
+                self.hour = other.hour;
+                self.part_of_day = other.part_of_day;
+            }
+            if other.minute.is_some() {
+                self.minute = other.minute;
+            }
         }
-        if other.minute.is_some() {
-            self.minute = other.minute;
+        if other.week.is_some() {
+            self.week = other.week;
         }
+        if other.weekday.is_some() {
+            self.weekday = other.weekday;
+        }
+        if other.period.is_some() {
+            self.period = other.period;
+            self.period_end = other.period_end;
+        }
+        if other.holiday.is_some() {
+            self.holiday = other.holiday;
+        }
+        if other.business_days.is_some() {
+            self.business_days = other.business_days;
+        }
+        if let Some(other_offset) = other.offset {
+            let offset = self.offset.unwrap_or_default();
+            self.offset = Some(Offset {
+                duration: saturating_add(offset.duration, other_offset.duration),
+                months: offset.months.saturating_add(other_offset.months),
+            });
+        }
+        if other.span.is_some() {
+            self.span = other.span;
+        }
+    }
+
+    /// Components the context sets along with their values, e.g. [("hour", "17")]
+    pub(crate) fn components(&self) -> Vec<(&'static str, String)> {
+        fn some<T: std::fmt::Debug>(
+            name: &'static str,
+            value: &Option<T>,
+        ) -> Option<(&'static str, String)> {
+            value.as_ref().map(|value| (name, format!("{:?}", value)))
+        }
+
+        let duration = Some(self.duration.num_seconds()).filter(|&seconds| seconds != 0);
+        [
+            some("duration", &duration),
+            some("year", &self.year),
+            some("month", &self.month),
+            some("day", &self.day),
+            some("hour", &self.hour),
+            some("minute", &self.minute),
+            some("part_of_day", &Some(true).filter(|_| self.part_of_day)),
+            some("week", &self.week),
+            some("weekday", &self.weekday),
+            some("period", &self.period),
+            some("period_end", &Some(true).filter(|_| self.period_end)),
+            some("holiday", &self.holiday),
+            some("business_days", &self.business_days),
+            some("offset", &self.offset),
+            some("span", &self.span),
+        ]
+        .iter()
+        .flatten()
+        .cloned()
+        .collect()
+    }
+
+    /// Names of the components which both contexts set to different values, e.g. ["day"] for
+    /// "march 5" and "march 6", such contexts can't be updated with each other without losing
+    /// information. Relative shifts conflict if both are set, parts of the day don't conflict
+    /// with exact times.
+    pub fn conflicts(&self, other: &Self) -> Vec<&'static str> {
+        fn differ<T: PartialEq>(first: &Option<T>, second: &Option<T>) -> bool {
+            matches!((first, second), (Some(first), Some(second)) if first != second)
+        }
+
+        let time_conflicts = self.part_of_day == other.part_of_day;
+        let checks = [
+            (
+                "duration",
+                !self.duration.is_zero() && !other.duration.is_zero(),
+            ),
+            ("year", differ(&self.year, &other.year)),
+            ("month", differ(&self.month, &other.month)),
+            ("day", differ(&self.day, &other.day)),
+            ("hour", time_conflicts && differ(&self.hour, &other.hour)),
+            (
+                "minute",
+                time_conflicts && differ(&self.minute, &other.minute),
+            ),
+            ("week", differ(&self.week, &other.week)),
+            ("weekday", differ(&self.weekday, &other.weekday)),
+            ("period", differ(&self.period, &other.period)),
+            ("holiday", differ(&self.holiday, &other.holiday)),
+            (
+                "business days",
+                differ(&self.business_days, &other.business_days),
+            ),
+            ("span", differ(&self.span, &other.span)),
+        ];
+
+        checks
+            .iter()
+            .filter(|(_, conflict)| *conflict)
+            .map(|(name, _)| *name)
+            .collect()
     }
 }
 
+/// Sum of the durations clamped to the durations range, parser reports out of range dates
+fn saturating_add(first: time::Duration, second: time::Duration) -> time::Duration {
+    first.checked_add(&second).unwrap_or_else(|| {
+        if second < time::Duration::zero() {
+            time::Duration::min_value()
+        } else {
+            time::Duration::max_value()
+        }
+    })
+}
+
 impl Default for Context {
     fn default() -> Self {
         Context {
@@ -86,6 +298,15 @@ impl Default for Context {
             day: None,
             hour: None,
             minute: None,
+            part_of_day: false,
+            week: None,
+            weekday: None,
+            period: None,
+            period_end: false,
+            holiday: None,
+            business_days: None,
+            offset: None,
+            span: None,
         }
     }
 }
@@ -94,6 +315,10 @@ impl Default for Context {
 pub(crate) struct RuleResult<'a> {
     pub tail: &'a str,
     pub tokens: Option<Vec<PToken>>,
+    // (typos cost, length) of every matched token
+    pub evidence: Vec<(f64, usize)>,
+    // distance of every matched token from its pattern
+    pub dists: Vec<Dist>,
     pub bounds: Option<MatchBounds>,
 
     pub context: Context,
@@ -107,6 +332,8 @@ impl<'a> RuleResult<'a> {
         Self {
             tail: "",
             tokens: None,
+            evidence: Vec::new(),
+            dists: Vec::new(),
             bounds: None,
             context: Default::default(),
         }
@@ -114,6 +341,8 @@ impl<'a> RuleResult<'a> {
 
     pub fn set_token(&mut self, token_descr: &TokenDesc) -> &mut Self {
         if token_descr.token != PToken::Stub {
+            self.evidence.push((token_descr.cost, token_descr.len));
+            self.dists.push(token_descr.dist.clone());
             if let Some(ref mut tokens) = self.tokens {
                 tokens.push(token_descr.clone_content());
             } else {
@@ -191,6 +420,9 @@ impl<'a> RuleResult<'a> {
 pub struct MatchResult {
     bounds: MatchBounds,
     time_shift: Context,
+    language: Option<Language>,
+    // from 0 to 1, low for words which are dates only by coincidence, e.g. "may" in "you may go"
+    confidence: f64,
 }
 
 impl MatchResult {
@@ -198,9 +430,34 @@ impl MatchResult {
         Self {
             bounds: MatchBounds::new(start_idx, end_idx),
             time_shift,
+            language: None,
+            confidence: 1.0,
         }
     }
 
+    pub fn with_language(mut self, language: Option<Language>) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: MatchBounds) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    pub fn get_language(&self) -> Option<Language> {
+        self.language
+    }
+
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn get_confidence(&self) -> f64 {
+        self.confidence
+    }
+
     pub fn get_timeshift(&self) -> &Context {
         &self.time_shift
     }
@@ -0,0 +1,85 @@
+use super::vocabulary::Vocabulary;
+use crate::aliases::AliasIndex;
+use crate::holidays::{default_calendars, holiday_names};
+use crate::languages::{Language, LanguagePolicy};
+use crate::typos::{EditDistance, TypoModel};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Weak words without a date context around them (e.g. "sun" in "sun is out") are dropped
+pub(crate) const DEFAULT_MIN_CONFIDENCE: f64 = 0.5;
+
+/// Vocabulary which is known only at runtime (e.g. holidays registered by user), rules consult
+/// it while matching. Parser installs its own session for the duration of parsing, rules applied
+/// outside of a parser (e.g. in tests) use the default one.
+#[derive(Debug)]
+pub(crate) struct Session {
+    // words of every holiday name, position in this list identifies a holiday
+    pub holidays: Vec<Vec<String>>,
+    // extra surface forms of tokens, consulted when a word doesn't match a rule's own spelling
+    pub aliases: Arc<AliasIndex>,
+    // words of the parser's own patterns (custom holidays, data rules) indexed for fuzzy lookup,
+    // words of built-in rules are indexed separately
+    pub vocabulary: Arc<Vocabulary>,
+    pub exact_match: bool,
+    // languages in the order of preference and how their intersecting matches are resolved
+    pub languages: Vec<Language>,
+    pub language_policy: LanguagePolicy,
+    // matches with lower confidence are dropped, see "combinators::remove_weak"
+    pub min_confidence: f64,
+    pub typo_model: Arc<dyn TypoModel>,
+}
+
+impl Session {
+    pub fn new(holidays: &[(usize, String)]) -> Self {
+        Session {
+            holidays: holidays.iter().map(|(_, name)| split_words(name)).collect(),
+            aliases: Arc::new(AliasIndex::default()),
+            vocabulary: Arc::new(Vocabulary::default()),
+            exact_match: false,
+            languages: Vec::new(),
+            language_policy: LanguagePolicy::Order,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+            typo_model: Arc::new(EditDistance),
+        }
+    }
+}
+
+/// Splits a phrase into words the same way as input text gets tokenized, i.e. "st. patrick's day"
+/// becomes ["st", "patrick", "s", "day"]
+pub(crate) fn split_words(phrase: &str) -> Vec<String> {
+    phrase
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<Session>>> = const { RefCell::new(None) };
+    static DEFAULT: Rc<Session> = Rc::new(Session::new(&holiday_names(&default_calendars())));
+}
+
+/// Runs the function with the session installed, previous session is restored afterwards even
+/// if the function panics
+pub(crate) fn scoped<R, F: FnOnce() -> R>(session: Session, f: F) -> R {
+    struct Restore(Option<Rc<Session>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(Rc::new(session)))));
+    f()
+}
+
+/// Returns currently installed session or the default one
+pub(crate) fn current() -> Rc<Session> {
+    CURRENT
+        .with(|current| current.borrow().clone())
+        .unwrap_or_else(|| DEFAULT.with(|default| default.clone()))
+}
@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Month {
     January,
     February,
@@ -14,7 +14,7 @@ pub enum Month {
     December,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Weekday {
     Monday,
     Tuesday,
@@ -25,17 +25,19 @@ pub enum Weekday {
     Sunday,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Pronouns {
     This,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Prepositions {
     Of,
+    After,
+    Before,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum When {
     Within,
     In,
@@ -48,15 +50,17 @@ pub enum When {
     Tonight,
     Tomorrow,
     Yesterday,
+    DayAfterTomorrow,
+    DayBeforeYesterday,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AmPm {
     Am,
     Pm,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TimeOfDay {
     Night,
     Morning,
@@ -65,7 +69,7 @@ pub enum TimeOfDay {
     Afternoon,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum IntWord {
     One,
     Two,
@@ -81,7 +85,7 @@ pub enum IntWord {
     Twelve,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Ordinals {
     First,
     Second,
@@ -116,31 +120,50 @@ pub enum Ordinals {
     ThirtiethFirst,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TimeInterval {
     Second,
     Minute,
     Hour,
     Day,
+    BusinessDay,
     Week,
     Month,
+    Quarter,
+    HalfYear,
     Year,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Boundary {
+    Start,
+    End,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Adverbs {
     Half,
     Few,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Articles {
     A,
     An,
     The,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+// japanese eras, e.g. "令和5年" is 2023
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Era {
+    Meiji,
+    Taisho,
+    Showa,
+    Heisei,
+    Reiwa,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Token {
     Char, // stands for any character
     Week,
@@ -158,14 +181,18 @@ pub enum Token {
     TimeOfDay(TimeOfDay),
     Adverbs(Adverbs),
     Pronouns(Pronouns),
+    Boundary(Boundary),
+    Era(Era),
+    // position of a holiday name in the session holidays list
+    Holiday(usize),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Priority(pub isize);
 
 // This enum adds priority value to token, tokens with smaller priority numbers are
 // being parsed first
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PToken {
     Stub,
     PToken(Token, Priority),
@@ -0,0 +1,122 @@
+use super::chart::Chart;
+use crate::explain::{MatchTrace, Removal, RemovalReason, RuleTrace, TokenTrace};
+use crate::languages::Language;
+use crate::rule::RuleMatch;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// What rules did with the input, it is recorded only while parser explains a parse
+#[derive(Debug, Default)]
+pub(crate) struct Trace {
+    pub rules: Vec<RuleTrace>,
+    pub removed: Vec<Removal>,
+    // tokens of the matches of the rule being applied, keyed by the bounds of the matches
+    pub tokens: HashMap<(usize, usize), Vec<TokenTrace>>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Trace>> = const { RefCell::new(None) };
+}
+
+/// Runs the function recording the trace, previous trace is restored afterwards even if the
+/// function panics
+pub(crate) fn scoped<R, F: FnOnce() -> R>(f: F) -> (R, Trace) {
+    struct Restore(Option<Trace>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CURRENT.with(|current| *current.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(CURRENT.with(|current| current.replace(Some(Trace::default()))));
+    let result = f();
+    let trace = CURRENT.with(|current| current.borrow_mut().take());
+    (result, trace.unwrap_or_default())
+}
+
+/// Updates the trace if it is being recorded
+pub(crate) fn record<F: FnOnce(&mut Trace)>(f: F) {
+    CURRENT.with(|current| {
+        if let Some(trace) = current.borrow_mut().as_mut() {
+            f(trace);
+        }
+    });
+}
+
+/// Records matches of the rule along with the tokens the rule reported for them
+pub(crate) fn rule_applied(
+    name: &str,
+    language: Option<Language>,
+    priority: i32,
+    input: &str,
+    matches: &[RuleMatch],
+) {
+    record(|trace| {
+        let mut tokens = std::mem::take(&mut trace.tokens);
+        trace.rules.push(RuleTrace {
+            name: name.to_owned(),
+            language: language.map(|language| language.code().to_owned()),
+            priority,
+            matches: matches
+                .iter()
+                .map(|m| MatchTrace {
+                    start_idx: m.start_idx,
+                    end_idx: m.end_idx,
+                    text: input.get(m.start_idx..m.end_idx).unwrap_or("").to_owned(),
+                    confidence: m.confidence,
+                    tokens: tokens.remove(&(m.start_idx, m.end_idx)).unwrap_or_default(),
+                    error: m.result.as_ref().err().map(|error| error.to_string()),
+                })
+                .collect(),
+        });
+    });
+}
+
+/// Records candidates a step of the parser dropped given their bounds before and after the step
+pub(crate) fn removed(
+    reason: RemovalReason,
+    input: &str,
+    before: &[(usize, usize)],
+    after: &[(usize, usize)],
+) {
+    record(|trace| {
+        let mut left = after.to_vec();
+        for &(start_idx, end_idx) in before {
+            match left
+                .iter()
+                .position(|&bounds| bounds == (start_idx, end_idx))
+            {
+                Some(idx) => {
+                    left.swap_remove(idx);
+                }
+                None => trace.removed.push(Removal {
+                    start_idx,
+                    end_idx,
+                    text: input.get(start_idx..end_idx).unwrap_or("").to_owned(),
+                    reason,
+                    score: None,
+                }),
+            }
+        }
+    });
+}
+
+/// Records candidates which are not a part of the best cover of the input
+pub(crate) fn unselected(input: &str, chart: &Chart) {
+    record(|trace| {
+        for entry in chart.entries.iter().filter(|entry| !entry.selected) {
+            trace.removed.push(Removal {
+                start_idx: entry.start_idx,
+                end_idx: entry.end_idx,
+                text: input
+                    .get(entry.start_idx..entry.end_idx)
+                    .unwrap_or("")
+                    .to_owned(),
+                reason: RemovalReason::NotSelected,
+                score: Some(entry.score),
+            });
+        }
+    });
+}
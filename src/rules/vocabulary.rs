@@ -0,0 +1,186 @@
+use super::session::split_words;
+use crate::holidays::{default_calendars, holiday_names};
+use crate::languages::Language;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Words which differ from the pattern in more edits are not indexed, rules allow at most 3
+pub(crate) const MAX_EDITS: usize = 3;
+
+/// Symmetric delete index of words: every pattern is stored along with all strings obtained by
+/// deleting up to MAX_EDITS letters from it, so patterns close to a word are found by looking up
+/// deletes of the word
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Vocabulary {
+    patterns: Vec<String>,
+    ids: HashMap<String, usize>,
+    deletes: HashMap<String, Vec<usize>>,
+    max_len: usize,
+}
+
+/// All strings obtained by deleting up to max_edits letters from the word, the word included
+fn deletes(word: &str, max_edits: usize) -> HashSet<String> {
+    let mut result = HashSet::new();
+    let mut current = vec![word.to_owned()];
+    result.insert(word.to_owned());
+
+    for _ in 0..max_edits {
+        let mut next = Vec::new();
+        for word in &current {
+            for (idx, c) in word.char_indices() {
+                let mut deleted = word.clone();
+                deleted.replace_range(idx..idx + c.len_utf8(), "");
+                if result.insert(deleted.clone()) {
+                    next.push(deleted);
+                }
+            }
+        }
+        current = next;
+    }
+
+    result
+}
+
+impl Vocabulary {
+    /// Indexes the pattern if it is new
+    pub fn add(&mut self, pattern: &str) {
+        if self.ids.contains_key(pattern) {
+            return;
+        }
+
+        let id = self.patterns.len();
+        for deleted in deletes(pattern, MAX_EDITS) {
+            self.deletes.entry(deleted).or_default().push(id);
+        }
+        self.patterns.push(pattern.to_owned());
+        self.ids.insert(pattern.to_owned(), id);
+        self.max_len = self.max_len.max(pattern.chars().count());
+    }
+
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.ids.contains_key(pattern)
+    }
+
+    /// Finds all patterns which may be within MAX_EDITS edits from the word in one probe
+    pub fn candidates(&self, word: &str) -> Vec<&str> {
+        if self.patterns.is_empty() || word.chars().count() > self.max_len + MAX_EDITS {
+            return Vec::new();
+        }
+
+        let mut ids: Vec<usize> = deletes(word, MAX_EDITS)
+            .iter()
+            .filter_map(|deleted| self.deletes.get(deleted))
+            .flatten()
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        ids.into_iter()
+            .map(|id| self.patterns[id].as_str())
+            .collect()
+    }
+}
+
+/// Words built-in rules of the language recognize with typos, see "vocabulary.txt" of the
+/// language, Chinese and Japanese rules recognize words exactly
+fn builtin_words(language: Language) -> impl Iterator<Item = &'static str> {
+    let words = match language {
+        Language::English => include_str!("en/vocabulary.txt"),
+        Language::Russian => include_str!("ru/vocabulary.txt"),
+        Language::Portuguese => include_str!("br/vocabulary.txt"),
+        Language::German => include_str!("de/vocabulary.txt"),
+        Language::French => include_str!("fr/vocabulary.txt"),
+        Language::Spanish => include_str!("es/vocabulary.txt"),
+        Language::Chinese | Language::Japanese => "",
+    };
+    words
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Index of the words built-in rules of the language recognize, it is built the first time it
+/// is needed and shared by all parsers, lookups don't lock
+pub(crate) fn builtin(language: Language) -> &'static Vocabulary {
+    static VOCABULARIES: OnceLock<HashMap<Language, OnceLock<Vocabulary>>> = OnceLock::new();
+
+    let vocabularies = VOCABULARIES.get_or_init(|| {
+        Language::all()
+            .iter()
+            .map(|&language| (language, OnceLock::new()))
+            .collect()
+    });
+
+    vocabularies[&language].get_or_init(|| {
+        let mut vocabulary = Vocabulary::default();
+        builtin_words(language).for_each(|pattern| vocabulary.add(pattern));
+        // names of the default holidays are recognized by english rules
+        if language == Language::English {
+            for (_, name) in holiday_names(&default_calendars()) {
+                split_words(&name)
+                    .iter()
+                    .for_each(|word| vocabulary.add(word));
+            }
+        }
+        vocabulary
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builtin, builtin_words, deletes, Vocabulary};
+    use crate::languages::Language;
+
+    #[test]
+    fn test_deletes() {
+        let mut found: Vec<String> = deletes("abc", 1).into_iter().collect();
+        found.sort();
+        assert_eq!(found, vec!["ab", "abc", "ac", "bc"]);
+        assert_eq!(deletes("abc", 3).len(), 8);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let mut vocabulary = Vocabulary::default();
+        vocabulary.add("friday");
+        vocabulary.add("monday");
+        vocabulary.add("twentieth");
+        vocabulary.add("friday");
+        assert!(vocabulary.contains("friday"));
+        assert!(!vocabulary.contains("fridy"));
+
+        // a dropped letter, swapped letters, "monday" is within 3 edits of "firday" as well
+        assert_eq!(vocabulary.candidates("fridy"), vec!["friday"]);
+        assert_eq!(vocabulary.candidates("firday"), vec!["friday", "monday"]);
+        assert_eq!(vocabulary.candidates("mnoday"), vec!["friday", "monday"]);
+        assert!(vocabulary.candidates("tomorrow").is_empty());
+        assert!(vocabulary.candidates("incomprehensibilities").is_empty());
+    }
+
+    #[test]
+    fn test_builtin() {
+        let english = builtin(Language::English);
+        assert!(english.contains("friday"));
+        assert!(english.contains("tomorrow"));
+        assert!(english.contains("christmas"));
+        assert!(!english.contains("пятница"));
+        assert!(english.candidates("fridy").contains(&"friday"));
+        assert!(builtin(Language::Russian).contains("пятница"));
+        // built once
+        assert!(std::ptr::eq(english, builtin(Language::English)));
+    }
+
+    #[test]
+    fn test_builtin_words() {
+        for &language in Language::all() {
+            let words: Vec<&str> = builtin_words(language).collect();
+            let mut sorted = words.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            assert_eq!(words, sorted, "{:?}", language);
+            assert!(words
+                .iter()
+                .all(|word| word.trim() == *word && !word.is_empty()));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::{is_leap_year, DAYS_IN_MONTH};
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Priority, Token};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define_cjk_num!(day: (Token::Number, Priority(0)));
+define_cjk_num!(month: (Token::Number, Priority(1)));
+define_cjk_num!(year: (Token::Number, Priority(2)));
+
+define!(year_word: (Token::Char, Priority(3)), "年", Dist(0));
+define!(month_word: (Token::Char, Priority(4)), "月", Dist(0));
+define!(
+    day_word:
+    [(Token::Char, Priority(5)), "日", Dist(0)] |
+    [(Token::Char, Priority(5)), "号", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // e.g. "2019年3月5日", "二〇一九年三月"
+            tuple!(year, apply!(year_word, exact_match), month, apply!(month_word, exact_match),
+                   day, apply!(day_word, exact_match)) |
+            tuple!(year, apply!(year_word, exact_match), month, apply!(month_word, exact_match),
+                   stub, stub) |
+            // e.g. "3月5号", "三月"
+            tuple!(stub, stub, month, apply!(month_word, exact_match), day,
+                   apply!(day_word, exact_match)) |
+            tuple!(stub, stub, month, apply!(month_word, exact_match), stub, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 6);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    // if day is omitted, assume it is 1st day of a month
+    let day = match res.token_by_priority(Priority(0)) {
+        Some(Token::Number(n)) => n,
+        _ => 1,
+    };
+
+    let month = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    let year = match res.token_by_priority(Priority(2)) {
+        Some(Token::Number(n)) => Some(n),
+        _ => None,
+    };
+
+    if !(1..=12).contains(&month) {
+        return Err(invalid_time_error(input, "month", month));
+    }
+
+    // 29 days in february for leap years
+    let days_in_month = if month == 2 && is_leap_year(year.unwrap_or_else(|| tz_aware.year())) {
+        29
+    } else {
+        DAYS_IN_MONTH[month as usize - 1]
+    };
+
+    if day <= 0 || day > days_in_month {
+        return Err(invalid_time_error(input, "day", day));
+    }
+
+    ctx.year = year;
+    ctx.day = Some(day);
+    ctx.month = Some(month);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_exact_month_date() {
+        let result = interpret("会议在3月5号", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(9, 17)));
+        assert_eq!(result.get_month(), 3);
+        assert_eq!(result.get_day(), 5);
+
+        let result = interpret("2020年2月29日", false, fixed_time()).unwrap();
+        assert_eq!(result.get_year(), 2020);
+        assert_eq!(result.get_month(), 2);
+        assert_eq!(result.get_day(), 29);
+
+        let result = interpret("二〇一九年十二月二十五日", false, fixed_time()).unwrap();
+        assert_eq!(result.get_year(), 2019);
+        assert_eq!(result.get_month(), 12);
+        assert_eq!(result.get_day(), 25);
+
+        let result = interpret("五月", false, fixed_time()).unwrap();
+        assert_eq!(result.get_month(), 5);
+        assert_eq!(result.get_day(), 1);
+
+        let result = interpret("三个月", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+
+    #[test]
+    fn test_wrong_dates() {
+        let result = interpret("2月30日", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("2月30日", "day", 30).extract_error()
+        );
+
+        let result = interpret("13月", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("13月", "month", 13).extract_error()
+        );
+    }
+}
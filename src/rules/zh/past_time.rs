@@ -0,0 +1,96 @@
+use super::combinators::{tokenize_count_symbols, Dist};
+use super::common::interpreters::past_time as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Adverbs, Priority, TimeInterval, Token};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define_cjk_num!(number: (Token::Number, Priority(1)));
+
+define!(few: (Token::Adverbs(Adverbs::Few), Priority(1)), "几", Dist(0));
+define!(half: (Token::Adverbs(Adverbs::Half), Priority(1)), "半", Dist(0));
+
+define!(
+    seconds:
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "秒钟", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Second), Priority(2)), "秒", Dist(0)]
+);
+define!(minutes: (Token::TimeInterval(TimeInterval::Minute), Priority(2)), "分钟", Dist(0));
+define!(
+    hours:
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "个小时", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "个钟头", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "小时", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Hour), Priority(2)), "钟头", Dist(0)]
+);
+define!(days: (Token::TimeInterval(TimeInterval::Day), Priority(2)), "天", Dist(0));
+define!(
+    weeks:
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "个星期", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "个礼拜", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "星期", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "礼拜", Dist(0)] |
+    [(Token::TimeInterval(TimeInterval::Week), Priority(2)), "周", Dist(0)]
+);
+define!(months: (Token::TimeInterval(TimeInterval::Month), Priority(2)), "个月", Dist(0));
+define!(years: (Token::TimeInterval(TimeInterval::Year), Priority(2)), "年", Dist(0));
+
+combine!(time_interval => seconds | minutes | hours | days | weeks | months | years);
+
+define!(
+    ago:
+    [(Token::Ago, Priority(0)), "以前", Dist(0)] |
+    [(Token::Ago, Priority(0)), "之前", Dist(0)] |
+    [(Token::Ago, Priority(0)), "前", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 3天前, 两个小时以前, 几分钟前
+            tuple!(alt!(number | apply!(few, exact_match)), apply!(time_interval, exact_match),
+                   apply!(ago, exact_match)) |
+            // 半小时前
+            tuple!(apply!(half, exact_match), apply!(hours, exact_match), apply!(ago, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_past_time() {
+        let result = interpret("3天前来过", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 7)));
+        assert_eq!(result.get_duration_sec(), -3 * consts::DAY as i64);
+
+        let result = interpret("两个小时以前", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -2 * consts::HOUR as i64);
+
+        let result = interpret("半小时前", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -30 * consts::MINUTE as i64);
+
+        let result = interpret("一年前", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -365 * consts::DAY as i64);
+
+        let result = interpret("1000000小时前", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("1000000小时前", "number", 1000000).extract_error()
+        );
+    }
+}
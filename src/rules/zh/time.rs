@@ -0,0 +1,192 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::consts;
+use super::errors::{invalid_time_error, SemanticError};
+use super::rules::{Context, RuleResult, TokenDesc};
+use super::tokens::{Adverbs, Priority, TimeOfDay, Token, When};
+use chrono::prelude::*;
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+// the day and the part of the day go before hours, e.g. "明天下午3点", so they are parsed here
+// rather than being merged with a separate match
+
+define!(
+    day:
+    [(Token::When(When::Today), Priority(0)), "今天", Dist(0)] |
+    [(Token::When(When::Tomorrow), Priority(0)), "明天", Dist(0)] |
+    [(Token::When(When::Yesterday), Priority(0)), "昨天", Dist(0)] |
+    [(Token::When(When::DayAfterTomorrow), Priority(0)), "后天", Dist(0)] |
+    [(Token::When(When::DayBeforeYesterday), Priority(0)), "前天", Dist(0)]
+);
+
+define!(
+    day_part:
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "早上", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "早晨", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Morning), Priority(5)), "上午", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Noon), Priority(5)), "中午", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Afternoon), Priority(5)), "下午", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(5)), "傍晚", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(5)), "晚上", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Evening), Priority(5)), "今晚", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Night), Priority(5)), "凌晨", Dist(0)] |
+    [(Token::TimeOfDay(TimeOfDay::Night), Priority(5)), "半夜", Dist(0)]
+);
+
+define_cjk_num!(hours: (Token::Number, Priority(1)));
+
+// hours in arabic digits, "一点" is also "a little", so it is not a time without other hints
+define_cjk_num!(digit_hours: (Token::Number, Priority(1)), digits);
+
+define!(
+    o_clock:
+    [(Token::Char, Priority(2)), "点钟", Dist(0)] |
+    [(Token::Char, Priority(2)), "点", Dist(0)] |
+    [(Token::Char, Priority(2)), "时", Dist(0)]
+);
+define!(full_o_clock: (Token::Char, Priority(2)), "点钟", Dist(0));
+
+define_char!(colon: Priority(2), ':');
+define_char!(wide_colon: Priority(2), '：');
+
+define_cjk_num!(minutes: (Token::Number, Priority(3)));
+define!(half: (Token::Adverbs(Adverbs::Half), Priority(3)), "半", Dist(0));
+
+define!(
+    minute_word:
+    [(Token::Char, Priority(4)), "分钟", Dist(0)] |
+    [(Token::Char, Priority(4)), "分", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // e.g. "3点半", "明天下午3点15分"
+            tuple!(alt!(apply!(day, exact_match) | stub), alt!(apply!(day_part, exact_match) | stub),
+                   hours, apply!(o_clock, exact_match),
+                   alt!(apply!(half, exact_match) | minutes), alt!(apply!(minute_word, exact_match) | stub)) |
+            // e.g. "下午3点", "明天早上八点"
+            tuple!(alt!(apply!(day, exact_match) | stub), apply!(day_part, exact_match),
+                   hours, apply!(o_clock, exact_match), stub, stub) |
+            // e.g. "明天3点"
+            tuple!(apply!(day, exact_match), stub, hours, apply!(o_clock, exact_match), stub, stub) |
+            // e.g. "3点", "三点钟"
+            tuple!(stub, stub, digit_hours, apply!(o_clock, exact_match), stub, stub) |
+            tuple!(stub, stub, hours, apply!(full_o_clock, exact_match), stub, stub) |
+            // e.g. "明天下午3:30"
+            tuple!(alt!(apply!(day, exact_match) | stub), alt!(apply!(day_part, exact_match) | stub),
+                   digit_hours, alt!(colon | wide_colon), minutes, stub)
+        )
+    )
+);
+
+make_interpreter!(positions = 6);
+
+fn make_time<'a, 'b, Tz: TimeZone>(
+    res: &'a RuleResult,
+    _tz_aware: DateTime<Tz>,
+    input: &'b str,
+) -> Result<Context, SemanticError<'b>> {
+    let mut ctx = Context::default();
+
+    let mut hrs = match res.token_by_priority(Priority(1)) {
+        Some(Token::Number(n)) => n,
+        _ => unreachable!(),
+    };
+
+    let minutes = match res.token_by_priority(Priority(3)) {
+        Some(Token::Number(n)) => n,
+        Some(Token::Adverbs(Adverbs::Half)) => 30,
+        _ => 0,
+    };
+
+    if !(0..=23).contains(&hrs) {
+        return Err(invalid_time_error(input, "hours", hrs));
+    }
+    if !(0..=59).contains(&minutes) {
+        return Err(invalid_time_error(input, "minutes", minutes));
+    }
+
+    match res.token_by_priority(Priority(5)) {
+        // "晚上12点" is midnight
+        Some(Token::TimeOfDay(TimeOfDay::Evening)) if hrs == 12 => hrs = 0,
+        Some(Token::TimeOfDay(TimeOfDay::Night)) if hrs == 12 => hrs = 0,
+        // "中午1点" is 13:00
+        Some(Token::TimeOfDay(TimeOfDay::Noon)) if hrs < 6 => hrs += 12,
+        Some(Token::TimeOfDay(TimeOfDay::Afternoon))
+        | Some(Token::TimeOfDay(TimeOfDay::Evening))
+            if hrs < 12 =>
+        {
+            hrs += 12
+        }
+        _ => (),
+    }
+
+    match res.token_by_priority(Priority(0)) {
+        Some(Token::When(When::Tomorrow)) => ctx.set_duration(consts::DAY),
+        Some(Token::When(When::Yesterday)) => ctx.set_duration(-consts::DAY),
+        Some(Token::When(When::DayAfterTomorrow)) => ctx.set_duration(2 * consts::DAY),
+        Some(Token::When(When::DayBeforeYesterday)) => ctx.set_duration(-2 * consts::DAY),
+        _ => (),
+    }
+
+    ctx.hour = Some(hrs);
+    ctx.minute = Some(minutes);
+
+    Ok(ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::consts;
+    use crate::rules::errors::invalid_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 0)
+    }
+
+    #[test]
+    fn test_time() {
+        let result = interpret("明天下午3点开会", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(0, 16)));
+        assert_eq!(result.get_duration_sec(), consts::DAY as i64);
+        assert_eq!(result.get_hours(), 15);
+        assert_eq!(result.get_minutes(), 0);
+
+        let result = interpret("3点半", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 3);
+        assert_eq!(result.get_minutes(), 30);
+
+        let result = interpret("晚上八点二十分", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 20);
+        assert_eq!(result.get_minutes(), 20);
+
+        let result = interpret("三点钟", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 3);
+
+        let result = interpret("中午1点", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 13);
+
+        let result = interpret("凌晨12点", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 0);
+
+        let result = interpret("下午3:45", false, fixed_time()).unwrap();
+        assert_eq!(result.get_hours(), 15);
+        assert_eq!(result.get_minutes(), 45);
+
+        let result = interpret("好一点", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+
+    #[test]
+    fn test_wrong_times() {
+        let result = interpret("25点", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            invalid_time_error("25点", "hours", 25).extract_error()
+        );
+    }
+}
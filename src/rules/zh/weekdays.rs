@@ -0,0 +1,99 @@
+use super::combinators::{stub, tokenize_count_symbols, Dist};
+use super::common::interpreters::week_day as make_time;
+use super::rules::{RuleResult, TokenDesc};
+use super::tokens::{Priority, Token, Weekday as Day, When};
+use chrono::prelude::*;
+
+use nom::{alt, apply, call, many_till, named_args, tuple, types::CompleteStr};
+
+define!(
+    week:
+    [(Token::Char, Priority(2)), "星期", Dist(0)] |
+    [(Token::Char, Priority(2)), "礼拜", Dist(0)] |
+    [(Token::Char, Priority(2)), "周", Dist(0)]
+);
+
+// days are numbered after the word "week", e.g. "星期五" is friday
+
+define!(monday: (Token::Weekday(Day::Monday), Priority(0)), "一", Dist(0));
+define!(tuesday: (Token::Weekday(Day::Tuesday), Priority(0)), "二", Dist(0));
+define!(wednesday: (Token::Weekday(Day::Wednesday), Priority(0)), "三", Dist(0));
+define!(thursday: (Token::Weekday(Day::Thursday), Priority(0)), "四", Dist(0));
+define!(friday: (Token::Weekday(Day::Friday), Priority(0)), "五", Dist(0));
+define!(saturday: (Token::Weekday(Day::Saturday), Priority(0)), "六", Dist(0));
+define!(
+    sunday:
+    [(Token::Weekday(Day::Sunday), Priority(0)), "日", Dist(0)] |
+    [(Token::Weekday(Day::Sunday), Priority(0)), "天", Dist(0)]
+);
+
+combine!(day_of_week => monday | tuesday | wednesday | thursday | friday | saturday | sunday);
+
+// longer words go first, e.g. "下个星期五" must not stop at "下"
+
+define!(
+    when:
+    [(Token::When(When::Next), Priority(1)), "下个", Dist(0)] |
+    [(Token::When(When::Next), Priority(1)), "下", Dist(0)] |
+    [(Token::When(When::Last), Priority(1)), "上个", Dist(0)] |
+    [(Token::When(When::Last), Priority(1)), "上", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "这个", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "这", Dist(0)] |
+    [(Token::When(When::This), Priority(1)), "本", Dist(0)]
+);
+
+named_args!(parse<'a>(exact_match: bool)<CompleteStr<'a>, (Vec<usize>,
+                             ( TokenDesc, TokenDesc, TokenDesc ) )>,
+    many_till!(tokenize_count_symbols,
+        alt!(
+            // 下周五, 上个星期三, 这周日
+            tuple!(apply!(when, exact_match), apply!(week, exact_match),
+                   apply!(day_of_week, exact_match)) |
+            // 星期五, 周一
+            tuple!(stub, apply!(week, exact_match), apply!(day_of_week, exact_match))
+        )
+    )
+);
+
+make_interpreter!(positions = 3);
+
+#[cfg(test)]
+mod tests {
+    use super::interpret;
+    use crate::rules::errors::ambiguous_time_error;
+    use crate::rules::rules::MatchBounds;
+    use chrono::prelude::*;
+
+    fn fixed_time() -> DateTime<Local> {
+        // 2019 1st January, Tuesday
+        Local.ymd(2019, 1, 1).and_hms(0, 0, 1)
+    }
+
+    #[test]
+    fn test_weekdays() {
+        let result = interpret("我们下周五见", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, Some(MatchBounds::new(6, 15)));
+        assert_eq!(result.get_duration_sec(), 10 * 86400);
+
+        let result = interpret("上个星期三", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -6 * 86400);
+
+        let result = interpret("这周一", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), -86400);
+
+        let result = interpret("星期五", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 3 * 86400);
+
+        let result = interpret("礼拜天", false, fixed_time()).unwrap();
+        assert_eq!(result.get_duration_sec(), 5 * 86400);
+
+        let result = interpret("周一", false, fixed_time());
+        assert_eq!(
+            result.unwrap_err().extract_error(),
+            ambiguous_time_error("周一").extract_error()
+        );
+
+        let result = interpret("一周后", false, fixed_time()).unwrap();
+        assert_eq!(result.bounds, None);
+    }
+}
@@ -0,0 +1,199 @@
+//! Typo models decide whether a word is a misspelling of a rule's word and how bad the
+//! misspelling is, the cost of typos lowers the confidence of a match. Plain edit distance is
+//! used by default, the keyboard aware model tells likely typos from random words better:
+//!
+//! ```
+//! use when::typos::Keyboard;
+//!
+//! let parser = when::Parser::new(chrono_tz::Europe::Moscow).typo_model(Keyboard);
+//! assert_eq!(parser.parse("see you next thyrsday").len(), 1);
+//! assert_eq!(parser.parse("see you next xyrsday").len(), 0);
+//! ```
+
+use std::fmt;
+use strsim::damerau_levenshtein;
+
+/// Weighs typos, the parser calls it only when fuzzy parsing is on and the rule allows typos
+/// for the word
+pub trait TypoModel: fmt::Debug + Send + Sync {
+    /// Cost of the typos which turn the pattern into the word, 0 for equal words
+    fn cost(&self, word: &str, pattern: &str) -> f64;
+
+    /// Largest cost a misspelled pattern may have, max_dist is the number of typos the rule
+    /// allows for the pattern
+    fn max_cost(&self, pattern: &str, max_dist: usize) -> f64;
+
+    /// Largest number of edits which a word within max_cost of the pattern may have, the parser
+    /// then checks only the words its vocabulary index finds close enough. None if the model
+    /// can't tell, then every word is compared with the pattern.
+    fn max_edits(&self, _pattern: &str, _max_dist: usize) -> Option<usize> {
+        None
+    }
+}
+
+/// Damerau-Levenshtein distance, every typo costs 1 and rules decide how many typos are allowed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EditDistance;
+
+impl TypoModel for EditDistance {
+    fn cost(&self, word: &str, pattern: &str) -> f64 {
+        damerau_levenshtein(word, pattern) as f64
+    }
+
+    fn max_cost(&self, _pattern: &str, max_dist: usize) -> f64 {
+        max_dist as f64
+    }
+
+    fn max_edits(&self, _pattern: &str, max_dist: usize) -> Option<usize> {
+        Some(max_dist)
+    }
+}
+
+/// Typos are weighted by how likely they are: a neighbouring key on a QWERTY keyboard, swapped,
+/// doubled or dropped letters cost less than a random letter. Allowed cost is a quarter of the
+/// pattern length, rules only tell whether typos are allowed for a word at all, e.g. they
+/// aren't for abbreviations like "sat".
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keyboard;
+
+const ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+const NEIGHBOUR_KEY: f64 = 0.5;
+const TRANSPOSITION: f64 = 0.5;
+const DOUBLED_LETTER: f64 = 0.5;
+const DROPPED_LETTER: f64 = 0.75;
+const OTHER_TYPO: f64 = 1.0;
+
+fn key_position(c: char) -> Option<(usize, usize)> {
+    ROWS.iter()
+        .enumerate()
+        .find_map(|(row, keys)| keys.find(c).map(|column| (row, column)))
+}
+
+/// Checks whether keys are next to each other in a row or in adjacent rows, lower rows are
+/// shifted to the right by half a key
+fn are_neighbours(a: char, b: char) -> bool {
+    match (key_position(a), key_position(b)) {
+        (Some((row_a, col_a)), Some((row_b, col_b))) => {
+            let ((upper, upper_col), (lower, lower_col)) = if row_a <= row_b {
+                ((row_a, col_a), (row_b, col_b))
+            } else {
+                ((row_b, col_b), (row_a, col_a))
+            };
+            match lower - upper {
+                0 => upper_col + 1 == lower_col || lower_col + 1 == upper_col,
+                1 => lower_col == upper_col || lower_col + 1 == upper_col,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+impl TypoModel for Keyboard {
+    fn cost(&self, word: &str, pattern: &str) -> f64 {
+        let word: Vec<char> = word.chars().collect();
+        let pattern: Vec<char> = pattern.chars().collect();
+
+        // a letter missing in the word, cheaper if it is one of double letters, e.g. "tomorow"
+        let dropped = |i: usize| {
+            if i > 0 && pattern[i] == pattern[i - 1] {
+                DOUBLED_LETTER
+            } else {
+                DROPPED_LETTER
+            }
+        };
+        // an extra letter in the word, cheaper if it repeats the previous one, e.g. "fridday"
+        let inserted = |j: usize| {
+            if j > 0 && word[j] == word[j - 1] {
+                DOUBLED_LETTER
+            } else {
+                OTHER_TYPO
+            }
+        };
+
+        // costs[i][j] is the cost of turning the first i letters of the pattern into the first
+        // j letters of the word
+        let mut costs = vec![vec![0.0; word.len() + 1]; pattern.len() + 1];
+        for i in 1..=pattern.len() {
+            costs[i][0] = costs[i - 1][0] + dropped(i - 1);
+        }
+        for j in 1..=word.len() {
+            costs[0][j] = costs[0][j - 1] + inserted(j - 1);
+        }
+
+        for i in 1..=pattern.len() {
+            for j in 1..=word.len() {
+                let substitution = if pattern[i - 1] == word[j - 1] {
+                    0.0
+                } else if are_neighbours(pattern[i - 1], word[j - 1]) {
+                    NEIGHBOUR_KEY
+                } else {
+                    OTHER_TYPO
+                };
+
+                let mut cost = (costs[i - 1][j - 1] + substitution)
+                    .min(costs[i - 1][j] + dropped(i - 1))
+                    .min(costs[i][j - 1] + inserted(j - 1));
+
+                if i > 1 && j > 1 && pattern[i - 1] == word[j - 2] && pattern[i - 2] == word[j - 1]
+                {
+                    cost = cost.min(costs[i - 2][j - 2] + TRANSPOSITION);
+                }
+
+                costs[i][j] = cost;
+            }
+        }
+
+        costs[pattern.len()][word.len()]
+    }
+
+    fn max_cost(&self, pattern: &str, max_dist: usize) -> f64 {
+        if max_dist == 0 {
+            0.0
+        } else {
+            pattern.chars().count() as f64 / 4.0
+        }
+    }
+
+    fn max_edits(&self, pattern: &str, max_dist: usize) -> Option<usize> {
+        // the cheapest typos cost NEIGHBOUR_KEY
+        Some((self.max_cost(pattern, max_dist) / NEIGHBOUR_KEY).floor() as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{are_neighbours, EditDistance, Keyboard, TypoModel};
+
+    #[test]
+    fn test_neighbours() {
+        assert!(are_neighbours('q', 'w'));
+        assert!(are_neighbours('y', 'h'));
+        assert!(are_neighbours('s', 'z'));
+        assert!(!are_neighbours('q', 'p'));
+        assert!(!are_neighbours('a', 'x'));
+        assert!(!are_neighbours('ä', 'a'));
+    }
+
+    #[test]
+    fn test_keyboard() {
+        assert_eq!(Keyboard.cost("thursday", "thursday"), 0.0);
+        // neighbouring key
+        assert_eq!(Keyboard.cost("thyrsday", "thursday"), 0.5);
+        // dropped and doubled letters
+        assert_eq!(Keyboard.cost("tursday", "thursday"), 0.75);
+        assert_eq!(Keyboard.cost("tomorow", "tomorrow"), 0.5);
+        assert_eq!(Keyboard.cost("fridday", "friday"), 0.5);
+        // transposition
+        assert_eq!(Keyboard.cost("thrusday", "thursday"), 0.5);
+        // random letters
+        assert_eq!(Keyboard.cost("xyrsday", "thursday"), 2.25);
+        assert_eq!(EditDistance.cost("xyrsday", "thursday"), 3.0);
+
+        assert_eq!(Keyboard.max_cost("thursday", 2), 2.0);
+        assert_eq!(Keyboard.max_cost("sat", 0), 0.0);
+        assert_eq!(Keyboard.max_edits("thursday", 2), Some(4));
+        assert_eq!(EditDistance.max_edits("thursday", 2), Some(2));
+    }
+}
@@ -1,6 +1,14 @@
-use chrono::{offset::TimeZone, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{offset::TimeZone, DateTime, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
 use std::str::FromStr;
 use when;
+use when::aliases::{chat_slang, Alias, Token, When};
+use when::data_rules::RuleSet;
+use when::explain::RemovalReason;
+use when::holidays::{Calendar, HolidayCalendar, HolidayDate, YearPolicy};
+use when::languages::{Language, LanguagePolicy};
+use when::parser::WarningKind;
+use when::rule::{recognize_phrase, words, Context, HolidayRef, Period, Rule, RuleMatch, Span};
+use when::typos::{EditDistance, Keyboard, TypoModel};
 use when::Parser;
 
 fn fixed_time() -> NaiveDateTime {
@@ -11,7 +19,7 @@ fn fixed_time() -> NaiveDateTime {
 }
 
 fn assert_date_time<'a, Tz: TimeZone>(
-    parser: Parser<'a, Tz>,
+    parser: Parser<Tz>,
     input: &'a str,
     expected_date_time_strs: &[&str],
     expected_length: usize,
@@ -49,6 +57,45 @@ fn test_merge_parse_results() {
         &["2018-08-06T18:00:00"],
         1,
     );
+
+    // connective words link a date with a time however long they are
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "tomorrow, some time around 5pm",
+        &["2018-08-04T17:00:00"],
+        1,
+    );
+
+    // different dates are not merged
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "next monday and next tuesday at 5pm",
+        &["2018-08-06T15:34:00", "2018-08-07T17:00:00"],
+        2,
+    );
+
+    // exact time refines a part of the day
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "tonight at 11:10 pm", &["2018-08-03T23:10:00"], 1);
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "tomorrow evening at 7pm",
+        &["2018-08-04T19:00:00"],
+        1,
+    );
+
+    // linked matches which conflict are reported
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_eq!(
+        parser.parse_fixed_time(fixed_time(), "tomorrow at 5pm at 6pm"),
+        vec![Err(when::DateTimeError::ConflictError {
+            text: "tomorrow at 5pm at 6pm".to_owned(),
+            what: "hour".to_owned()
+        })]
+    );
 }
 
 #[test]
@@ -70,6 +117,7 @@ fn test_multiple_results() {
 fn test_overlap_error() {
     let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
 
+    // "march 13" and "13 seconds ago" intersect, the longer match wins
     let res = parser.parse_fixed_time(
         fixed_time(),
         "Call me next march 13 seconds ago, something else, next monday",
@@ -77,11 +125,12 @@ fn test_overlap_error() {
 
     assert_eq!(res.len(), 2);
 
+    let naive = chrono::NaiveDateTime::from_str("2018-08-03T15:34:43").unwrap();
     assert_eq!(
         res[0],
-        Err(when::DateTimeError::IntersectionError {
-            text: "march 13 seconds ago".to_owned()
-        })
+        Ok(chrono_tz::Europe::Moscow
+            .from_local_datetime(&naive)
+            .unwrap())
     );
 
     let naive = chrono::NaiveDateTime::from_str("2018-08-06T15:34:00").unwrap();
@@ -93,6 +142,68 @@ fn test_overlap_error() {
     );
 }
 
+#[test]
+fn test_alternatives() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+
+    let alternatives = parser.alternatives_fixed_time(fixed_time(), "next march 13 seconds ago");
+    let found: Vec<_> = alternatives
+        .iter()
+        .map(|a| (a.start_idx, a.end_idx, a.selected))
+        .collect();
+    assert_eq!(found, vec![(5, 13, false), (11, 25, true)]);
+    assert!(alternatives[1].score > alternatives[0].score);
+
+    let naive = chrono::NaiveDateTime::from_str("2018-03-13T15:34:00").unwrap();
+    assert_eq!(
+        alternatives[0].result,
+        Ok(chrono_tz::Europe::Moscow
+            .from_local_datetime(&naive)
+            .unwrap())
+    );
+
+    // a rule goes on scanning after its match, so "friday" inside "next friday" is not its
+    // candidate
+    let alternatives = parser.alternatives_fixed_time(fixed_time(), "next friday 5pm");
+    let found: Vec<_> = alternatives
+        .iter()
+        .map(|a| (a.start_idx, a.end_idx, a.selected))
+        .collect();
+    assert_eq!(found, vec![(0, 11, true), (12, 15, true)]);
+}
+
+#[test]
+fn test_adjacent_matches() {
+    // the same time found with and without the separator before it is one candidate
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "5pm 6pm",
+        &["2018-08-03T17:00:00", "2018-08-03T18:00:00"],
+        2,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "tomorrow 5pm, 6pm",
+        &["2018-08-04T17:00:00", "2018-08-03T18:00:00"],
+        2,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    let input = "10am, 11am or 12pm";
+    let res = parser.parse_matches_fixed_time(fixed_time(), input);
+    let found: Vec<_> = res
+        .iter()
+        .map(|m| {
+            let m = m.as_ref().unwrap();
+            &input[m.start_idx..m.end_idx]
+        })
+        .collect();
+    assert_eq!(found, vec!["10am", "11am", "12pm"]);
+}
+
 #[test]
 fn test_seconds_delta() {
     let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
@@ -114,4 +225,1288 @@ fn test_various_requests() {
 
     let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
     assert_date_time(parser, "in 1 hour", &["2018-08-03T16:34:00"], 1);
+
+    // it's 02:30 on March 30th in Berlin, clocks are moved forward at 02:00 the next day
+    let now = NaiveDateTime::from_str("2019-03-30T01:30:00").unwrap();
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Berlin);
+    assert_eq!(
+        parser.parse_fixed_time(now, "march 31"),
+        vec![Err(when::DateTimeError::InvalidTime {
+            text: "march 31".to_owned(),
+            what: "hour".to_owned(),
+            value: 2,
+        })]
+    );
+}
+
+#[test]
+fn test_week_numbers() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "let's meet in week 42", &["2018-10-15T00:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "W42-3 at 2pm", &["2018-10-17T14:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "day 100 of 2020", &["2020-04-09T15:34:00"], 1);
+
+    // week 3 of this year has passed already
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "see you next week 3", &["2019-01-14T00:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).week_start(Weekday::Sun);
+    let res = parser.parse_range_fixed_time(fixed_time(), "CW1 2019");
+    let start = chrono::NaiveDateTime::from_str("2018-12-30T00:00:00").unwrap();
+    let end = chrono::NaiveDateTime::from_str("2019-01-06T00:00:00").unwrap();
+    assert_eq!(
+        res,
+        vec![Ok((
+            chrono_tz::Europe::Moscow
+                .from_local_datetime(&start)
+                .unwrap(),
+            chrono_tz::Europe::Moscow.from_local_datetime(&end).unwrap()
+        ))]
+    );
+
+    // 2020 has 53 weeks but 2019 doesn't, the year is merged from another match
+    let now = NaiveDateTime::from_str("2020-06-01T12:00:00").unwrap();
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    let res = parser.parse_fixed_time(now, "w53 of 10/03/2019");
+    assert_eq!(
+        res,
+        vec![Err(when::DateTimeError::InvalidTime {
+            text: "w53 of 10/03/2019".to_owned(),
+            what: "week".to_owned(),
+            value: 53,
+        })]
+    );
+}
+
+#[test]
+fn test_quarters() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "Q3 results", &["2018-07-01T00:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "next quarter", &["2018-10-01T00:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "by the end of Q2", &["2018-06-30T00:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .fiscal_year_start(10)
+        .unwrap();
+    assert_date_time(parser, "Q1 FY19", &["2018-10-01T00:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .fiscal_year_start(10)
+        .unwrap();
+    assert_date_time(parser, "last quarter", &["2018-04-01T00:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .fiscal_year_start(10)
+        .unwrap();
+    let res = parser.parse_range_fixed_time(fixed_time(), "H2 2018");
+    let start = chrono::NaiveDateTime::from_str("2018-04-01T00:00:00").unwrap();
+    let end = chrono::NaiveDateTime::from_str("2018-10-01T00:00:00").unwrap();
+    assert_eq!(
+        res,
+        vec![Ok((
+            chrono_tz::Europe::Moscow
+                .from_local_datetime(&start)
+                .unwrap(),
+            chrono_tz::Europe::Moscow.from_local_datetime(&end).unwrap()
+        ))]
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .fiscal_year_start(10)
+        .unwrap();
+    let res = parser.parse_fixed_time(fixed_time(), "q4 fy99999999");
+    assert_eq!(
+        res,
+        vec![Err(when::DateTimeError::InvalidTime {
+            text: "q4 fy99999999".to_owned(),
+            what: "year".to_owned(),
+            value: 99999999,
+        })]
+    );
+
+    let res = when::parser::Parser::new(chrono_tz::Europe::Moscow).fiscal_year_start(13);
+    assert_eq!(
+        res.err(),
+        Some(when::parser::ConfigError::FiscalYearStart { month: 13 })
+    );
+}
+
+#[test]
+fn test_holidays() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "see you on Christmas", &["2018-12-25T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "the day after Thanksgiving at 10am",
+        &["2018-11-23T10:00:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "last christmas", &["2017-12-25T15:34:00"], 1);
+
+    let parser =
+        when::parser::Parser::new(chrono_tz::Europe::Moscow).year_policy(YearPolicy::Future);
+    assert_date_time(parser, "on Easter Monday", &["2019-04-22T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .year_policy(YearPolicy::Future)
+        .holiday_calendar(Box::new(
+            Calendar::new().holiday(&["founders day"], HolidayDate::Fixed { month: 6, day: 12 }),
+        ));
+    assert_date_time(
+        parser,
+        "closed on Founders Day",
+        &["2019-06-12T15:34:00"],
+        1,
+    );
+
+    // words of custom holidays are recognized with typos as well as the default ones
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .year_policy(YearPolicy::Future)
+        .holiday_calendar(Box::new(
+            Calendar::new().holiday(&["founders day"], HolidayDate::Fixed { month: 6, day: 12 }),
+        ));
+    assert_date_time(parser, "next Foundrs Day", &["2019-06-12T15:34:00"], 1);
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "last chrismas", &["2017-12-25T15:34:00"], 1);
+}
+
+#[test]
+fn test_business_days() {
+    // 2018-08-03 is Friday
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "in 3 business days", &["2018-08-08T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "next working day", &["2018-08-06T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "2 workdays ago", &["2018-08-01T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "by the end of business day",
+        &["2018-08-03T17:00:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "eod tomorrow", &["2018-08-06T17:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .weekend(&[Weekday::Fri, Weekday::Sat])
+        .unwrap();
+    assert_date_time(parser, "next working day", &["2018-08-05T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).days_off_calendar(Box::new(
+        Calendar::new().holiday(&["company day"], HolidayDate::Fixed { month: 8, day: 6 }),
+    ));
+    assert_date_time(
+        parser,
+        "within 2 business days",
+        &["2018-08-08T15:34:00"],
+        1,
+    );
+
+    // whole weeks are skipped at once, days off within them are still counted
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "in 100 business days", &["2018-12-21T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "100 workdays ago", &["2018-03-16T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).days_off_calendar(Box::new(
+        Calendar::new()
+            .holiday(&["company day"], HolidayDate::Fixed { month: 8, day: 6 })
+            .holiday(&["retreat"], HolidayDate::Fixed { month: 10, day: 1 }),
+    ));
+    assert_date_time(parser, "in 100 business days", &["2018-12-25T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    let res = parser.parse_fixed_time(fixed_time(), "in 99999999 business days");
+    assert_eq!(
+        res,
+        vec![Err(when::DateTimeError::InvalidTime {
+            text: "in 99999999 business days".to_owned(),
+            what: "number".to_owned(),
+            value: 99999999,
+        })]
+    );
+
+    // repeated weekend days are counted once
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .weekend(&[Weekday::Sat, Weekday::Sat])
+        .unwrap();
+    assert_date_time(parser, "in 6 business days", &["2018-08-10T15:34:00"], 1);
+
+    let res = when::parser::Parser::new(chrono_tz::Europe::Moscow).weekend(&[
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]);
+    assert_eq!(res.err(), Some(when::parser::ConfigError::NoBusinessDays));
+
+    // days off calendars may leave no business days
+    let parser =
+        when::parser::Parser::new(chrono_tz::Europe::Moscow).days_off_calendar(Box::new(Closed));
+    let res = parser.parse_fixed_time(fixed_time(), "in 2 business days");
+    assert_eq!(
+        res,
+        vec![Err(when::DateTimeError::InvalidTime {
+            text: "in 2 business days".to_owned(),
+            what: "business days".to_owned(),
+            value: 2,
+        })]
+    );
+}
+
+/// Calendar of a business which is closed for good
+struct Closed;
+
+impl HolidayCalendar for Closed {
+    fn names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn date(&self, _name: &str, _year: i32) -> Option<NaiveDate> {
+        None
+    }
+
+    fn is_holiday(&self, _date: NaiveDate) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_anchored_offsets() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "the day after tomorrow",
+        &["2018-08-05T15:34:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "3 days after Christmas",
+        &["2018-12-28T15:34:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "2 weeks before March 1st",
+        &["2018-02-15T15:34:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "a week from Friday", &["2018-08-10T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(
+        parser,
+        "2 days prior to 25/12/2018",
+        &["2018-12-23T15:34:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "in 3 days from now", &["2018-08-06T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "an hour before noon", &["2018-08-03T11:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "3 days after the launch", &[], 0);
+
+    // offsets out of range are errors rather than panics
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    let invalid = |text: &str, what: &str, value| {
+        Err(when::DateTimeError::InvalidTime {
+            text: text.to_owned(),
+            what: what.to_owned(),
+            value,
+        })
+    };
+    let res = parser.parse_fixed_time(fixed_time(), "99999999 minutes after christmas");
+    assert_eq!(
+        res[0],
+        invalid("99999999 minutes after", "number", 99999999)
+    );
+    let res = parser.parse_fixed_time(fixed_time(), "999999 years after christmas");
+    assert_eq!(
+        res,
+        vec![invalid("999999 years after christmas", "months", 11999988)]
+    );
+    let res = parser.parse_fixed_time(fixed_time(), "99999999 months before christmas");
+    assert_eq!(
+        res,
+        vec![invalid(
+            "99999999 months before christmas",
+            "months",
+            -99999999
+        )]
+    );
+}
+
+/// Recognizes a fixed phrase as a fixed date
+struct Milestone(&'static [&'static str], (i32, i32, i32));
+
+impl<Tz: TimeZone> Rule<Tz> for Milestone {
+    fn name(&self) -> &str {
+        "milestone"
+    }
+
+    fn apply(&self, input: &str, exact_match: bool, _now: &DateTime<Tz>) -> Vec<RuleMatch> {
+        let (year, month, day) = self.1;
+        words(input)
+            .iter()
+            .filter_map(|word| {
+                let (len, _) = recognize_phrase(&input[word.start_idx..], self.0, exact_match)?;
+                let mut context = Context::default();
+                context.year = Some(year);
+                context.month = Some(month);
+                context.day = Some(day);
+                Some(RuleMatch::new(
+                    word.start_idx,
+                    word.start_idx + len,
+                    Ok(context),
+                ))
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_custom_rules() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .with_rule(Box::new(Milestone(&["release", "freeze"], (2018, 9, 14))));
+    assert_date_time(
+        parser,
+        "let's meet after the release freze at 10am",
+        &["2018-09-14T10:00:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .with_rule(Box::new(Milestone(&["release", "freeze"], (2018, 9, 14))));
+    assert_date_time(
+        parser,
+        "2 days before the release freeze",
+        &["2018-09-12T15:34:00"],
+        1,
+    );
+}
+
+/// Recognizes the word "deadline" as the given context
+struct Deadline(Context);
+
+impl<Tz: TimeZone> Rule<Tz> for Deadline {
+    fn name(&self) -> &str {
+        "deadline"
+    }
+
+    fn apply(&self, input: &str, _exact_match: bool, _now: &DateTime<Tz>) -> Vec<RuleMatch> {
+        input
+            .find("deadline")
+            .map(|start_idx| RuleMatch::new(start_idx, start_idx + 8, Ok(self.0)))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[test]
+fn test_custom_rule_validation() {
+    let invalid = |what: &str, value| {
+        vec![Err(when::DateTimeError::InvalidTime {
+            text: "deadline".to_owned(),
+            what: what.to_owned(),
+            value,
+        })]
+    };
+    let check = |set: &dyn Fn(&mut Context), what: &str, value| {
+        let mut context = Context::default();
+        set(&mut context);
+        let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+            .with_rule(Box::new(Deadline(context)));
+        assert_eq!(
+            parser.parse_fixed_time(fixed_time(), "the deadline"),
+            invalid(what, value)
+        );
+    };
+
+    check(&|ctx| ctx.month = Some(13), "month", 13);
+    check(&|ctx| ctx.hour = Some(99), "hour", 99);
+    check(&|ctx| ctx.minute = Some(-1), "minute", -1);
+    check(&|ctx| ctx.week = Some(60), "week", 60);
+    check(&|ctx| ctx.period = Some(Period::Quarter(7)), "quarter", 7);
+    check(
+        &|ctx| ctx.business_days = Some(i32::MAX),
+        "business days",
+        i32::MAX,
+    );
+    check(
+        &|ctx| ctx.span = Some(Span::Days(i64::MAX)),
+        "days",
+        i32::MAX,
+    );
+    check(
+        &|ctx| ctx.period = Some(Period::QuarterShift(i32::MAX)),
+        "year",
+        2018,
+    );
+    check(
+        &|ctx| {
+            ctx.holiday = Some(HolidayRef {
+                id: 999,
+                policy: None,
+                day_offset: 0,
+            })
+        },
+        "holiday",
+        999,
+    );
+    check(
+        &|ctx| {
+            ctx.month = Some(2);
+            ctx.day = Some(30);
+        },
+        "day",
+        30,
+    );
+}
+
+#[test]
+fn test_rule_selection() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "add 3/4 cup of flour", &["2018-04-03T15:34:00"], 1);
+
+    let parser =
+        when::parser::Parser::new(chrono_tz::Europe::Moscow).exclude_rules(&["common::slash_dmy"]);
+    assert_date_time(parser, "add 3/4 cup of flour", &[], 0);
+
+    let parser =
+        when::parser::Parser::new(chrono_tz::Europe::Moscow).exclude_rules(&["en::time_of_day"]);
+    assert_date_time(parser, "good morning", &[], 0);
+
+    let parser =
+        when::parser::Parser::new(chrono_tz::Europe::Moscow).exclude_rules(&["en::time_of_day"]);
+    assert_date_time(parser, "tomorrow morning", &["2018-08-04T08:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .exclude_rules(&["en"])
+        .include_rules(&["en::weekdays"]);
+    assert_date_time(parser, "friday at 5pm", &["2018-08-03T15:34:00"], 1);
+}
+
+#[test]
+fn test_rule_priorities() {
+    // "review tomorrow" and "evening" cover more words than "tomorrow evening"
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .with_rule(Box::new(Milestone(&["review", "tomorrow"], (2018, 9, 14))));
+    assert_date_time(
+        parser,
+        "sprint review tomorrow evening",
+        &["2018-09-14T18:00:00"],
+        1,
+    );
+
+    // equally good intersecting matches are ambiguous
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .with_rule(Box::new(Milestone(&["tomorrow", "evening"], (2018, 9, 14))));
+    let res = parser.parse_fixed_time(fixed_time(), "sprint review tomorrow evening");
+    assert_eq!(
+        res,
+        vec![Err(when::DateTimeError::IntersectionError {
+            text: "tomorrow evening".to_owned()
+        })]
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .with_rule(Box::new(Milestone(&["review", "tomorrow"], (2018, 9, 14))))
+        .rule_priority("milestone", 1);
+    assert_date_time(
+        parser,
+        "sprint review tomorrow evening",
+        &["2018-09-14T18:00:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .with_rule(Box::new(Milestone(&["review", "tomorrow"], (2018, 9, 14))))
+        .rule_priority("en", 1);
+    assert_date_time(
+        parser,
+        "sprint review tomorrow evening",
+        &["2018-08-04T18:00:00"],
+        1,
+    );
+}
+
+#[test]
+fn test_aliases() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "see you tmrw", &[], 0);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).aliases(chat_slang());
+    assert_date_time(parser, "see you tmrw", &["2018-08-04T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).aliases(chat_slang());
+    assert_date_time(parser, "tuesday nxt wk", &["2018-08-07T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).aliases(chat_slang());
+    assert_date_time(parser, "in 2 hrs", &["2018-08-03T17:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).aliases(chat_slang());
+    assert_date_time(parser, "next weds arvo", &["2018-08-08T15:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).alias(Alias::new(
+        "morgen",
+        Token::When(When::Tomorrow),
+        1,
+    ));
+    assert_date_time(parser, "morgn at 10am", &["2018-08-04T10:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow)
+        .fuzzy_parse(false)
+        .alias(Alias::new("morgen", Token::When(When::Tomorrow), 1));
+    assert_date_time(parser, "morgn at 10am", &["2018-08-03T10:00:00"], 1);
+}
+
+#[test]
+fn test_data_rules() {
+    let rules = || RuleSet::from_file("tests/data/de.toml").unwrap();
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Berlin).data_rules(rules());
+    assert_date_time(parser, "in 3 tagen", &["2018-08-06T14:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Berlin).data_rules(rules());
+    assert_date_time(parser, "morgen am abend", &["2018-08-04T19:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Berlin).data_rules(rules());
+    assert_date_time(
+        parser,
+        "nächsten montg um 10am",
+        &["2018-08-06T10:00:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Berlin)
+        .data_rules(rules())
+        .exclude_rules(&["de"]);
+    assert_date_time(parser, "morgen", &[], 0);
+}
+
+#[test]
+fn test_russian() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::ru));
+    assert_date_time(
+        parser,
+        "Позвони мне завтра вечером",
+        &["2018-08-04T18:00:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::ru));
+    assert_date_time(
+        parser,
+        "в следующую пятницу в 15:30",
+        &["2018-08-10T15:30:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::ru));
+    assert_date_time(
+        parser,
+        "это было 3 часа назад, ответ через 5 минут",
+        &["2018-08-03T12:34:00", "2018-08-03T15:39:00"],
+        2,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::ru));
+    assert_date_time(parser, "8 марта в 12 часов", &["2018-03-08T12:00:00"], 1);
+
+    // declined forms are recognized exactly, not as typos in the nominative ones
+    let parser = Parser::new(chrono_tz::Europe::Moscow).languages(&[Language::Russian]);
+    let confidence = |input| {
+        parser.parse_matches_fixed_time(fixed_time(), input)[0]
+            .as_ref()
+            .unwrap()
+            .confidence
+    };
+    // a weekday alone is a single token match whatever its form
+    assert_eq!(confidence("в субботу"), confidence("суббота"));
+    for input in &[
+        "5 января",
+        "в субботу",
+        "в следующую субботу",
+        "через 5 минут",
+    ] {
+        let res = parser.parse_matches_fixed_time(fixed_time(), input);
+        let m = res[0].as_ref().unwrap();
+        if *input != "в субботу" {
+            assert_eq!((input, m.confidence), (input, 1.0));
+        }
+        assert!(
+            m.warnings
+                .iter()
+                .all(|warning| !matches!(warning.kind, WarningKind::TypoCorrected { .. })),
+            "{}: {:?}",
+            input,
+            m.warnings
+        );
+    }
+}
+
+#[test]
+fn test_portuguese() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::br));
+    assert_date_time(
+        parser,
+        "Me liga amanhã à noite",
+        &["2018-08-04T18:00:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::br));
+    assert_date_time(
+        parser,
+        "na próxima sexta às 15h30",
+        &["2018-08-10T15:30:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::br));
+    assert_date_time(
+        parser,
+        "ele ligou há 3 horas, entrega dentro de 2 dias",
+        &["2018-08-03T12:34:00", "2018-08-05T15:34:00"],
+        2,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::br));
+    assert_date_time(
+        parser,
+        "5 de março às 10 horas",
+        &["2018-03-05T10:00:00"],
+        1,
+    );
+}
+
+#[test]
+fn test_german() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::de));
+    assert_date_time(
+        parser,
+        "nächsten Dienstag um 14 Uhr",
+        &["2018-08-07T14:00:00"],
+        1,
+    );
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::de));
+    assert_date_time(parser, "in drei Tagen", &["2018-08-06T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::de));
+    assert_date_time(parser, "morgen früh um 8:30", &["2018-08-04T08:30:00"], 1);
+}
+
+#[test]
+fn test_french() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::fr));
+    assert_date_time(parser, "mardi prochain à 14h", &["2018-08-07T14:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::fr));
+    assert_date_time(parser, "il y a deux jours", &["2018-08-01T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::fr));
+    assert_date_time(parser, "le 1er mai à midi", &["2018-05-01T12:00:00"], 1);
+}
+
+#[test]
+fn test_spanish() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::es));
+    assert_date_time(parser, "el próximo martes", &["2018-08-07T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::es));
+    assert_date_time(parser, "hace dos días", &["2018-08-01T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::es));
+    assert_date_time(
+        parser,
+        "la reunión es a las 3 de la tarde",
+        &["2018-08-03T15:00:00"],
+        1,
+    );
+}
+
+#[test]
+fn test_inflected_forms() {
+    // plural and inflected forms are recognized exactly, not as typos in the singular ones
+    let cases = [
+        (Language::German, "vor 2 Stunden"),
+        (Language::German, "in 5 Minuten"),
+        (Language::German, "vor 3 Wochen"),
+        (Language::French, "il y a 3 heures"),
+        (Language::French, "dans 2 jours"),
+        (Language::French, "dans 5 semaines"),
+        (Language::Spanish, "en 3 días"),
+        (Language::Spanish, "en 3 dias"),
+        (Language::Spanish, "hace 2 horas"),
+        (Language::Spanish, "dentro de 4 meses"),
+    ];
+    for (language, input) in &cases {
+        let parser = Parser::new(chrono_tz::Europe::Moscow).languages(&[*language]);
+        let res = parser.parse_matches_fixed_time(fixed_time(), input);
+        let m = res[0].as_ref().unwrap();
+        assert_eq!((input, m.confidence), (input, 1.0));
+        assert!(m.warnings.is_empty(), "{}: {:?}", input, m.warnings);
+    }
+}
+
+#[test]
+fn test_chinese() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::zh));
+    assert_date_time(parser, "明天下午3点", &["2018-08-04T15:00:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::zh));
+    assert_date_time(parser, "下周五晚上八点半", &["2018-08-10T20:30:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::zh));
+    assert_date_time(parser, "3天后", &["2018-08-06T15:34:00"], 1);
+}
+
+#[test]
+fn test_japanese() {
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::ja));
+    assert_date_time(parser, "来週の金曜日", &["2018-08-10T15:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::ja));
+    assert_date_time(parser, "2時間前", &["2018-08-03T13:34:00"], 1);
+
+    let parser = when::parser::Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::ja));
+    assert_date_time(parser, "令和5年4月1日", &["2023-04-01T15:34:00"], 1);
+}
+
+#[test]
+fn test_multiple_languages() {
+    let languages = [Language::English, Language::Spanish];
+    let local = |s| {
+        let naive = chrono::NaiveDateTime::from_str(s).unwrap();
+        chrono_tz::Europe::Moscow
+            .from_local_datetime(&naive)
+            .unwrap()
+    };
+
+    let parser = Parser::new(chrono_tz::Europe::Moscow).languages(&languages);
+    let res =
+        parser.parse_matches_fixed_time(fixed_time(), "tomorrow at 5pm, y luego el 5 de marzo");
+    assert_eq!(res.len(), 2);
+    let first = res[0].as_ref().unwrap();
+    assert_eq!(first.date_time, local("2018-08-04T17:00:00"));
+    assert_eq!((first.start_idx, first.end_idx), (0, 15));
+    assert_eq!(first.language, Some(Language::English));
+    let second = res[1].as_ref().unwrap();
+    assert_eq!(second.date_time, local("2018-03-05T15:34:00"));
+    assert_eq!(second.language, Some(Language::Spanish));
+
+    // "a las 5" and "5 pm" intersect, english goes first and wins, "a las" links it with "hoy"
+    let parser = Parser::new(chrono_tz::Europe::Moscow).languages(&languages);
+    assert_date_time(parser, "hoy a las 5 pm", &["2018-08-03T17:00:00"], 1);
+
+    let parser = Parser::new(chrono_tz::Europe::Moscow)
+        .languages(&languages)
+        .language_policy(LanguagePolicy::Longest);
+    assert_date_time(parser, "hoy a las 5 pm", &["2018-08-03T05:00:00"], 1);
+
+    let parser = Parser::new(chrono_tz::Europe::Moscow)
+        .languages(&languages)
+        .language_policy(LanguagePolicy::Strict);
+    // "hoy" is kept along with the error
+    let res = parser.parse_fixed_time(fixed_time(), "hoy a las 5 pm");
+    assert_eq!(res.len(), 2);
+    assert_eq!(
+        res[1],
+        Err(when::DateTimeError::IntersectionError {
+            text: "a las 5 pm".to_owned()
+        })
+    );
+
+    // only spanish rules are applied to a spanish message
+    let parser = Parser::new(chrono_tz::Europe::Moscow)
+        .languages(&languages)
+        .detect_language(true);
+    let res = parser.parse_matches_fixed_time(fixed_time(), "nos vemos hoy a las 5 pm");
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].as_ref().unwrap().language, Some(Language::Spanish));
+    assert_eq!(
+        res[0].as_ref().unwrap().date_time,
+        local("2018-08-03T05:00:00")
+    );
+
+    // shared rules are registered once and have no language
+    let parser = Parser::new(chrono_tz::Europe::Moscow).languages(&[
+        Language::German,
+        Language::French,
+        Language::Spanish,
+    ]);
+    let res = parser.parse_matches_fixed_time(fixed_time(), "17:45");
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].as_ref().unwrap().language, None);
+
+    // an empty list keeps the current languages
+    let parser = Parser::new(chrono_tz::Europe::Moscow)
+        .languages(&[Language::Spanish])
+        .languages(&[]);
+    let res = parser.parse_matches_fixed_time(fixed_time(), "mañana");
+    assert_eq!(res[0].as_ref().unwrap().language, Some(Language::Spanish));
+    let parser = Parser::new(chrono_tz::Europe::Moscow).languages(&[]);
+    assert_date_time(parser, "tomorrow at 5pm", &["2018-08-04T17:00:00"], 1);
+}
+
+#[test]
+fn test_weak_words() {
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    for input in &[
+        "you may go",
+        "march forward",
+        "I sat down",
+        "sun is out",
+        "wait a second",
+    ] {
+        assert!(parser.parse_fixed_time(fixed_time(), input).is_empty());
+    }
+
+    // numbers, prepositions and other dates make them dates
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "may 5", &["2018-05-05T15:34:00"], 1);
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "see you in march", &["2018-03-01T15:34:00"], 1);
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "next sun", &["2018-08-05T15:34:00"], 1);
+
+    // support spreads along neighbouring weak words
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    let res = parser.parse_matches_fixed_time(fixed_time(), "on sat and sun");
+    let bounds: Vec<_> = res
+        .iter()
+        .map(|m| (m.as_ref().unwrap().start_idx, m.as_ref().unwrap().end_idx))
+        .collect();
+    assert_eq!(bounds, vec![(3, 6), (11, 14)]);
+
+    let res = parser.parse_matches_fixed_time(fixed_time(), "on sat");
+    // single word supported by the context
+    assert!((res[0].as_ref().unwrap().confidence - 0.72).abs() < 1e-9);
+    let res = parser.parse_matches_fixed_time(fixed_time(), "next friday");
+    assert_eq!(res[0].as_ref().unwrap().confidence, 1.0);
+
+    // the threshold can be lowered to keep everything
+    let parser = Parser::new(chrono_tz::Europe::Moscow).min_confidence(0.0);
+    let res = parser.parse_matches_fixed_time(fixed_time(), "I sat down");
+    assert_eq!(res.len(), 1);
+    let m = res[0].as_ref().unwrap();
+    assert_eq!((m.start_idx, m.end_idx), (2, 5));
+    assert!((m.confidence - 0.27).abs() < 1e-9);
+}
+
+#[test]
+fn test_confidence() {
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    let confidence = |input| -> Vec<f64> {
+        parser
+            .parse_matches_fixed_time(fixed_time(), input)
+            .into_iter()
+            .map(|m| m.unwrap().confidence)
+            .collect()
+    };
+
+    assert_eq!(confidence("next friday at 5pm"), vec![1.0]);
+    assert_eq!(confidence("friday"), vec![0.9]);
+
+    // typos in longer expressions cost less
+    let typo = confidence("fridy")[0];
+    let typo_in_context = confidence("next fridy")[0];
+    assert!(typo < typo_in_context && typo_in_context < 1.0);
+
+    // "moon" is too short to be a typo in "noon"
+    assert!(confidence("look at the moon").is_empty());
+
+    let parser = Parser::new(chrono_tz::Europe::Moscow).min_confidence(0.7);
+    assert!(parser.parse_fixed_time(fixed_time(), "fridy").is_empty());
+    assert_eq!(parser.parse_fixed_time(fixed_time(), "next fridy").len(), 1);
+
+    let parser = Parser::new(chrono_tz::Europe::Moscow).min_confidence(0.0);
+    assert_date_time(parser, "look at the moon", &["2018-08-03T12:00:00"], 1);
+}
+
+#[test]
+fn test_typo_models() {
+    let confidence = |parser: &Parser<chrono_tz::Tz>, input| {
+        parser.parse_matches_fixed_time(fixed_time(), input)[0]
+            .as_ref()
+            .unwrap()
+            .confidence
+    };
+
+    // every typo costs the same by default
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    assert_eq!(
+        confidence(&parser, "next thyrsday"),
+        confidence(&parser, "next thzrsday")
+    );
+
+    // "y" is next to "u" on a keyboard, "z" is not
+    let parser = Parser::new(chrono_tz::Europe::Moscow).typo_model(Keyboard);
+    assert!(confidence(&parser, "next thyrsday") > confidence(&parser, "next thzrsday"));
+    assert_eq!(
+        parser.parse_fixed_time(fixed_time(), "next xyrsday").len(),
+        0
+    );
+
+    // custom model which doesn't tolerate typos at all
+    #[derive(Debug)]
+    struct NoTypos;
+
+    impl TypoModel for NoTypos {
+        fn cost(&self, word: &str, pattern: &str) -> f64 {
+            if word == pattern {
+                0.0
+            } else {
+                1.0
+            }
+        }
+
+        fn max_cost(&self, _pattern: &str, _max_dist: usize) -> f64 {
+            0.0
+        }
+    }
+
+    let parser = Parser::new(chrono_tz::Europe::Moscow).typo_model(NoTypos);
+    assert!(parser
+        .parse_fixed_time(fixed_time(), "next fridy")
+        .is_empty());
+    assert_eq!(
+        parser.parse_fixed_time(fixed_time(), "next friday").len(),
+        1
+    );
+
+    // variants of a word are chosen by the model too, "lext" is one typo away from "next" and
+    // two from "last", but the model finds the latter cheaper
+    #[derive(Debug)]
+    struct PreferLast;
+
+    impl TypoModel for PreferLast {
+        fn cost(&self, word: &str, pattern: &str) -> f64 {
+            match EditDistance.cost(word, pattern) {
+                cost if cost > 0.0 && pattern == "last" => cost / 4.0,
+                cost => cost,
+            }
+        }
+
+        fn max_cost(&self, pattern: &str, max_dist: usize) -> f64 {
+            EditDistance.max_cost(pattern, max_dist)
+        }
+    }
+
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    assert_date_time(parser, "lext working day", &["2018-08-06T15:34:00"], 1);
+
+    let parser = Parser::new(chrono_tz::Europe::Moscow).typo_model(PreferLast);
+    assert_date_time(parser, "lext working day", &["2018-08-02T15:34:00"], 1);
+}
+
+#[test]
+fn test_diagnostics() {
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+
+    let res = parser.parse_matches_fixed_time(fixed_time(), "meet me on 30th of february");
+    let diagnostic = res[0].as_ref().unwrap_err();
+    assert_eq!(diagnostic.code(), "invalid_time");
+    assert_eq!((diagnostic.start_idx, diagnostic.end_idx), (11, 27));
+    assert_eq!(diagnostic.token, "30th");
+    assert_eq!(diagnostic.suggestions, vec!["28th of february"]);
+    assert_eq!(
+        diagnostic.to_string(),
+        "invalid time in: 30th of february, day can't be 30, did you mean 28th of february?"
+    );
+
+    // the current day is friday
+    let res = parser.parse_matches_fixed_time(fixed_time(), "this mondy");
+    let diagnostic = res[0].as_ref().unwrap_err();
+    assert_eq!(diagnostic.code(), "ambiguous_time");
+    assert_eq!(diagnostic.suggestions, vec!["next monday", "last monday"]);
+
+    let res = parser.parse_matches_fixed_time(fixed_time(), "tomorrow at 5pm at 6pm");
+    let diagnostic = res[0].as_ref().unwrap_err();
+    assert_eq!(diagnostic.code(), "conflict");
+    assert_eq!((diagnostic.start_idx, diagnostic.end_idx), (0, 22));
+    assert_eq!(diagnostic.token, "6pm");
+    assert!(diagnostic.suggestions.is_empty());
+
+    // language functions lose positions, errors are looked up in the input
+    let parser = Parser::new(chrono_tz::Europe::Moscow).parser(Box::new(&when::en));
+    let res = parser.parse_matches_fixed_time(fixed_time(), "meet me on 30th of february");
+    let diagnostic = res[0].as_ref().unwrap_err();
+    assert_eq!((diagnostic.start_idx, diagnostic.end_idx), (11, 27));
+    assert!(diagnostic.suggestions.is_empty());
+}
+
+#[test]
+fn test_offsets() {
+    // "İ" takes 2 bytes and its lowercase form 3, offsets point into the input anyway
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    let input = "İİİİ Tomorrow at 5pm";
+    let res = parser.parse_matches_fixed_time(fixed_time(), input);
+    let m = res[0].as_ref().unwrap();
+    assert_eq!((m.start_idx, m.end_idx), (9, 24));
+    assert_eq!(&input[m.start_idx..m.end_idx], "Tomorrow at 5pm");
+
+    let input = "İİ Next Fridy";
+    let res = parser.parse_matches_fixed_time(fixed_time(), input);
+    let warning = &res[0].as_ref().unwrap().warnings[0];
+    assert_eq!(&input[warning.start_idx..warning.end_idx], "Fridy");
+
+    let input = "İİ 30th of February";
+    let res = parser.parse_matches_fixed_time(fixed_time(), input);
+    let diagnostic = res[0].as_ref().unwrap_err();
+    assert_eq!(
+        &input[diagnostic.start_idx..diagnostic.end_idx],
+        "30th of February"
+    );
+
+    let input = "İİ Next Fridy at 5pm";
+    let explanation = parser.explain_fixed_time(fixed_time(), input);
+    assert_eq!(explanation.input, input);
+    let group = &explanation.groups[0];
+    assert_eq!(
+        (group.start_idx, group.end_idx, group.text.as_str()),
+        (5, 22, "Next Fridy at 5pm")
+    );
+    assert_eq!(group.parts, vec![(5, 15), (19, 22)]);
+    let weekdays = explanation
+        .rules
+        .iter()
+        .find(|rule| rule.name == "en::weekdays")
+        .unwrap();
+    assert_eq!(weekdays.matches[0].text, "Next Fridy");
+
+    let alternatives = parser.alternatives_fixed_time(fixed_time(), "İİ Tomorrow");
+    assert_eq!(
+        (alternatives[0].start_idx, alternatives[0].end_idx),
+        (5, 13)
+    );
+}
+
+#[test]
+fn test_warnings() {
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    let warnings = |input| -> Vec<(String, usize, usize)> {
+        parser.parse_matches_fixed_time(fixed_time(), input)[0]
+            .as_ref()
+            .unwrap()
+            .warnings
+            .iter()
+            .map(|warning| (warning.to_string(), warning.start_idx, warning.end_idx))
+            .collect()
+    };
+
+    // march 5, 2018 is monday
+    let res = parser.parse_matches_fixed_time(fixed_time(), "sunday, march 5");
+    let m = res[0].as_ref().unwrap();
+    assert_eq!(
+        m.date_time.naive_local().date(),
+        NaiveDate::from_ymd(2018, 3, 5)
+    );
+    assert_eq!(
+        m.warnings[0].kind,
+        WarningKind::WeekdayMismatch {
+            stated: "sunday".to_owned(),
+            actual: "monday".to_owned()
+        }
+    );
+    assert_eq!(
+        warnings("sunday, march 5"),
+        vec![
+            ("the date is monday, not sunday".to_owned(), 0, 6),
+            ("assumed year 2018".to_owned(), 8, 15)
+        ]
+    );
+    assert_eq!(
+        warnings("sunday, march 4"),
+        vec![("assumed year 2018".to_owned(), 8, 15)]
+    );
+
+    // tuesday has passed this week, so it's ambiguous on its own, but not next to a date
+    assert_eq!(
+        warnings("Tuesday, March 5"),
+        vec![
+            ("the date is monday, not tuesday".to_owned(), 0, 7),
+            ("assumed year 2018".to_owned(), 9, 16)
+        ]
+    );
+    let res = parser.parse_fixed_time(fixed_time(), "March 5, Tuesday");
+    assert_eq!(res.len(), 1);
+    assert_eq!(
+        res[0].as_ref().unwrap().naive_local().date(),
+        NaiveDate::from_ymd(2018, 3, 5)
+    );
+    let res = parser.parse_fixed_time(fixed_time(), "tuesday at 5pm");
+    assert_eq!(res.len(), 2);
+    assert_eq!(
+        res[0],
+        Err(when::DateTimeError::AmbiguousTime {
+            msg: "tuesday".to_owned()
+        })
+    );
+
+    assert_eq!(
+        warnings("next fridy"),
+        vec![("assumed \"friday\" for \"fridy\"".to_owned(), 5, 10)]
+    );
+    assert_eq!(
+        warnings("tomorrow evening"),
+        vec![("assumed 18:00".to_owned(), 0, 16)]
+    );
+    assert_eq!(
+        warnings("week 42, march 5"),
+        vec![
+            ("assumed year 2018".to_owned(), 0, 7),
+            ("ignored month".to_owned(), 9, 16),
+            ("ignored day".to_owned(), 9, 16)
+        ]
+    );
+    assert!(warnings("tomorrow at 5pm").is_empty());
+}
+
+#[test]
+fn test_explain() {
+    let parser = Parser::new(chrono_tz::Europe::Moscow);
+    let explanation =
+        parser.explain_fixed_time(fixed_time(), "next fridy at 5pm, march 13 seconds ago");
+
+    // every rule is listed, even the ones which didn't match
+    assert!(explanation
+        .rules
+        .iter()
+        .any(|rule| rule.name == "en::deadline"));
+    let weekdays = explanation
+        .rules
+        .iter()
+        .find(|rule| rule.name == "en::weekdays")
+        .unwrap();
+    let m = &weekdays.matches[0];
+    assert_eq!(
+        (m.start_idx, m.end_idx, m.text.as_str()),
+        (0, 10, "next fridy")
+    );
+    let tokens: Vec<_> = m
+        .tokens
+        .iter()
+        .map(|token| (token.token.as_str(), token.priority, token.dist))
+        .collect();
+    assert_eq!(
+        tokens,
+        vec![("When(Next)", 1, 0), ("Weekday(Friday)", 0, 1)]
+    );
+
+    // "march 13" loses to "13 seconds ago"
+    assert!(explanation
+        .removed
+        .iter()
+        .any(|removal| removal.text == "march 13" && removal.reason == RemovalReason::NotSelected));
+
+    assert_eq!(explanation.groups.len(), 2);
+    let group = &explanation.groups[0];
+    assert_eq!(group.text, "next fridy at 5pm");
+    assert_eq!(group.parts, vec![(0, 10), (14, 17)]);
+    assert_eq!(group.context["hour"], "17");
+    assert_eq!(
+        group.date_time.as_deref(),
+        Some("2018-08-10T17:00:00+03:00")
+    );
+
+    let tree = explanation.to_string();
+    assert!(tree.contains("  0..17 \"next fridy at 5pm\" -> 2018-08-10T17:00:00+03:00"));
+    let json: serde_json::Value = serde_json::from_str(&explanation.to_json()).unwrap();
+    assert_eq!(json["groups"][1]["text"], "13 seconds ago");
+    assert!(json["removed"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|removal| removal["reason"] == "not_selected"));
+
+    // weak words are dropped before the candidates compete
+    let explanation = parser.explain_fixed_time(fixed_time(), "you may go");
+    assert_eq!(explanation.removed[0].text, "may");
+    assert_eq!(explanation.removed[0].reason, RemovalReason::Weak);
+    assert!(explanation.groups.is_empty());
+}
+
+#[test]
+fn test_shared_parser() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Parser<chrono_tz::Tz>>();
+
+    let parser = std::sync::Arc::new(
+        Parser::new(chrono_tz::Europe::Moscow)
+            .languages(&[Language::English, Language::Russian])
+            .holiday_calendar(Box::new(
+                Calendar::new()
+                    .holiday(&["founders day"], HolidayDate::Fixed { month: 6, day: 12 }),
+            ))
+            .typo_model(Keyboard)
+            .fuzzy_parse(true),
+    );
+
+    let threads: Vec<_> = (0..4)
+        .map(|i| {
+            let parser = parser.clone();
+            std::thread::spawn(move || {
+                // inputs don't outlive the call
+                let input = format!("call me in {} days", i + 1);
+                let result = parser.parse_fixed_time(fixed_time(), &input);
+                let holiday = parser.parse_fixed_time(fixed_time(), &"founders day".to_string());
+                (result, holiday)
+            })
+        })
+        .collect();
+
+    for (i, thread) in threads.into_iter().enumerate() {
+        let (result, holiday) = thread.join().unwrap();
+        let expected = chrono_tz::Europe::Moscow
+            .ymd(2018, 8, 4 + i as u32)
+            .and_hms(15, 34, 0);
+        assert_eq!(result, vec![Ok(expected)]);
+        assert_eq!(
+            holiday,
+            vec![Ok(chrono_tz::Europe::Moscow
+                .ymd(2018, 6, 12)
+                .and_hms(15, 34, 0))]
+        );
+    }
+
+    // language functions are accepted as well as closures
+    let parser = Parser::new(chrono_tz::Europe::Moscow)
+        .parser(|now, input: &str, exact_match| when::ru(now, input, exact_match));
+    let input = String::from("завтра");
+    assert_eq!(parser.parse_fixed_time(fixed_time(), &input).len(), 1);
 }